@@ -0,0 +1,183 @@
+// The lexer as it was before it moved to bytes, cut down to RFC 8259 and
+// kept only as a yardstick for the benchmarks: it walks `Peekable<Chars>`
+// one `char` at a time, counts lines and columns as it goes and builds
+// numbers up in a `String`.
+
+use std::iter::Peekable;
+use std::str::Chars;
+use basic_json_parser::{JsonNumber, JsonValue};
+
+enum Token {
+    CurlyLeft,
+    CurlyRight,
+    SquareLeft,
+    SquareRight,
+    Colon,
+    Comma,
+    String(String),
+    Number(JsonNumber),
+    True,
+    False,
+    Null,
+    Eof,
+}
+
+struct Lexer<'a> {
+    chars: Peekable<Chars<'a>>,
+    line: usize,
+    col: usize,
+    offset: usize,
+}
+
+// Where the error happened; the benchmarks only parse valid input.
+type Error = (usize, usize, usize);
+
+impl Lexer<'_> {
+    fn error(&self) -> Error {
+        (self.offset, self.line, self.col)
+    }
+
+    fn advance(&mut self) -> Option<char> {
+        let c = self.chars.next()?;
+        self.offset += c.len_utf8();
+        if c == '\n' {
+            self.line += 1;
+            self.col = 1;
+        } else {
+            self.col += 1;
+        }
+        Some(c)
+    }
+
+    fn next_token(&mut self) -> Result<Token, Error> {
+        while let Some(' ' | '\t' | '\n' | '\r') = self.chars.peek() {
+            self.advance();
+        }
+        let token = match self.chars.peek() {
+            None => return Ok(Token::Eof),
+            Some('"') => return self.string(),
+            Some(c) if c.is_ascii_digit() || *c == '-' => return self.number(),
+            Some('{') => Token::CurlyLeft,
+            Some('}') => Token::CurlyRight,
+            Some('[') => Token::SquareLeft,
+            Some(']') => Token::SquareRight,
+            Some(':') => Token::Colon,
+            Some(',') => Token::Comma,
+            Some('t') => return self.literal("true", Token::True),
+            Some('f') => return self.literal("false", Token::False),
+            Some('n') => return self.literal("null", Token::Null),
+            Some(_) => return Err(self.error()),
+        };
+        self.advance();
+        Ok(token)
+    }
+
+    fn string(&mut self) -> Result<Token, Error> {
+        self.advance();
+        let mut string = String::new();
+        loop {
+            match self.advance().ok_or_else(|| self.error())? {
+                '"' => return Ok(Token::String(string)),
+                '\\' => {
+                    let escaped = match self.advance().ok_or_else(|| self.error())? {
+                        'u' => {
+                            let mut hex = String::with_capacity(4);
+                            for _ in 0..4 {
+                                hex.push(self.advance().ok_or_else(|| self.error())?);
+                            }
+                            let unit = u32::from_str_radix(&hex, 16).map_err(|_| self.error())?;
+                            char::from_u32(unit).unwrap_or(char::REPLACEMENT_CHARACTER)
+                        }
+                        'b' => '\u{8}',
+                        'f' => '\u{c}',
+                        'n' => '\n',
+                        'r' => '\r',
+                        't' => '\t',
+                        c => c,
+                    };
+                    string.push(escaped);
+                }
+                c if c < '\u{20}' => return Err(self.error()),
+                c => string.push(c),
+            }
+        }
+    }
+
+    fn number(&mut self) -> Result<Token, Error> {
+        let mut text = String::new();
+        while let Some(&c) = self.chars.peek()
+            && (c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E'))
+        {
+            text.push(c);
+            self.advance();
+        }
+        let number = match text.parse() {
+            Ok(i) => JsonNumber::Integer(i),
+            Err(_) => JsonNumber::Float(text.parse().map_err(|_| self.error())?),
+        };
+        Ok(Token::Number(number))
+    }
+
+    fn literal(&mut self, expected: &str, token: Token) -> Result<Token, Error> {
+        for c in expected.chars() {
+            if self.advance() != Some(c) {
+                return Err(self.error());
+            }
+        }
+        Ok(token)
+    }
+}
+
+pub fn deserialize(input: &str) -> Result<JsonValue, Error> {
+    let mut lexer = Lexer { chars: input.chars().peekable(), line: 1, col: 1, offset: 0 };
+    let token = lexer.next_token()?;
+    let value = value(&mut lexer, token)?;
+    match lexer.next_token()? {
+        Token::Eof => Ok(value),
+        _ => Err(lexer.error()),
+    }
+}
+
+fn value(lexer: &mut Lexer, token: Token) -> Result<JsonValue, Error> {
+    Ok(match token {
+        Token::Null => JsonValue::Null,
+        Token::True => JsonValue::Bool(true),
+        Token::False => JsonValue::Bool(false),
+        Token::Number(n) => JsonValue::Number(n),
+        Token::String(s) => JsonValue::String(s),
+        Token::SquareLeft => {
+            let mut elements = Vec::new();
+            let mut token = lexer.next_token()?;
+            if !matches!(token, Token::SquareRight) {
+                loop {
+                    elements.push(value(lexer, token)?);
+                    match lexer.next_token()? {
+                        Token::Comma => token = lexer.next_token()?,
+                        Token::SquareRight => break,
+                        _ => return Err(lexer.error()),
+                    }
+                }
+            }
+            JsonValue::Array(elements)
+        }
+        Token::CurlyLeft => {
+            let mut pairs = Vec::new();
+            let mut token = lexer.next_token()?;
+            if !matches!(token, Token::CurlyRight) {
+                loop {
+                    let Token::String(key) = token else { return Err(lexer.error()) };
+                    let Token::Colon = lexer.next_token()? else { return Err(lexer.error()) };
+                    let next = lexer.next_token()?;
+                    pairs.push((key, value(lexer, next)?));
+                    match lexer.next_token()? {
+                        Token::Comma => token = lexer.next_token()?,
+                        Token::CurlyRight => break,
+                        _ => return Err(lexer.error()),
+                    }
+                }
+            }
+            JsonValue::Object(pairs)
+        }
+        _ => return Err(lexer.error()),
+    })
+}
@@ -0,0 +1,71 @@
+// Documents shared by the benchmarks: `input.json` and generated ones of a
+// few MB.
+
+use basic_json_parser::{to_string, to_string_pretty, JsonNumber, JsonValue};
+
+// A small deterministic generator, so every run parses the same documents.
+struct Random(u64);
+
+impl Random {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        self.0 >> 33
+    }
+
+    fn word(&mut self) -> String {
+        let len = 3 + self.next() % 10;
+        (0..len).map(|_| char::from(b'a' + (self.next() % 26) as u8)).collect()
+    }
+}
+
+// Objects of mixed members, as an API response or log dump would hold.
+fn records(count: usize) -> JsonValue {
+    let mut random = Random(1);
+    let records = (0..count).map(|id| {
+        let tags = (0..random.next() % 5).map(|_| JsonValue::String(random.word())).collect();
+        JsonValue::Object(vec![
+            ("id".to_string(), JsonValue::Number(JsonNumber::Integer(id as i64))),
+            ("name".to_string(), JsonValue::String(format!("{} {}", random.word(), random.word()))),
+            ("score".to_string(), JsonValue::Number(JsonNumber::Float(random.next() as f64 / 1e4))),
+            ("active".to_string(), JsonValue::Bool(random.next().is_multiple_of(2))),
+            ("parent".to_string(), JsonValue::Null),
+            ("tags".to_string(), JsonValue::Array(tags)),
+        ])
+    });
+    JsonValue::Array(records.collect())
+}
+
+// Long strings, a few of them with escapes and non-ASCII text.
+fn strings(count: usize) -> JsonValue {
+    let mut random = Random(2);
+    let strings = (0..count).map(|i| {
+        let mut text: Vec<String> = (0..40).map(|_| random.word()).collect();
+        match i % 8 {
+            0 => text.push("\"quoted\"\n".to_string()),
+            1 => text.push("grüße ☺".to_string()),
+            _ => {}
+        }
+        JsonValue::String(text.join(" "))
+    });
+    JsonValue::Array(strings.collect())
+}
+
+fn numbers(count: usize) -> JsonValue {
+    let mut random = Random(3);
+    let numbers = (0..count).map(|i| match i % 3 {
+        0 => JsonNumber::Integer(random.next() as i64 - (1 << 30)),
+        1 => JsonNumber::Float(random.next() as f64 / 7.0),
+        _ => JsonNumber::Float((random.next() as f64 * 1e10).recip()),
+    });
+    JsonValue::Array(numbers.map(JsonValue::Number).collect())
+}
+
+pub fn corpora() -> Vec<(&'static str, String)> {
+    vec![
+        ("input.json", include_str!("../../input.json").to_string()),
+        ("records", to_string(&records(20_000))),
+        ("records pretty", to_string_pretty(&records(20_000))),
+        ("strings", to_string(&strings(10_000))),
+        ("numbers", to_string(&numbers(200_000))),
+    ]
+}
@@ -0,0 +1,89 @@
+// Cost of parsing into a `Document` tape and of reading it back, against
+// the `JsonValue` tree `deserialize` builds, and of looking one value up in
+// each.
+//
+//     cargo bench --bench document
+
+mod corpus;
+
+use std::hint::black_box;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use basic_json_parser::{deserialize, deserialize_document, to_string, JsonNumber, JsonValue, Node};
+
+// Visits every value, adding up what a query over all of them would read;
+// strings are looked into, not just measured.
+fn text(s: &str) -> f64 {
+    (s.len() + usize::from(s.bytes().next().unwrap_or(0))) as f64
+}
+
+fn walk_value(value: &JsonValue) -> f64 {
+    match value {
+        JsonValue::Array(items) => items.iter().map(walk_value).sum(),
+        JsonValue::Object(pairs) => pairs.iter().map(|(name, value)| text(name) + walk_value(value)).sum(),
+        JsonValue::String(s) => text(s),
+        JsonValue::Number(n) => n.as_f64(),
+        _ => 1.0,
+    }
+}
+
+fn walk_node(node: Node) -> f64 {
+    if node.is_array() {
+        node.elements().map(walk_node).sum()
+    } else if node.is_object() {
+        node.members().map(|(name, value)| text(name) + walk_node(value)).sum()
+    } else if let Some(s) = node.as_str() {
+        text(s)
+    } else {
+        node.as_f64().unwrap_or(1.0)
+    }
+}
+
+fn parse(c: &mut Criterion) {
+    for (name, input) in corpus::corpora() {
+        let mut group = c.benchmark_group(format!("document {}", name));
+        group.throughput(Throughput::Bytes(input.len() as u64));
+        group.bench_with_input(BenchmarkId::new("deserialize", name), &input, |b, input| {
+            b.iter(|| deserialize(input).unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("deserialize_document", name), &input, |b, input| {
+            b.iter(|| deserialize_document(input).unwrap())
+        });
+
+        let value = deserialize(&input).unwrap();
+        let document = deserialize_document(&input).unwrap();
+        assert_eq!(walk_value(&value).to_bits(), walk_node(document.root()).to_bits());
+        group.bench_function(BenchmarkId::new("walk JsonValue", name), |b| b.iter(|| walk_value(black_box(&value))));
+        group.bench_function(BenchmarkId::new("walk Document", name), |b| b.iter(|| walk_node(black_box(&document).root())));
+        group.bench_function(BenchmarkId::new("Document::to_value", name), |b| b.iter(|| document.to_value()));
+        group.finish();
+    }
+}
+
+// `Node::get_index` and `Node::get` step over everything before the value
+// they find, so these read the last element or member.
+fn lookup(c: &mut Criterion) {
+    let members = (0..10_000).map(|i| (format!("key{}", i), JsonValue::Number(JsonNumber::Integer(i))));
+    let object = ("object", to_string(&JsonValue::Object(members.collect())));
+    for (name, input) in corpus::corpora().into_iter().chain([object]) {
+        let value = deserialize(&input).unwrap();
+        let document = deserialize_document(&input).unwrap();
+        let root = document.root();
+        let mut group = c.benchmark_group(format!("lookup {}", name));
+        if let Some(last) = root.len().checked_sub(1).filter(|_| root.is_array()) {
+            group.bench_function(BenchmarkId::new("JsonValue::get_index", name), |b| {
+                b.iter(|| black_box(&value).get_index(black_box(last)).is_some())
+            });
+            group.bench_function(BenchmarkId::new("Node::get_index", name), |b| {
+                b.iter(|| black_box(root).get_index(black_box(last)).is_some())
+            });
+        }
+        if let Some((key, _)) = root.members().last() {
+            group.bench_function(BenchmarkId::new("JsonValue::get", name), |b| b.iter(|| black_box(&value).get(black_box(key)).is_some()));
+            group.bench_function(BenchmarkId::new("Node::get", name), |b| b.iter(|| black_box(root).get(black_box(key)).is_some()));
+        }
+        group.finish();
+    }
+}
+
+criterion_group!(benches, parse, lookup);
+criterion_main!(benches);
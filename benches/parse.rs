@@ -0,0 +1,36 @@
+// Parsing throughput of each front end against the char-at-a-time lexer
+// it replaced, on `input.json` and generated documents of a few MB.
+//
+//     cargo bench --bench parse
+
+mod baseline;
+mod corpus;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use basic_json_parser::{deserialize, deserialize_borrowed, deserialize_reader, JsonReader};
+
+fn parse(c: &mut Criterion) {
+    for (name, input) in corpus::corpora() {
+        let mut group = c.benchmark_group(name);
+        group.throughput(Throughput::Bytes(input.len() as u64));
+        group.bench_with_input(BenchmarkId::new("char lexer", name), &input, |b, input| {
+            b.iter(|| baseline::deserialize(input).unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("deserialize", name), &input, |b, input| {
+            b.iter(|| deserialize(input).unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("deserialize_borrowed", name), &input, |b, input| {
+            b.iter(|| deserialize_borrowed(input).unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("deserialize_reader", name), &input, |b, input| {
+            b.iter(|| deserialize_reader(input.as_bytes()).unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("JsonReader", name), &input, |b, input| {
+            b.iter(|| JsonReader::new(input).map(Result::unwrap).count())
+        });
+        group.finish();
+    }
+}
+
+criterion_group!(benches, parse);
+criterion_main!(benches);
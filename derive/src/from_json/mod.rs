@@ -0,0 +1,270 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::Ident;
+use crate::model::{Body, Container, DefaultValue, Field, Shape, Tagging};
+
+pub(crate) fn expand(container: &Container) -> TokenStream {
+    let ident = container.ident;
+    let generics = container.bounded(quote!(::basic_json_parser::FromJson));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let value = format_ident!("__value");
+    let body = match &container.body {
+        Body::Struct(shape) => construct(quote!(Self), shape, &value),
+        Body::Enum { tagging, variants } => {
+            let names = variants.iter().map(|variant| &variant.name);
+            let variants_const = quote!(const VARIANTS: &[&str] = &[#(#names),*];);
+            match tagging {
+                Tagging::External => {
+                    let payload = format_ident!("__payload");
+                    let units = variants.iter().filter(|v| matches!(v.shape, Shape::Unit)).map(|variant| {
+                        let (ident, name) = (variant.ident, &variant.name);
+                        quote!(#name => ::std::result::Result::Ok(Self::#ident),)
+                    });
+                    let arms = variants.iter().map(|variant| {
+                        let (ident, name) = (variant.ident, &variant.name);
+                        let construct = construct(quote!(Self::#ident), &variant.shape, &payload);
+                        quote!(#name => #construct.map_err(|error| error.at_key(#name)),)
+                    });
+                    quote! {
+                        #variants_const
+                        match #value {
+                            ::basic_json_parser::JsonValue::String(__name) => match __name.as_str() {
+                                #(#units)*
+                                __name if VARIANTS.contains(&__name) => ::std::result::Result::Err(
+                                    ::basic_json_parser::FromJsonError::invalid_type("an object with one member", #value),
+                                ),
+                                __name => ::std::result::Result::Err(::basic_json_parser::FromJsonError::unknown_variant(__name, VARIANTS)),
+                            },
+                            ::basic_json_parser::JsonValue::Object(__members) if __members.len() == 1 => {
+                                let (__name, #payload) = &__members[0];
+                                match __name.as_str() {
+                                    #(#arms)*
+                                    __name => ::std::result::Result::Err(
+                                        ::basic_json_parser::FromJsonError::unknown_variant(__name, VARIANTS).at_key(__name),
+                                    ),
+                                }
+                            }
+                            _ => ::std::result::Result::Err(
+                                ::basic_json_parser::FromJsonError::invalid_type("a string or an object with one member", #value),
+                            ),
+                        }
+                    }
+                }
+                Tagging::Internal { tag } | Tagging::Adjacent { tag, .. } => {
+                    let arms = variants.iter().map(|variant| {
+                        let (ident, name) = (variant.ident, &variant.name);
+                        let ctor = quote!(Self::#ident);
+                        let construct = match tagging {
+                            _ if variant.shape.fields().iter().all(|field| field.skip) => {
+                                let init = initialize(&ctor, &variant.shape, &value);
+                                quote!(::std::result::Result::Ok(#init))
+                            }
+                            Tagging::Adjacent { content, .. } => {
+                                let payload = format_ident!("__content");
+                                let construct = construct(ctor, &variant.shape, &payload);
+                                quote!(match #value.get(#content) {
+                                    ::std::option::Option::Some(#payload) => #construct.map_err(|error| error.at_key(#content)),
+                                    ::std::option::Option::None => {
+                                        ::std::result::Result::Err(::basic_json_parser::FromJsonError::missing_field(#content))
+                                    }
+                                })
+                            }
+                            _ if variant.shape.fields().iter().any(|field| field.flatten) => {
+                                // Keep the tag out of the flattened members.
+                                let fields = format_ident!("__fields");
+                                let (without, construct) = (without(&value, [tag.as_str()].into_iter()), construct(ctor, &variant.shape, &fields));
+                                quote!({
+                                    let #fields = &#without;
+                                    #construct
+                                })
+                            }
+                            _ => construct(ctor, &variant.shape, &value),
+                        };
+                        quote!(#name => #construct,)
+                    });
+                    quote! {
+                        #variants_const
+                        let __tag = match #value.get(#tag) {
+                            ::std::option::Option::Some(::basic_json_parser::JsonValue::String(__tag)) => __tag.as_str(),
+                            ::std::option::Option::Some(__tag) => {
+                                return ::std::result::Result::Err(
+                                    ::basic_json_parser::FromJsonError::invalid_type("a string", __tag).at_key(#tag),
+                                );
+                            }
+                            ::std::option::Option::None if #value.is_object() => {
+                                return ::std::result::Result::Err(::basic_json_parser::FromJsonError::missing_field(#tag));
+                            }
+                            ::std::option::Option::None => {
+                                return ::std::result::Result::Err(
+                                    ::basic_json_parser::FromJsonError::invalid_type("an object", #value),
+                                );
+                            }
+                        };
+                        match __tag {
+                            #(#arms)*
+                            __tag => ::std::result::Result::Err(
+                                ::basic_json_parser::FromJsonError::unknown_variant(__tag, VARIANTS).at_key(#tag),
+                            ),
+                        }
+                    }
+                }
+                Tagging::Untagged => {
+                    let attempts = variants.iter().map(|variant| {
+                        let ident = variant.ident;
+                        let construct = construct(quote!(Self::#ident), &variant.shape, &value);
+                        quote! {
+                            if let ::std::result::Result::Ok(__variant) = #construct {
+                                return ::std::result::Result::Ok(__variant);
+                            }
+                        }
+                    });
+                    let message = format!("data did not match any variant of untagged enum {}", ident);
+                    quote! {
+                        #(#attempts)*
+                        ::std::result::Result::Err(::basic_json_parser::FromJsonError::custom(#message))
+                    }
+                }
+            }
+        }
+    };
+    quote! {
+        impl #impl_generics ::basic_json_parser::FromJson for #ident #ty_generics #where_clause {
+            fn from_json(#value: &::basic_json_parser::JsonValue) -> ::std::result::Result<Self, ::basic_json_parser::FromJsonError> {
+                #body
+            }
+        }
+    }
+}
+
+// A block evaluating to `Result<Self, FromJsonError>` that builds `ctor` out
+// of `value`, with error paths relative to `value`.
+fn construct(ctor: TokenStream, shape: &Shape, value: &Ident) -> TokenStream {
+    let check = match shape {
+        Shape::Unit => quote! {
+            if let ::std::result::Result::Err(error) = <() as ::basic_json_parser::FromJson>::from_json(#value) {
+                break '__construct ::std::result::Result::Err(error);
+            }
+        },
+        Shape::Tuple(fields) if fields.len() == 1 => quote!(),
+        Shape::Tuple(fields) => {
+            let len = fields.len();
+            // An empty tuple reads none of the elements.
+            let items = if len == 0 { quote!(_) } else { quote!(__items) };
+            quote! {
+                let #items = match #value.as_array() {
+                    ::std::option::Option::Some(__items) if __items.len() == #len => __items,
+                    ::std::option::Option::Some(__items) => break '__construct ::std::result::Result::Err(
+                        ::basic_json_parser::FromJsonError::new(::basic_json_parser::FromJsonErrorKind::InvalidLength {
+                            expected: #len,
+                            found: __items.len(),
+                        }),
+                    ),
+                    ::std::option::Option::None => break '__construct ::std::result::Result::Err(
+                        ::basic_json_parser::FromJsonError::invalid_type("an array", #value),
+                    ),
+                };
+            }
+        }
+        Shape::Named(_) => quote! {
+            if !#value.is_object() {
+                break '__construct ::std::result::Result::Err(::basic_json_parser::FromJsonError::invalid_type("an object", #value));
+            }
+        },
+    };
+    let init = initialize(&ctor, shape, value);
+    quote!('__construct: {
+        #check
+        ::std::result::Result::Ok(#init)
+    })
+}
+
+// The expression building `ctor`, once `construct` has checked the shape of
+// `value`; it breaks out of `'__construct` when a field fails.
+fn initialize(ctor: &TokenStream, shape: &Shape, value: &Ident) -> TokenStream {
+    match shape {
+        Shape::Unit => ctor.clone(),
+        Shape::Tuple(fields) if fields.len() == 1 => {
+            let read = read(fields[0].ty, quote!(#value), quote!(error));
+            quote!(#ctor(#read))
+        }
+        Shape::Tuple(fields) => {
+            let reads = fields.iter().enumerate().map(|(index, field)| {
+                read(field.ty, quote!(&__items[#index]), quote!(error.at_index(#index)))
+            });
+            quote!(#ctor(#(#reads),*))
+        }
+        Shape::Named(fields) if fields.iter().any(|field| field.flatten) => {
+            // Flattened fields read the members no other field claimed.
+            let names = fields.iter().filter(|field| !field.skip && !field.flatten).map(|field| field.name.as_str());
+            let rest = format_ident!("__rest");
+            let without = without(value, names);
+            let inits = fields.iter().map(|field| {
+                let member = &field.member;
+                let value = named_field(field, if field.flatten { &rest } else { value });
+                quote!(#member: #value)
+            });
+            quote!({
+                let #rest = &#without;
+                #ctor { #(#inits),* }
+            })
+        }
+        Shape::Named(fields) => {
+            let inits = fields.iter().map(|field| {
+                let member = &field.member;
+                let value = named_field(field, value);
+                quote!(#member: #value)
+            });
+            quote!(#ctor { #(#inits),* })
+        }
+    }
+}
+
+// A copy of the object `value` without the members called `names`.
+fn without<'a>(value: &Ident, names: impl Iterator<Item = &'a str>) -> TokenStream {
+    quote!(::basic_json_parser::JsonValue::Object(
+        #value
+            .as_object()
+            .into_iter()
+            .flatten()
+            .filter(|(__name, _)| ![#(#names),*].contains(&__name.as_str()))
+            .cloned()
+            .collect(),
+    ))
+}
+
+fn named_field(field: &Field, value: &Ident) -> TokenStream {
+    let (ty, name) = (field.ty, &field.name);
+    let default = match &field.default {
+        Some(DefaultValue::Trait) => Some(quote!(::std::default::Default::default())),
+        Some(DefaultValue::Function(path)) => Some(quote!(#path())),
+        None => None,
+    };
+    if field.skip {
+        return default.unwrap_or_else(|| quote!(::std::default::Default::default()));
+    }
+    if field.flatten {
+        return read(ty, quote!(#value), quote!(error));
+    }
+    let missing = default.unwrap_or_else(|| {
+        quote!(match <#ty as ::basic_json_parser::FromJson>::from_missing() {
+            ::std::option::Option::Some(__missing) => __missing,
+            ::std::option::Option::None => {
+                break '__construct ::std::result::Result::Err(::basic_json_parser::FromJsonError::missing_field(#name));
+            }
+        })
+    });
+    let read = read(ty, quote!(__member), quote!(error.at_key(#name)));
+    quote!(match #value.get(#name) {
+        ::std::option::Option::Some(__member) => #read,
+        ::std::option::Option::None => #missing,
+    })
+}
+
+// Reads a `ty` out of `value`, breaking out of `'__construct` with `error`
+// mapped through `wrap` on failure.
+fn read(ty: &syn::Type, value: TokenStream, wrap: TokenStream) -> TokenStream {
+    quote!(match <#ty as ::basic_json_parser::FromJson>::from_json(#value) {
+        ::std::result::Result::Ok(__field) => __field,
+        ::std::result::Result::Err(error) => break '__construct ::std::result::Result::Err(#wrap),
+    })
+}
@@ -0,0 +1,49 @@
+//! `#[derive(ToJson, FromJson)]` for `basic_json_parser`, re-exported by its
+//! `derive` feature.
+//!
+//! Structs with named fields become objects, tuple structs arrays, newtypes
+//! their single field and unit structs `null`. Enums are externally tagged
+//! unless the container says otherwise.
+//!
+//! Container attributes, on enums only:
+//! - `#[json(tag = "type")]`: the variant name is a member of the object
+//!   next to the fields; unit and struct variants only.
+//! - `#[json(tag = "t", content = "c")]`: the variant name and its payload
+//!   are two members of one object.
+//! - `#[json(untagged)]`: the payload alone; reading tries each variant in
+//!   declaration order.
+//!
+//! Variant attributes: `#[json(rename = "name")]`.
+//!
+//! Field attributes, on named fields only:
+//! - `#[json(rename = "name")]`: the member name in JSON.
+//! - `#[json(default)]` or `#[json(default = "path::to::fn")]`: the value
+//!   when the member is missing.
+//! - `#[json(skip)]`: never written, and read as its default.
+//! - `#[json(flatten)]`: the field's members are written into the
+//!   surrounding object, and it reads back the members no other field claims.
+
+use proc_macro::TokenStream;
+use syn::{parse_macro_input, DeriveInput};
+
+mod from_json;
+mod model;
+mod to_json;
+
+#[proc_macro_derive(ToJson, attributes(json))]
+pub fn derive_to_json(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    model::Container::from_input(&input)
+        .map(|container| to_json::expand(&container))
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+#[proc_macro_derive(FromJson, attributes(json))]
+pub fn derive_from_json(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    model::Container::from_input(&input)
+        .map(|container| from_json::expand(&container))
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
@@ -0,0 +1,198 @@
+use proc_macro2::TokenStream;
+use syn::ext::IdentExt;
+use syn::spanned::Spanned;
+use syn::{parse_quote, Attribute, Data, DeriveInput, ExprPath, Fields, Generics, Ident, LitStr, Member, Token, Type};
+
+/// A struct or enum with its `#[json(...)]` attributes read and checked.
+pub(crate) struct Container<'a> {
+    pub ident: &'a Ident,
+    pub generics: &'a Generics,
+    pub body: Body<'a>,
+}
+
+pub(crate) enum Body<'a> {
+    Struct(Shape<'a>),
+    Enum { tagging: Tagging, variants: Vec<Variant<'a>> },
+}
+
+/// How an enum records which variant a value is.
+pub(crate) enum Tagging {
+    /// `{"Variant": payload}`, or `"Variant"` for a unit variant.
+    External,
+    /// `{"<tag>": "Variant", ...fields}`.
+    Internal { tag: String },
+    /// `{"<tag>": "Variant", "<content>": payload}`.
+    Adjacent { tag: String, content: String },
+    /// The payload alone; reading tries each variant in turn.
+    Untagged,
+}
+
+pub(crate) struct Variant<'a> {
+    pub ident: &'a Ident,
+    pub name: String,
+    pub shape: Shape<'a>,
+}
+
+pub(crate) enum Shape<'a> {
+    Unit,
+    /// One element per field; a single field is a newtype.
+    Tuple(Vec<Field<'a>>),
+    Named(Vec<Field<'a>>),
+}
+
+pub(crate) struct Field<'a> {
+    pub member: Member,
+    pub ty: &'a Type,
+    /// Member name in JSON; empty for tuple fields.
+    pub name: String,
+    pub default: Option<DefaultValue>,
+    pub skip: bool,
+    pub flatten: bool,
+}
+
+/// Where a missing or skipped field gets its value.
+pub(crate) enum DefaultValue {
+    Trait,
+    Function(ExprPath),
+}
+
+impl<'a> Container<'a> {
+    pub(crate) fn from_input(input: &'a DeriveInput) -> syn::Result<Self> {
+        let mut tag = None;
+        let mut content = None;
+        let mut untagged = false;
+        for_each_json_attribute(&input.attrs, |meta| {
+            if meta.path.is_ident("tag") {
+                tag = Some(meta.value()?.parse::<LitStr>()?.value());
+            } else if meta.path.is_ident("content") {
+                content = Some(meta.value()?.parse::<LitStr>()?.value());
+            } else if meta.path.is_ident("untagged") {
+                untagged = true;
+            } else {
+                return Err(meta.error("expected `tag`, `content` or `untagged`"));
+            }
+            Ok(())
+        })?;
+
+        let body = match &input.data {
+            Data::Struct(data) => {
+                if tag.is_some() || content.is_some() || untagged {
+                    return Err(syn::Error::new(input.ident.span(), "`tag`, `content` and `untagged` only apply to enums"));
+                }
+                Body::Struct(Shape::from_fields(&data.fields)?)
+            }
+            Data::Enum(data) => {
+                let tagging = match (tag, content, untagged) {
+                    (None, None, false) => Tagging::External,
+                    (Some(tag), None, false) => Tagging::Internal { tag },
+                    (Some(tag), Some(content), false) => Tagging::Adjacent { tag, content },
+                    (None, None, true) => Tagging::Untagged,
+                    (None, Some(_), false) => return Err(syn::Error::new(input.ident.span(), "`content` needs a `tag`")),
+                    _ => return Err(syn::Error::new(input.ident.span(), "`untagged` cannot be combined with `tag` or `content`")),
+                };
+                let variants = data.variants.iter().map(Variant::from_variant).collect::<syn::Result<Vec<_>>>()?;
+                if let Tagging::Internal { .. } = tagging
+                    && let Some(variant) = variants.iter().find(|v| matches!(v.shape, Shape::Tuple(_)))
+                {
+                    return Err(syn::Error::new(
+                        variant.ident.span(),
+                        "internally tagged enums only support unit and struct variants; add `content` for adjacent tagging",
+                    ));
+                }
+                Body::Enum { tagging, variants }
+            }
+            Data::Union(_) => return Err(syn::Error::new(input.ident.span(), "unions are not supported")),
+        };
+        Ok(Self { ident: &input.ident, generics: &input.generics, body })
+    }
+
+    /// The container's generics with `bound` added to every type parameter.
+    pub(crate) fn bounded(&self, bound: TokenStream) -> Generics {
+        let mut generics = self.generics.clone();
+        for param in generics.type_params_mut() {
+            param.bounds.push(parse_quote!(#bound));
+        }
+        generics
+    }
+}
+
+impl<'a> Variant<'a> {
+    fn from_variant(variant: &'a syn::Variant) -> syn::Result<Self> {
+        let mut name = variant.ident.unraw().to_string();
+        for_each_json_attribute(&variant.attrs, |meta| {
+            if meta.path.is_ident("rename") {
+                name = meta.value()?.parse::<LitStr>()?.value();
+                Ok(())
+            } else {
+                Err(meta.error("expected `rename`"))
+            }
+        })?;
+        Ok(Self { ident: &variant.ident, name, shape: Shape::from_fields(&variant.fields)? })
+    }
+}
+
+impl<'a> Shape<'a> {
+    fn from_fields(fields: &'a Fields) -> syn::Result<Self> {
+        let named = matches!(fields, Fields::Named(_));
+        let fields = fields.iter().enumerate().map(|(index, field)| Field::from_field(index, field, named));
+        match fields.collect::<syn::Result<Vec<_>>>()? {
+            fields if fields.is_empty() && !named => Ok(Shape::Unit),
+            fields if named => Ok(Shape::Named(fields)),
+            fields => Ok(Shape::Tuple(fields)),
+        }
+    }
+}
+
+impl Shape<'_> {
+    pub(crate) fn fields(&self) -> &[Field<'_>] {
+        match self {
+            Shape::Unit => &[],
+            Shape::Tuple(fields) | Shape::Named(fields) => fields,
+        }
+    }
+}
+
+impl<'a> Field<'a> {
+    fn from_field(index: usize, field: &'a syn::Field, named: bool) -> syn::Result<Self> {
+        let (member, mut name) = match &field.ident {
+            Some(ident) => (Member::Named(ident.clone()), ident.unraw().to_string()),
+            None => (Member::Unnamed(index.into()), String::new()),
+        };
+        let (mut renamed, mut default, mut skip, mut flatten) = (false, None, false, false);
+        for_each_json_attribute(&field.attrs, |meta| {
+            if meta.path.is_ident("rename") {
+                name = meta.value()?.parse::<LitStr>()?.value();
+                renamed = true;
+            } else if meta.path.is_ident("default") {
+                default = Some(match meta.input.peek(Token![=]) {
+                    true => DefaultValue::Function(meta.value()?.parse::<LitStr>()?.parse()?),
+                    false => DefaultValue::Trait,
+                });
+            } else if meta.path.is_ident("skip") {
+                skip = true;
+            } else if meta.path.is_ident("flatten") {
+                flatten = true;
+            } else {
+                return Err(meta.error("expected `rename`, `default`, `skip` or `flatten`"));
+            }
+            if !named {
+                return Err(meta.error("field attributes only apply to named fields"));
+            }
+            Ok(())
+        })?;
+        if flatten && (renamed || skip || default.is_some()) {
+            return Err(syn::Error::new(field.span(), "`flatten` cannot be combined with other field attributes"));
+        }
+        Ok(Self { member, ty: &field.ty, name, default, skip, flatten })
+    }
+}
+
+fn for_each_json_attribute(
+    attrs: &[Attribute],
+    mut parse: impl FnMut(syn::meta::ParseNestedMeta) -> syn::Result<()>,
+) -> syn::Result<()> {
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("json")) {
+        attr.parse_nested_meta(&mut parse)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,118 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use crate::model::{Body, Container, Field, Shape, Tagging};
+
+pub(crate) fn expand(container: &Container) -> TokenStream {
+    let ident = container.ident;
+    let generics = container.bounded(quote!(::basic_json_parser::ToJson));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let body = match &container.body {
+        Body::Struct(shape) => {
+            let accessors = shape.fields().iter().map(|field| {
+                let member = &field.member;
+                quote!(&self.#member)
+            });
+            payload(shape, &accessors.collect::<Vec<_>>())
+        }
+        Body::Enum { tagging, variants } => {
+            let arms = variants.iter().map(|variant| {
+                let (ident, name) = (variant.ident, &variant.name);
+                let bindings: Vec<_> = (0..variant.shape.fields().len()).map(|i| format_ident!("__field{}", i)).collect();
+                let pattern = match &variant.shape {
+                    Shape::Unit => quote!(Self::#ident),
+                    Shape::Tuple(_) => quote!(Self::#ident(#(#bindings),*)),
+                    Shape::Named(fields) => {
+                        let members = fields.iter().zip(&bindings).map(|(field, binding)| {
+                            let member = &field.member;
+                            if field.skip { quote!(#member: _) } else { quote!(#member: #binding) }
+                        });
+                        quote!(Self::#ident { #(#members),* })
+                    }
+                };
+                let accessors: Vec<_> = bindings.iter().map(|binding| quote!(#binding)).collect();
+                let tagged = match tagging {
+                    Tagging::External if matches!(variant.shape, Shape::Unit) => string(name),
+                    Tagging::External => {
+                        let payload = payload(&variant.shape, &accessors);
+                        quote!(::basic_json_parser::JsonValue::Object(::std::vec![(::std::string::String::from(#name), #payload)]))
+                    }
+                    Tagging::Internal { tag } | Tagging::Adjacent { tag, .. } if variant.shape.fields().iter().all(|field| field.skip) => {
+                        let tag_value = string(name);
+                        quote!(::basic_json_parser::JsonValue::Object(::std::vec![(::std::string::String::from(#tag), #tag_value)]))
+                    }
+                    Tagging::Internal { tag } => {
+                        let members = members(variant.shape.fields(), &accessors);
+                        let tag_value = string(name);
+                        quote!({
+                            let mut members = ::std::vec![(::std::string::String::from(#tag), #tag_value)];
+                            #members
+                            ::basic_json_parser::JsonValue::Object(members)
+                        })
+                    }
+                    Tagging::Adjacent { tag, content } => {
+                        let (tag_value, payload) = (string(name), payload(&variant.shape, &accessors));
+                        quote!(::basic_json_parser::JsonValue::Object(::std::vec![
+                            (::std::string::String::from(#tag), #tag_value),
+                            (::std::string::String::from(#content), #payload),
+                        ]))
+                    }
+                    Tagging::Untagged => payload(&variant.shape, &accessors),
+                };
+                quote!(#pattern => #tagged,)
+            });
+            quote!(match self { #(#arms)* })
+        }
+    };
+    quote! {
+        impl #impl_generics ::basic_json_parser::ToJson for #ident #ty_generics #where_clause {
+            fn to_json(&self) -> ::basic_json_parser::JsonValue {
+                #body
+            }
+        }
+    }
+}
+
+fn string(text: &str) -> TokenStream {
+    quote!(::basic_json_parser::JsonValue::String(::std::string::String::from(#text)))
+}
+
+// The value of a struct or variant, reading field `i` through `accessors[i]`.
+fn payload(shape: &Shape, accessors: &[TokenStream]) -> TokenStream {
+    match shape {
+        Shape::Unit => quote!(::basic_json_parser::JsonValue::Null),
+        Shape::Tuple(_) if accessors.len() == 1 => {
+            let accessor = &accessors[0];
+            quote!(::basic_json_parser::ToJson::to_json(#accessor))
+        }
+        Shape::Tuple(_) => quote!(::basic_json_parser::JsonValue::Array(::std::vec![
+            #(::basic_json_parser::ToJson::to_json(#accessors)),*
+        ])),
+        Shape::Named(fields) if fields.iter().all(|field| field.skip) => {
+            quote!(::basic_json_parser::JsonValue::Object(::std::vec::Vec::new()))
+        }
+        Shape::Named(fields) => {
+            let members = members(fields, accessors);
+            quote!({
+                let mut members = ::std::vec::Vec::new();
+                #members
+                ::basic_json_parser::JsonValue::Object(members)
+            })
+        }
+    }
+}
+
+// Statements pushing the members of named `fields` onto `members`.
+fn members(fields: &[Field], accessors: &[TokenStream]) -> TokenStream {
+    let pushes = fields.iter().zip(accessors).filter(|(field, _)| !field.skip).map(|(field, accessor)| {
+        let name = &field.name;
+        if field.flatten {
+            quote!(match ::basic_json_parser::ToJson::to_json(#accessor) {
+                ::basic_json_parser::JsonValue::Object(inner) => members.extend(inner),
+                other => members.push((::std::string::String::from(#name), other)),
+            })
+        } else {
+            quote!(members.push((::std::string::String::from(#name), ::basic_json_parser::ToJson::to_json(#accessor)));)
+        }
+    });
+    quote!(#(#pushes)*)
+}
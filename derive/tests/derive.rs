@@ -0,0 +1,152 @@
+use std::collections::BTreeMap;
+use basic_json_parser::{from_str, to_string, FromJson, FromJsonError, ToJson, Value};
+
+fn read<T: FromJson>(input: &str) -> Result<T, FromJsonError> {
+    T::from_json(&from_str(input).unwrap())
+}
+
+#[derive(Debug, PartialEq, ToJson, FromJson)]
+struct Server {
+    #[json(rename = "listen-port")]
+    port: u16,
+    #[json(default)]
+    hosts: Vec<String>,
+    #[json(default = "default_weight")]
+    weight: f64,
+    owner: Option<String>,
+    #[json(skip)]
+    connections: usize,
+    #[json(flatten)]
+    labels: BTreeMap<String, Value>,
+}
+
+fn default_weight() -> f64 {
+    1.0
+}
+
+#[derive(Debug, PartialEq, ToJson, FromJson)]
+struct Point(i32, i32);
+
+#[derive(Debug, PartialEq, ToJson, FromJson)]
+struct Meters(f64);
+
+#[derive(Debug, PartialEq, ToJson, FromJson)]
+struct Marker;
+
+#[derive(Debug, PartialEq, ToJson, FromJson)]
+struct Wrapper<T> {
+    inner: T,
+}
+
+#[derive(Debug, PartialEq, ToJson, FromJson)]
+enum External {
+    Dot,
+    #[json(rename = "circle")]
+    Circle(f64),
+    Line(Point, Point),
+    Rect { w: u8, h: u8 },
+}
+
+#[derive(Debug, PartialEq, ToJson, FromJson)]
+#[json(tag = "type")]
+enum Internal {
+    Ping,
+    Move { x: i32, y: i32 },
+    Note { id: u8, #[json(flatten)] extra: BTreeMap<String, Value> },
+}
+
+#[derive(Debug, PartialEq, ToJson, FromJson)]
+#[json(tag = "t", content = "c")]
+enum Adjacent {
+    Reset,
+    Set(u8),
+    Pair(u8, u8),
+}
+
+#[derive(Debug, PartialEq, ToJson, FromJson)]
+#[json(untagged)]
+enum Untagged {
+    Number(i64),
+    Text(String),
+    Point { x: i32, y: i32 },
+}
+
+#[test]
+fn test_struct_attributes() {
+    let server = Server {
+        port: 80,
+        hosts: vec!["a".to_string()],
+        weight: 0.5,
+        owner: None,
+        connections: 7,
+        labels: BTreeMap::from([("zone".to_string(), Value::String("eu".to_string()))]),
+    };
+    let json = r#"{"listen-port":80,"hosts":["a"],"weight":0.5,"owner":null,"zone":"eu"}"#;
+    assert_eq!(to_string(&server.to_json()), json);
+    assert_eq!(read::<Server>(json).unwrap(), Server { connections: 0, ..server });
+
+    // Missing members fall back to their defaults; `Option` may be left out.
+    let server = read::<Server>(r#"{"listen-port": 1}"#).unwrap();
+    assert_eq!((server.hosts.len(), server.weight, server.owner), (0, 1.0, None));
+    assert!(server.labels.is_empty());
+}
+
+#[test]
+fn test_struct_shapes() {
+    assert_eq!(to_string(&Point(1, -2).to_json()), "[1,-2]");
+    assert_eq!(read::<Point>("[1, -2]").unwrap(), Point(1, -2));
+    assert_eq!(to_string(&Meters(2.5).to_json()), "2.5");
+    assert_eq!(read::<Meters>("2.5").unwrap(), Meters(2.5));
+    assert_eq!(to_string(&Marker.to_json()), "null");
+    assert_eq!(read::<Marker>("null").unwrap(), Marker);
+    let wrapper = Wrapper { inner: vec![Some(1u8), None] };
+    assert_eq!(to_string(&wrapper.to_json()), r#"{"inner":[1,null]}"#);
+    assert_eq!(read::<Wrapper<Vec<Option<u8>>>>(r#"{"inner":[1,null]}"#).unwrap(), wrapper);
+}
+
+#[test]
+fn test_enum_tagging() {
+    let external = vec![External::Dot, External::Circle(1.5), External::Line(Point(0, 0), Point(1, 1)), External::Rect { w: 3, h: 4 }];
+    let json = r#"["Dot",{"circle":1.5},{"Line":[[0,0],[1,1]]},{"Rect":{"w":3,"h":4}}]"#;
+    assert_eq!(to_string(&external.to_json()), json);
+    assert_eq!(read::<Vec<External>>(json).unwrap(), external);
+
+    let extra = BTreeMap::from([("by".to_string(), Value::Null)]);
+    let internal = vec![Internal::Ping, Internal::Move { x: 1, y: 2 }, Internal::Note { id: 3, extra }];
+    let json = r#"[{"type":"Ping"},{"type":"Move","x":1,"y":2},{"type":"Note","id":3,"by":null}]"#;
+    assert_eq!(to_string(&internal.to_json()), json);
+    assert_eq!(read::<Vec<Internal>>(json).unwrap(), internal);
+
+    let adjacent = vec![Adjacent::Reset, Adjacent::Set(5), Adjacent::Pair(1, 2)];
+    let json = r#"[{"t":"Reset"},{"t":"Set","c":5},{"t":"Pair","c":[1,2]}]"#;
+    assert_eq!(to_string(&adjacent.to_json()), json);
+    assert_eq!(read::<Vec<Adjacent>>(json).unwrap(), adjacent);
+
+    let untagged = vec![Untagged::Number(-1), Untagged::Text("a".to_string()), Untagged::Point { x: 1, y: 2 }];
+    let json = r#"[-1,"a",{"x":1,"y":2}]"#;
+    assert_eq!(to_string(&untagged.to_json()), json);
+    assert_eq!(read::<Vec<Untagged>>(json).unwrap(), untagged);
+}
+
+#[test]
+fn test_errors_carry_the_path() {
+    let err = read::<Vec<Server>>(r#"[{"listen-port": 1}, {"listen-port": "80"}]"#).unwrap_err();
+    assert_eq!(err.to_string(), "Invalid type: found a string, expected u16 in $[1]['listen-port']");
+    assert_eq!(read::<Server>("{}").unwrap_err().to_string(), "Missing field `listen-port`");
+    assert_eq!(read::<Server>("[]").unwrap_err().to_string(), "Invalid type: found an array, expected an object");
+    assert_eq!(read::<Point>("[1, 2, 3]").unwrap_err().to_string(), "Invalid length: found 3 elements, expected 2");
+
+    let err = read::<External>(r#"{"Rect": {"w": 1, "h": -1}}"#).unwrap_err();
+    assert_eq!(err.path(), "$['Rect']['h']");
+    let err = read::<External>(r#""Square""#).unwrap_err();
+    assert_eq!(err.to_string(), "Unknown variant `Square`, expected `Dot`, `circle`, `Line` or `Rect`");
+    let err = read::<External>(r#""Rect""#).unwrap_err();
+    assert_eq!(err.to_string(), "Invalid type: found a string, expected an object with one member");
+
+    assert_eq!(read::<Internal>(r#"{"x": 1}"#).unwrap_err().to_string(), "Missing field `type`");
+    assert_eq!(read::<Internal>(r#"{"type": "Move", "x": 1}"#).unwrap_err().to_string(), "Missing field `y`");
+    assert_eq!(read::<Adjacent>(r#"{"t": "Set", "c": "x"}"#).unwrap_err().path(), "$['c']");
+    assert_eq!(read::<Adjacent>(r#"{"t": 1}"#).unwrap_err().path(), "$['t']");
+    let err = read::<Untagged>("true").unwrap_err();
+    assert_eq!(err.to_string(), "data did not match any variant of untagged enum Untagged");
+}
@@ -0,0 +1,63 @@
+use std::borrow::Cow;
+use crate::core::{JsonNumber, JsonValue};
+use crate::wtf8::Wtf8String;
+
+/// A parsed document whose strings and member names point into the input
+/// wherever they contain no escapes, so reading them costs no allocation.
+/// Produced by [`deserialize_borrowed`](crate::deserialize_borrowed).
+#[derive(Debug, PartialEq, Clone)]
+pub enum BorrowedJsonValue<'a> {
+    Null,
+    Bool(bool),
+    Number(JsonNumber),
+    String(Cow<'a, str>),
+    /// See [`JsonValue::Wtf8`]; always owned.
+    Wtf8(Wtf8String),
+    Array(Vec<BorrowedJsonValue<'a>>),
+    Object(Vec<(Cow<'a, str>, BorrowedJsonValue<'a>)>),
+}
+
+impl<'a> BorrowedJsonValue<'a> {
+    /// Copies the borrowed strings, detaching the value from the input.
+    pub fn into_owned(self) -> JsonValue {
+        match self {
+            BorrowedJsonValue::Null => JsonValue::Null,
+            BorrowedJsonValue::Bool(b) => JsonValue::Bool(b),
+            BorrowedJsonValue::Number(n) => JsonValue::Number(n),
+            BorrowedJsonValue::String(s) => JsonValue::String(s.into_owned()),
+            BorrowedJsonValue::Wtf8(s) => JsonValue::Wtf8(s),
+            BorrowedJsonValue::Array(items) => JsonValue::Array(items.into_iter().map(Self::into_owned).collect()),
+            BorrowedJsonValue::Object(pairs) => {
+                JsonValue::Object(pairs.into_iter().map(|(k, v)| (k.into_owned(), v.into_owned())).collect())
+            }
+        }
+    }
+
+    /// Looks up `key` in an object; the last of repeated keys wins.
+    pub fn get(&self, key: &str) -> Option<&BorrowedJsonValue<'a>> {
+        match self {
+            BorrowedJsonValue::Object(pairs) => pairs.iter().rev().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub fn get_index(&self, index: usize) -> Option<&BorrowedJsonValue<'a>> {
+        match self {
+            BorrowedJsonValue::Array(items) => items.get(index),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            BorrowedJsonValue::String(s) => Some(s),
+            _ => None,
+        }
+    }
+}
+
+impl From<BorrowedJsonValue<'_>> for JsonValue {
+    fn from(value: BorrowedJsonValue<'_>) -> Self {
+        value.into_owned()
+    }
+}
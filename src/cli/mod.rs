@@ -1,70 +1,112 @@
 use std::env;
+use std::fmt;
+use std::io::{self, Write};
+use std::process::ExitCode;
 use crate::driver::*;
 use crate::io::*;
+use crate::patch::JsonPatch;
+use crate::core::{JsonError, JsonValue};
+use crate::options::{DuplicateKeyPolicy, ParseOptions};
+use crate::writer::{NewlineStyle, NonFinitePolicy, PrettyConfig, WriteOptions};
 
-pub fn handle_cli() {
-    let mut args = env::args().skip(1); // skip executable name
+/// Options given as `--flag[=value]` anywhere on the command line.
+#[derive(Debug, Default)]
+pub(crate) struct CliOptions {
+    pub(crate) pretty: PrettyConfig,
+    pub(crate) parse: ParseOptions,
+    pub(crate) write: WriteOptions,
+}
+
+/// Runs the command named on the command line, reporting any failure on
+/// stderr and in the exit status.
+pub fn handle_cli() -> ExitCode {
+    let (options, positional) = match parse_flags(env::args().skip(1)) { // skip executable name
+        Ok(parsed) => parsed,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            print_flags_usage();
+            return ExitCode::FAILURE;
+        }
+    };
+    let mut args = positional.into_iter();
 
-    match args.next().as_deref() {
+    let result = match args.next().as_deref() {
         Some("run") => {
             match args.next().as_deref() {
                 Some("-serialize") => {
                     if let Some(json_input) = args.next() {
-                        match deserialize(&json_input) {
-                            Ok(value) => println!("{}", serialize_pretty(&value)),
-                            Err(e) => eprintln!("Error parsing JSON: {}", e),
-                        }
-                    } else{
-                        eprintln!("Usage: basic_json_parser run -serialize '<json_string>'");
+                        run_serialize(&json_input, &options)
+                    } else {
+                        usage("run -serialize '<json_string>'")
                     }
                 }
                 Some("-deserialize") => {
                     if let Some(json_input) = args.next() {
-                        run(&json_input);
+                        run(&json_input, &options)
                     } else {
-                        eprintln!("Usage: basic_json_parser run -deserialize '<json_string>'");
+                        usage("run -deserialize '<json_string>'")
                     }
                 }
-                Some(json_input) => {
-                    run(json_input);
-                }
-                None => {
-                    eprintln!("Usage: basic_json_parser run -serialize|-deserialize '<json_string>'");
-                }
+                Some(json_input) => run(json_input, &options),
+                None => usage("run -serialize|-deserialize '<json_string>'"),
             }
         }
         Some("file") => {
             match args.next().as_deref() {
                 Some("-serialize") => {
                     if let Some(path) = args.next() {
-                        if let Err(e) = {
-                            let input = read_from_file(&path).unwrap();
-                            let value = deserialize(&input).unwrap();
-                            write_to_file("output.json", &serialize_pretty(&value))
-                        } {
-                            eprintln!("Error: {}", e);
-                        }
+                        run_serialize_file(&path, &options)
                     } else {
-                        eprintln!("Usage: basic_json_parser file serialize <file_path>");
+                        usage("file serialize <file_path>")
                     }
                 }
                 Some("-deserialize") => {
                     if let Some(path) = args.next() {
-                        if let Err(e) = run_file(&path) {
-                            eprintln!("Error: {}", e);
-                        }
+                        run_file(&path, &options)
                     } else {
-                        eprintln!("Usage: basic_json_parser file deserialize <file_path>");
+                        usage("file deserialize <file_path>")
                     }
                 }
-                Some(path) => {
-                    if let Err(e) = run_file(path) {
-                        eprintln!("Error: {}", e);
-                    }
-                }
-                None => {
-                    eprintln!("Usage: basic_json_parser file serialize|deserialize <file_path>");
-                }
+                Some(path) => run_file(path, &options),
+                None => usage("file serialize|deserialize <file_path>"),
+            }
+        }
+        Some("pointer") => {
+            match (args.next(), args.next()) {
+                (Some(path), Some(pointer)) => run_pointer(&path, &pointer, &options),
+                _ => usage("pointer <file_path> <json_pointer>"),
+            }
+        }
+        Some("query") => {
+            match (args.next(), args.next()) {
+                (Some(path), Some(query)) => run_query(&path, &query, &options),
+                _ => usage("query <file_path> <jsonpath>"),
+            }
+        }
+        Some("patch") => {
+            match (args.next(), args.next()) {
+                (Some(path), Some(patch_path)) => run_patch(&path, &patch_path, &options),
+                _ => usage("patch <file_path> <patch_file_path>"),
+            }
+        }
+        Some("diff") => {
+            match (args.next(), args.next()) {
+                (Some(from_path), Some(to_path)) => run_diff(&from_path, &to_path, &options),
+                _ => usage("diff <from_file_path> <to_file_path>"),
+            }
+        }
+        Some("check") => {
+            match args.next() {
+                Some(path) => run_check(&path, &options.parse),
+                None => usage("check <file_path>"),
+            }
+        }
+        Some("merge") => {
+            let paths: Vec<String> = args.collect();
+            if paths.len() < 2 {
+                usage("merge <file_path> <merge_patch_file_path>...")
+            } else {
+                run_merge(&paths, &options)
             }
         } _ => {
             eprintln!("Unknown command or missing arguments");
@@ -73,23 +115,252 @@ pub fn handle_cli() {
             eprintln!("  basic_json_parser run -deserialize '<json_string>'");
             eprintln!("  basic_json_parser file serialize <file_path>");
             eprintln!("  basic_json_parser file deserialize <file_path>");
+            eprintln!("  basic_json_parser pointer <file_path> <json_pointer>");
+            eprintln!("  basic_json_parser query <file_path> <jsonpath>");
+            eprintln!("  basic_json_parser patch <file_path> <patch_file_path>");
+            eprintln!("  basic_json_parser diff <from_file_path> <to_file_path>");
+            eprintln!("  basic_json_parser merge <file_path> <merge_patch_file_path>...");
+            eprintln!("  basic_json_parser check <file_path>");
+            print_flags_usage();
+            Err(UsageError.into())
+        }
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            report(&*e);
+            ExitCode::FAILURE
         }
     }
 }
 
+/// A command given the wrong arguments, after its usage has been printed.
+#[derive(Debug)]
+struct UsageError;
 
-pub(crate) fn run(json_input: &str) {
-    match deserialize(&json_input) {
-        Ok(value) => {
-            println!("{}", value.to_json_string());
-        }
-        Err(e) => eprintln!("Error parsing JSON: {}", e),
+impl fmt::Display for UsageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("invalid arguments")
+    }
+}
+
+impl std::error::Error for UsageError {}
+
+fn usage(command: &str) -> Result<(), Box<dyn std::error::Error>> {
+    eprintln!("Usage: basic_json_parser {}", command);
+    Err(UsageError.into())
+}
+
+pub(crate) fn run_serialize(json_input: &str, options: &CliOptions) -> Result<(), Box<dyn std::error::Error>> {
+    let value = deserialize_with(json_input, &options.parse).map_err(|e| Diagnostic(e.render(json_input, "<input>")))?;
+    println!("{}", serialize_with_options(&value, Some(&options.pretty), &options.write)?);
+    Ok(())
+}
+
+pub(crate) fn run(json_input: &str, options: &CliOptions) -> Result<(), Box<dyn std::error::Error>> {
+    let value = deserialize_with(json_input, &options.parse).map_err(|e| Diagnostic(e.render(json_input, "<input>")))?;
+    println!("{}", serialize_with_options(&value, None, &options.write)?);
+    Ok(())
+}
+
+/// Parses the file at `path` (or stdin for `-`) without reading it into memory first.
+pub(crate) fn read_value(path: &str, options: &ParseOptions) -> Result<JsonValue, Box<dyn std::error::Error>> {
+    deserialize_reader_with(open_input(path)?, options).map_err(|e| annotate(e, path))
+}
+
+/// Text ready for stderr, such as a parse error rendered against its input
+/// by [`JsonError::render`].
+#[derive(Debug)]
+pub(crate) struct Diagnostic(pub(crate) String);
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for Diagnostic {}
+
+// Quoting the offending line means reading the file a second time, which
+// is only possible for files; errors on stdin are reported as they are.
+fn annotate(error: JsonError, path: &str) -> Box<dyn std::error::Error> {
+    match read_from_file(path) {
+        Ok(source) if path != "-" => Box::new(Diagnostic(error.render(&source, path))),
+        _ => Box::new(error),
     }
 }
 
-pub(crate) fn run_file(path: &str) -> Result<(), Box<dyn std::error::Error>> {
+/// Prints `error` to stderr, as a source excerpt when one was rendered.
+/// Usage errors have been printed already.
+pub(crate) fn report(error: &(dyn std::error::Error + 'static)) {
+    if error.is::<UsageError>() {
+        return;
+    }
+    match error.downcast_ref::<Diagnostic>() {
+        Some(diagnostic) => eprint!("{}", diagnostic),
+        None => eprintln!("Error: {}", error),
+    }
+}
+
+pub(crate) fn run_serialize_file(path: &str, options: &CliOptions) -> Result<(), Box<dyn std::error::Error>> {
     let input = read_from_file(path)?;
-    let value = deserialize(&input)?;
-    write_to_file("output.json", &serialize(&value))?;
+    let value = deserialize_with(&input, &options.parse).map_err(|e| Diagnostic(e.render(&input, path)))?;
+    write_to_file("output.json", &serialize_with_options(&value, Some(&options.pretty), &options.write)?)?;
     Ok(())
 }
+
+pub(crate) fn run_file(path: &str, options: &CliOptions) -> Result<(), Box<dyn std::error::Error>> {
+    let value = read_value(path, &options.parse)?;
+    write_output("output.json", |output| value.write_json_with_options(output, None, &options.write))?;
+    Ok(())
+}
+
+pub(crate) fn run_pointer(path: &str, pointer: &str, options: &CliOptions) -> Result<(), Box<dyn std::error::Error>> {
+    let value = read_value(path, &options.parse)?;
+    print_pretty(value.pointer(pointer)?, options)
+}
+
+pub(crate) fn run_query(path: &str, query: &str, options: &CliOptions) -> Result<(), Box<dyn std::error::Error>> {
+    let value = read_value(path, &options.parse)?;
+    for node in value.query(query)? {
+        println!("{} = {}", node.normalized_path(), serialize_with_options(node.value(), None, &options.write)?);
+    }
+    Ok(())
+}
+
+pub(crate) fn run_patch(path: &str, patch_path: &str, options: &CliOptions) -> Result<(), Box<dyn std::error::Error>> {
+    let mut value = read_value(path, &options.parse)?;
+    let patch = JsonPatch::from_value(&read_value(patch_path, &options.parse)?)?;
+    patch.apply(&mut value)?;
+    print_pretty(&value, options)
+}
+
+pub(crate) fn run_diff(from_path: &str, to_path: &str, options: &CliOptions) -> Result<(), Box<dyn std::error::Error>> {
+    let from = read_value(from_path, &options.parse)?;
+    let to = read_value(to_path, &options.parse)?;
+    print_pretty(&JsonPatch::diff(&from, &to).to_value(), options)
+}
+
+pub(crate) fn run_merge(paths: &[String], options: &CliOptions) -> Result<(), Box<dyn std::error::Error>> {
+    let mut value = read_value(&paths[0], &options.parse)?;
+    for path in &paths[1..] {
+        value.merge_patch(&read_value(path, &options.parse)?);
+    }
+    print_pretty(&value, options)
+}
+
+/// Lists every syntax error in the file at `path` (or stdin for `-`) rather
+/// than just the first, along with any member name an object repeats, and
+/// fails with a count of them if there are any.
+///
+/// Repeated names are always reported; `--duplicate-keys` has no effect here.
+pub(crate) fn run_check(path: &str, options: &ParseOptions) -> Result<(), Box<dyn std::error::Error>> {
+    let options = ParseOptions { duplicate_keys: DuplicateKeyPolicy::Error, ..*options };
+    let (_, errors) = deserialize_reader_recovering(open_input(path)?, &options);
+    // As in `annotate`, excerpts need the file read again; stdin gets plain messages.
+    let source = if path == "-" { None } else { read_from_file(path).ok() };
+    for error in &errors {
+        match &source {
+            Some(source) => eprintln!("{}", error.render(source, path)),
+            None => eprintln!("Error: {}", error),
+        }
+    }
+    match errors.len() {
+        0 => {
+            println!("{}: no problems found", path);
+            Ok(())
+        }
+        1 => Err(Box::new(Diagnostic(format!("{}: 1 problem found\n", path)))),
+        n => Err(Box::new(Diagnostic(format!("{}: {} problems found\n", path, n)))),
+    }
+}
+
+/// Streams `value` to stdout, ending with exactly one newline.
+pub(crate) fn print_pretty(value: &JsonValue, options: &CliOptions) -> Result<(), Box<dyn std::error::Error>> {
+    let mut stdout = io::stdout().lock();
+    value.write_json_with_options(&mut stdout, Some(&options.pretty), &options.write)?;
+    if !options.pretty.trailing_newline {
+        writeln!(stdout)?;
+    }
+    Ok(())
+}
+
+/// Pulls `--flag[=value]` options out of `args`, returning them and the
+/// remaining positional arguments.
+pub(crate) fn parse_flags(args: impl Iterator<Item = String>) -> Result<(CliOptions, Vec<String>), String> {
+    let args: Vec<String> = args.collect();
+    let mut options = CliOptions::default();
+    // Unlike the library, the CLI refuses to turn NaN and infinities into null.
+    options.write.non_finite = NonFinitePolicy::Error;
+    // The JSON5 preset is applied first so other input flags can refine it.
+    if args.iter().any(|arg| arg == "--json5") {
+        options.parse = ParseOptions::json5();
+    }
+    let config = &mut options.pretty;
+    let mut positional = Vec::new();
+    for arg in args {
+        let Some(flag) = arg.strip_prefix("--") else {
+            positional.push(arg);
+            continue;
+        };
+        let (name, value) = match flag.split_once('=') {
+            Some((name, value)) => (name, Some(value)),
+            None => (flag, None),
+        };
+        match (name, value) {
+            ("indent", Some("tab")) => config.indent = "\t".to_string(),
+            ("indent", Some(n)) => {
+                let width: usize = n.parse().map_err(|_| format!("invalid indent '{}'", n))?;
+                config.indent = " ".repeat(width);
+            }
+            ("crlf", None) => config.newline = NewlineStyle::CrLf,
+            ("no-space-after-colon", None) => config.space_after_colon = false,
+            ("inline-width", Some(n)) => {
+                config.max_inline_width = n.parse().map_err(|_| format!("invalid inline width '{}'", n))?;
+            }
+            ("trailing-newline", None) => config.trailing_newline = true,
+            ("sort-keys", None) => config.sort_keys = true,
+            ("json5", None) => {}
+            ("arbitrary-precision", None) => options.parse.arbitrary_precision = true,
+            ("duplicate-keys", Some(policy)) => {
+                options.parse.duplicate_keys = match policy {
+                    "all" => DuplicateKeyPolicy::All,
+                    "first" => DuplicateKeyPolicy::First,
+                    "last" => DuplicateKeyPolicy::Last,
+                    "error" => DuplicateKeyPolicy::Error,
+                    _ => return Err(format!("invalid duplicate key policy '{}'", policy)),
+                };
+            }
+            ("preserve-floats", None) => options.write.preserve_float = true,
+            ("non-finite", Some(policy)) => {
+                options.write.non_finite = match policy {
+                    "error" => NonFinitePolicy::Error,
+                    "null" => NonFinitePolicy::Null,
+                    "literal" => NonFinitePolicy::Literal,
+                    "string" => NonFinitePolicy::String,
+                    _ => return Err(format!("invalid non-finite policy '{}'", policy)),
+                };
+            }
+            _ => return Err(format!("unknown option '{}'", arg)),
+        }
+    }
+    Ok((options, positional))
+}
+
+fn print_flags_usage() {
+    eprintln!("Input options:");
+    eprintln!("  --json5                 accept JSON5 (comments, trailing commas, unquoted keys, ...)");
+    eprintln!("  --arbitrary-precision   keep numbers beyond i64/u64 exactly as written");
+    eprintln!("  --duplicate-keys=<p>    repeated member names: all (default), first, last or error;");
+    eprintln!("                          ignored by check, which reports them all");
+    eprintln!("Output options:");
+    eprintln!("  --preserve-floats       write whole floats as 42.0 so they stay floats");
+    eprintln!("  --non-finite=<policy>   NaN/Infinity: error (default), null, literal or string");
+    eprintln!("Pretty-printing options:");
+    eprintln!("  --indent=<n|tab>        indent with n spaces or a tab (default 4)");
+    eprintln!("  --crlf                  use CRLF line endings");
+    eprintln!("  --no-space-after-colon  write \"key\":value");
+    eprintln!("  --inline-width=<n>      keep arrays/objects up to n characters on one line");
+    eprintln!("  --trailing-newline      end output with a newline");
+    eprintln!("  --sort-keys             sort object keys");
+}
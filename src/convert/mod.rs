@@ -0,0 +1,425 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::fmt::Formatter;
+use std::hash::Hash;
+use std::str::FromStr;
+use crate::core::{quote, JsonNumber, JsonValue, RawNumber};
+use crate::engine::PathStep;
+use crate::jsonpath::normalized_path;
+
+/// Conversion of a Rust value into a [`JsonValue`].
+///
+/// Implemented for the primitive types, `String`, `Option`, `Vec`, slices,
+/// arrays, `HashMap`/`BTreeMap` with keys that print as strings, tuples and
+/// `JsonValue` itself. `#[derive(ToJson)]` from the `derive` feature writes
+/// it for structs and enums.
+pub trait ToJson {
+    fn to_json(&self) -> JsonValue;
+}
+
+/// Conversion of a [`JsonValue`] into a Rust value, failing with the path of
+/// the first value that does not fit.
+pub trait FromJson: Sized {
+    fn from_json(value: &JsonValue) -> Result<Self, FromJsonError>;
+
+    /// What an object member of this type reads as when it is left out, or
+    /// `None` when it is required. `Option` members may be left out.
+    fn from_missing() -> Option<Self> {
+        None
+    }
+}
+
+/// Why a [`FromJson`] conversion failed.
+#[derive(Debug, PartialEq, Clone)]
+#[non_exhaustive]
+pub enum FromJsonErrorKind {
+    /// The value has the wrong JSON type, e.g. a string where a number belongs.
+    InvalidType { expected: &'static str, found: &'static str },
+    /// A number that is not whole where an integer belongs, or is out of range.
+    InvalidNumber { expected: &'static str, found: JsonNumber },
+    /// An array of the wrong length for a tuple.
+    InvalidLength { expected: usize, found: usize },
+    /// An object key that does not parse as the map's key type.
+    InvalidKey { expected: &'static str, found: String },
+    MissingField(&'static str),
+    UnknownVariant { expected: &'static [&'static str], found: String },
+    Custom(String),
+}
+
+/// A [`FromJson`] failure and where in the document it happened.
+#[derive(Debug, PartialEq, Clone)]
+pub struct FromJsonError {
+    kind: FromJsonErrorKind,
+    // From the value that failed out to the root.
+    path: Vec<PathStep>,
+}
+
+impl FromJsonError {
+    pub fn new(kind: FromJsonErrorKind) -> Self {
+        Self { kind, path: Vec::new() }
+    }
+
+    pub fn invalid_type(expected: &'static str, found: &JsonValue) -> Self {
+        Self::new(FromJsonErrorKind::InvalidType { expected, found: type_name(found) })
+    }
+
+    pub fn missing_field(name: &'static str) -> Self {
+        Self::new(FromJsonErrorKind::MissingField(name))
+    }
+
+    pub fn unknown_variant(found: &str, expected: &'static [&'static str]) -> Self {
+        Self::new(FromJsonErrorKind::UnknownVariant { expected, found: found.to_string() })
+    }
+
+    pub fn custom(message: impl fmt::Display) -> Self {
+        Self::new(FromJsonErrorKind::Custom(message.to_string()))
+    }
+
+    /// Places the error inside the member `key` of an object.
+    pub fn at_key(mut self, key: &str) -> Self {
+        self.path.push(PathStep::Key(key.to_string()));
+        self
+    }
+
+    /// Places the error inside the element `index` of an array.
+    pub fn at_index(mut self, index: usize) -> Self {
+        self.path.push(PathStep::Index(index));
+        self
+    }
+
+    pub fn kind(&self) -> &FromJsonErrorKind {
+        &self.kind
+    }
+
+    /// Normalized JSONPath of the value that failed, e.g. `$['items'][2]`.
+    pub fn path(&self) -> String {
+        normalized_path(self.path.iter().rev().map(PathStep::element))
+    }
+}
+
+/// Reads like ``Invalid type: found a string, expected u16 in $['port']``.
+impl fmt::Display for FromJsonError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match &self.kind {
+            FromJsonErrorKind::InvalidType { expected, found } => write!(f, "Invalid type: found {}, expected {}", found, expected)?,
+            FromJsonErrorKind::InvalidNumber { expected, found } => write!(f, "Invalid number: found `{}`, expected {}", found, expected)?,
+            FromJsonErrorKind::InvalidLength { expected, found } => {
+                write!(f, "Invalid length: found {} elements, expected {}", found, expected)?
+            }
+            FromJsonErrorKind::InvalidKey { expected, found } => write!(f, "Invalid key: found {}, expected {}", quote(found), expected)?,
+            FromJsonErrorKind::MissingField(name) => write!(f, "Missing field `{}`", name)?,
+            FromJsonErrorKind::UnknownVariant { expected, found } => {
+                write!(f, "Unknown variant {}, expected ", quote(found))?;
+                match expected.split_last() {
+                    None => f.write_str("no variants")?,
+                    Some((last, [])) => write!(f, "`{}`", last)?,
+                    Some((last, rest)) => write!(f, "`{}` or `{}`", rest.join("`, `"), last)?,
+                }
+            }
+            FromJsonErrorKind::Custom(message) => f.write_str(message)?,
+        }
+        if !self.path.is_empty() {
+            write!(f, " in {}", self.path())?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for FromJsonError {}
+
+fn type_name(value: &JsonValue) -> &'static str {
+    match value {
+        JsonValue::Null => "null",
+        JsonValue::Bool(_) => "a boolean",
+        JsonValue::Number(_) => "a number",
+        JsonValue::String(_) | JsonValue::Wtf8(_) => "a string",
+        JsonValue::Array(_) => "an array",
+        JsonValue::Object(_) => "an object",
+    }
+}
+
+impl ToJson for JsonValue {
+    fn to_json(&self) -> JsonValue {
+        self.clone()
+    }
+}
+
+impl FromJson for JsonValue {
+    fn from_json(value: &JsonValue) -> Result<Self, FromJsonError> {
+        Ok(value.clone())
+    }
+}
+
+impl ToJson for JsonNumber {
+    fn to_json(&self) -> JsonValue {
+        JsonValue::Number(self.clone())
+    }
+}
+
+impl FromJson for JsonNumber {
+    fn from_json(value: &JsonValue) -> Result<Self, FromJsonError> {
+        match value {
+            JsonValue::Number(n) => Ok(n.clone()),
+            _ => Err(FromJsonError::invalid_type("a number", value)),
+        }
+    }
+}
+
+impl ToJson for bool {
+    fn to_json(&self) -> JsonValue {
+        JsonValue::Bool(*self)
+    }
+}
+
+impl FromJson for bool {
+    fn from_json(value: &JsonValue) -> Result<Self, FromJsonError> {
+        value.as_bool().ok_or_else(|| FromJsonError::invalid_type("a boolean", value))
+    }
+}
+
+// Integers are written as `Integer` when they fit `i64`, as
+// `UnsignedInteger` when they fit `u64` and as exact `Raw` text otherwise.
+// Reading them rejects fractions and values out of range.
+macro_rules! integer_conversions {
+    ($($ty:ty),*) => {
+        $(
+            impl ToJson for $ty {
+                fn to_json(&self) -> JsonValue {
+                    let n = match (i64::try_from(*self).ok(), u64::try_from(*self).ok()) {
+                        (Some(i), _) => JsonNumber::Integer(i),
+                        (None, Some(u)) => JsonNumber::UnsignedInteger(u),
+                        (None, None) => JsonNumber::Raw(RawNumber::new(self.to_string())),
+                    };
+                    JsonValue::Number(n)
+                }
+            }
+
+            impl FromJson for $ty {
+                fn from_json(value: &JsonValue) -> Result<Self, FromJsonError> {
+                    let JsonValue::Number(n) = value else {
+                        return Err(FromJsonError::invalid_type(stringify!($ty), value));
+                    };
+                    let whole = match n {
+                        JsonNumber::Float(_) => None,
+                        // Raw text may be past `i128`, e.g. a large `u128`.
+                        JsonNumber::Raw(text) => text.as_str().parse().ok(),
+                        n => n.to_i128().and_then(|i| <$ty>::try_from(i).ok()),
+                    };
+                    whole.ok_or_else(|| {
+                        FromJsonError::new(FromJsonErrorKind::InvalidNumber { expected: stringify!($ty), found: n.clone() })
+                    })
+                }
+            }
+        )*
+    };
+}
+
+integer_conversions!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+impl ToJson for f64 {
+    fn to_json(&self) -> JsonValue {
+        JsonValue::Number(JsonNumber::Float(*self))
+    }
+}
+
+impl FromJson for f64 {
+    fn from_json(value: &JsonValue) -> Result<Self, FromJsonError> {
+        value.as_f64().ok_or_else(|| FromJsonError::invalid_type("f64", value))
+    }
+}
+
+/// Written with the fewest digits that read back as the same `f32`.
+impl ToJson for f32 {
+    fn to_json(&self) -> JsonValue {
+        JsonValue::Number(JsonNumber::Float(self.to_string().parse().expect("f32 text parses as f64")))
+    }
+}
+
+impl FromJson for f32 {
+    fn from_json(value: &JsonValue) -> Result<Self, FromJsonError> {
+        value.as_f64().map(|f| f as f32).ok_or_else(|| FromJsonError::invalid_type("f32", value))
+    }
+}
+
+impl ToJson for str {
+    fn to_json(&self) -> JsonValue {
+        JsonValue::String(self.to_string())
+    }
+}
+
+impl ToJson for String {
+    fn to_json(&self) -> JsonValue {
+        JsonValue::String(self.clone())
+    }
+}
+
+impl FromJson for String {
+    fn from_json(value: &JsonValue) -> Result<Self, FromJsonError> {
+        value.as_str().map(str::to_string).ok_or_else(|| FromJsonError::invalid_type("a string", value))
+    }
+}
+
+impl ToJson for char {
+    fn to_json(&self) -> JsonValue {
+        JsonValue::String(self.to_string())
+    }
+}
+
+impl FromJson for char {
+    fn from_json(value: &JsonValue) -> Result<Self, FromJsonError> {
+        let mut chars = value.as_str().unwrap_or_default().chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Ok(c),
+            _ => Err(FromJsonError::invalid_type("a single character string", value)),
+        }
+    }
+}
+
+impl ToJson for () {
+    fn to_json(&self) -> JsonValue {
+        JsonValue::Null
+    }
+}
+
+impl FromJson for () {
+    fn from_json(value: &JsonValue) -> Result<Self, FromJsonError> {
+        match value {
+            JsonValue::Null => Ok(()),
+            _ => Err(FromJsonError::invalid_type("null", value)),
+        }
+    }
+}
+
+impl<T: ToJson + ?Sized> ToJson for &T {
+    fn to_json(&self) -> JsonValue {
+        (**self).to_json()
+    }
+}
+
+impl<T: ToJson + ?Sized> ToJson for Box<T> {
+    fn to_json(&self) -> JsonValue {
+        (**self).to_json()
+    }
+}
+
+impl<T: FromJson> FromJson for Box<T> {
+    fn from_json(value: &JsonValue) -> Result<Self, FromJsonError> {
+        T::from_json(value).map(Box::new)
+    }
+
+    fn from_missing() -> Option<Self> {
+        T::from_missing().map(Box::new)
+    }
+}
+
+/// `None` is `null`.
+impl<T: ToJson> ToJson for Option<T> {
+    fn to_json(&self) -> JsonValue {
+        self.as_ref().map_or(JsonValue::Null, T::to_json)
+    }
+}
+
+impl<T: FromJson> FromJson for Option<T> {
+    fn from_json(value: &JsonValue) -> Result<Self, FromJsonError> {
+        match value {
+            JsonValue::Null => Ok(None),
+            value => T::from_json(value).map(Some),
+        }
+    }
+
+    fn from_missing() -> Option<Self> {
+        Some(None)
+    }
+}
+
+impl<T: ToJson> ToJson for [T] {
+    fn to_json(&self) -> JsonValue {
+        JsonValue::Array(self.iter().map(T::to_json).collect())
+    }
+}
+
+impl<T: ToJson, const N: usize> ToJson for [T; N] {
+    fn to_json(&self) -> JsonValue {
+        self.as_slice().to_json()
+    }
+}
+
+impl<T: ToJson> ToJson for Vec<T> {
+    fn to_json(&self) -> JsonValue {
+        self.as_slice().to_json()
+    }
+}
+
+impl<T: FromJson> FromJson for Vec<T> {
+    fn from_json(value: &JsonValue) -> Result<Self, FromJsonError> {
+        let items = value.as_array().ok_or_else(|| FromJsonError::invalid_type("an array", value))?;
+        items.iter().enumerate().map(|(i, item)| T::from_json(item).map_err(|e| e.at_index(i))).collect()
+    }
+}
+
+// Maps become objects with their keys printed as member names, and are read
+// back by parsing each name as a key. `HashMap` members come out in no
+// particular order.
+macro_rules! map_conversions {
+    ($map:ident, $($bound:path),*) => {
+        impl<K: fmt::Display, V: ToJson> ToJson for $map<K, V> {
+            fn to_json(&self) -> JsonValue {
+                JsonValue::Object(self.iter().map(|(k, v)| (k.to_string(), v.to_json())).collect())
+            }
+        }
+
+        /// A repeated member name keeps its last value.
+        impl<K: FromStr $(+ $bound)*, V: FromJson> FromJson for $map<K, V> {
+            fn from_json(value: &JsonValue) -> Result<Self, FromJsonError> {
+                let pairs = value.as_object().ok_or_else(|| FromJsonError::invalid_type("an object", value))?;
+                pairs
+                    .iter()
+                    .map(|(name, member)| {
+                        let key = name.parse().map_err(|_| {
+                            let kind = FromJsonErrorKind::InvalidKey { expected: std::any::type_name::<K>(), found: name.clone() };
+                            FromJsonError::new(kind).at_key(name)
+                        })?;
+                        Ok((key, V::from_json(member).map_err(|e| e.at_key(name))?))
+                    })
+                    .collect()
+            }
+        }
+    };
+}
+
+map_conversions!(HashMap, Eq, Hash);
+map_conversions!(BTreeMap, Ord);
+
+// Tuples are arrays of exactly their length.
+macro_rules! tuple_conversions {
+    ($($len:literal => ($($name:ident $index:tt),+))*) => {
+        $(
+            impl<$($name: ToJson),+> ToJson for ($($name,)+) {
+                fn to_json(&self) -> JsonValue {
+                    JsonValue::Array(vec![$(self.$index.to_json()),+])
+                }
+            }
+
+            impl<$($name: FromJson),+> FromJson for ($($name,)+) {
+                fn from_json(value: &JsonValue) -> Result<Self, FromJsonError> {
+                    let items = value.as_array().ok_or_else(|| FromJsonError::invalid_type("an array", value))?;
+                    if items.len() != $len {
+                        return Err(FromJsonError::new(FromJsonErrorKind::InvalidLength { expected: $len, found: items.len() }));
+                    }
+                    Ok(($($name::from_json(&items[$index]).map_err(|e| e.at_index($index))?,)+))
+                }
+            }
+        )*
+    };
+}
+
+tuple_conversions! {
+    1 => (A 0)
+    2 => (A 0, B 1)
+    3 => (A 0, B 1, C 2)
+    4 => (A 0, B 1, C 2, D 3)
+    5 => (A 0, B 1, C 2, D 3, E 4)
+    6 => (A 0, B 1, C 2, D 3, E 4, F 5)
+    7 => (A 0, B 1, C 2, D 3, E 4, F 5, G 6)
+    8 => (A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7)
+}
@@ -1,33 +1,134 @@
 use std::fmt;
+use std::io;
 use std::fmt::{ Formatter};
+use crate::number::format_float;
+use crate::parser::{NumberParser, StringEscaper};
+use crate::wtf8::Wtf8String;
 
 #[derive(Debug, PartialEq, Clone)]
-pub(crate) enum JsonNumber{
+pub enum JsonNumber{
     Integer(i64),
     UnsignedInteger(u64),
-    Float(f64)
+    Float(f64),
+    /// Number text kept verbatim instead of being rounded to `f64`; see
+    /// [`ParseOptions::arbitrary_precision`](crate::ParseOptions::arbitrary_precision).
+    /// Built from text with [`JsonNumber::from_lexeme`].
+    Raw(RawNumber),
 }
 
+/// The text of a [`JsonNumber::Raw`], known to be a valid JSON number.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct RawNumber(String);
+
+impl RawNumber {
+    /// `text` must already be a valid JSON number, e.g. one the lexer read.
+    pub(crate) fn new(text: String) -> Self {
+        RawNumber(text)
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for RawNumber {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl JsonNumber {
+    /// Keeps `text` verbatim as a [`Raw`](JsonNumber::Raw) number, after
+    /// checking it against the RFC 8259 grammar, e.g. `-12.50e300`.
+    pub fn from_lexeme(text: &str) -> Result<Self, JsonError> {
+        match NumberParser::check_lexeme(text) {
+            Ok(()) => Ok(JsonNumber::Raw(RawNumber(text.to_string()))),
+            Err(at) => {
+                let position = Position { offset: at, line: 1, col: text[..at].chars().count() + 1 };
+                Err(JsonError::invalid_number(position, quote(text), &["a JSON number"]))
+            }
+        }
+    }
+
+    pub fn is_i64(&self) -> bool {
+        self.as_i64().is_some()
+    }
+
+    pub fn is_u64(&self) -> bool {
+        self.as_u64().is_some()
+    }
+
+    /// True for floats, and for raw numbers written with a fraction or an
+    /// exponent even when their value is whole.
+    pub fn is_f64(&self) -> bool {
+        match self {
+            JsonNumber::Float(_) => true,
+            JsonNumber::Raw(raw) => raw.as_str().contains(['.', 'e', 'E']),
+            _ => false,
+        }
+    }
+
+    /// Returns the number as `i64` if it is an integer that fits.
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            JsonNumber::Integer(i) => Some(i),
+            JsonNumber::UnsignedInteger(u) => i64::try_from(u).ok(),
+            JsonNumber::Float(_) => None,
+            JsonNumber::Raw(_) => self.to_i128().and_then(|i| i64::try_from(i).ok()),
+        }
+    }
+
+    /// Returns the number as `u64` if it is a non-negative integer.
+    pub fn as_u64(&self) -> Option<u64> {
+        match *self {
+            JsonNumber::Integer(i) => u64::try_from(i).ok(),
+            JsonNumber::UnsignedInteger(u) => Some(u),
+            JsonNumber::Float(_) => None,
+            JsonNumber::Raw(_) => self.to_i128().and_then(|i| u64::try_from(i).ok()),
+        }
+    }
+
+    /// Returns the number as `f64`; integers may lose precision.
+    pub fn as_f64(&self) -> f64 {
+        match *self {
+            JsonNumber::Integer(i) => i as f64,
+            JsonNumber::UnsignedInteger(u) => u as f64,
+            JsonNumber::Float(f) => f,
+            JsonNumber::Raw(ref s) => s.as_str().parse().unwrap_or(f64::NAN),
+        }
+    }
+}
+
+/// Floats are written with the fewest digits that parse back to the same
+/// value, e.g. `0.1`, `1e300`, `1.5e-8`. Whole floats print like integers
+/// (`42`); the alternate form `{:#}` keeps them as floats (`42.0`). NaN and
+/// infinities print as `null`; see [`NonFinitePolicy`](crate::NonFinitePolicy)
+/// for the alternatives when serializing.
 impl fmt::Display for JsonNumber{
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match self{
             JsonNumber::Integer(i) => write!(f,"{}", i),
             JsonNumber::UnsignedInteger(u) => write!(f, "{}", u),
+            JsonNumber::Raw(s) => f.write_str(s.as_str()),
             JsonNumber::Float(fl) => {
-                if fl.is_nan() {
-                    write!(f, "null")
-                } else if fl.is_infinite(){
+                if fl.is_nan() || fl.is_infinite() {
                     write!(f, "null")
-                } else if fl.fract() == 0.0 && fl.abs() < 1e15{
-                    write!(f, "{:.0}", fl)
-                }else {
-                    write!(f, "{}", fl)
+                } else {
+                    f.write_str(&format_float(*fl, f.alternate()))
                 }
             }
         }
     }
 }
 
+/// A location in the input: byte offset plus 1-based line and column.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Position {
+    pub offset: usize,
+    pub line: usize,
+    pub col: usize,
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub(crate) enum Token {
     CurlyLeft,
@@ -37,41 +138,363 @@ pub(crate) enum Token {
     Colon,
     Comma,
     String(String),
+    /// A string with a lone surrogate, under [`LoneSurrogatePolicy::Preserve`](crate::LoneSurrogatePolicy::Preserve).
+    Wtf8(Wtf8String),
     Number(JsonNumber),
+    /// A bare word other than `true`/`false`/`null`; only produced when
+    /// unquoted keys or `Infinity`/`NaN` are enabled.
+    Identifier(String),
     True,
     False,
     Null,
+    /// Input that cannot start any token: a bare word, or a single character.
+    Unexpected(String),
     Eof,
 }
 
+impl Token {
+    /// How the token reads in error messages, e.g. `` `}` `` or `` `"key"` ``;
+    /// `None` at the end of input.
+    pub(crate) fn describe(&self) -> Option<String> {
+        let text = match self {
+            Token::CurlyLeft => "{".to_string(),
+            Token::CurlyRight => "}".to_string(),
+            Token::SquareLeft => "[".to_string(),
+            Token::SquareRight => "]".to_string(),
+            Token::Colon => ":".to_string(),
+            Token::Comma => ",".to_string(),
+            Token::String(s) => format!("\"{}\"", StringEscaper::escape(s)),
+            Token::Wtf8(s) => format!("\"{}\"", StringEscaper::escape_wtf8(s)),
+            Token::Number(n) => n.to_string(),
+            Token::Identifier(name) => name.clone(),
+            Token::True => "true".to_string(),
+            Token::False => "false".to_string(),
+            Token::Null => "null".to_string(),
+            Token::Unexpected(text) => {
+                let mut chars = text.chars();
+                return match (chars.next(), chars.next()) {
+                    (Some(c), None) => Some(describe_char(c)),
+                    _ => Some(quote(text)),
+                };
+            }
+            Token::Eof => return None,
+        };
+        Some(quote(&text))
+    }
+}
+
+/// `c` as it reads in error messages: `` `@` ``, or `U+0007` for characters
+/// that would not show up.
+pub(crate) fn describe_char(c: char) -> String {
+    if c.is_control() || c.is_whitespace() {
+        format!("U+{:04X}", c as u32)
+    } else {
+        format!("`{}`", c)
+    }
+}
+
+/// `text` in backticks, cut short when long.
+pub(crate) fn quote(text: &str) -> String {
+    format!("`{}`", truncated(text))
+}
+
+// Long strings are cut short so one bad token cannot flood a message.
+fn truncated(s: &str) -> String {
+    const MAX_CHARS: usize = 32;
+    match s.char_indices().nth(MAX_CHARS) {
+        Some((end, _)) => format!("{}...", &s[..end]),
+        None => s.to_string(),
+    }
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub enum JsonValue {
     Null,
     Bool(bool),
     Number(JsonNumber),
     String(String),
+    /// A string with a lone UTF-16 surrogate, only read under
+    /// [`LoneSurrogatePolicy::Preserve`](crate::LoneSurrogatePolicy::Preserve).
+    /// It is written back with the same `\u` escapes, but has no serde form.
+    Wtf8(Wtf8String),
     Array(Vec<JsonValue>),
     Object(Vec<(String, JsonValue)>),
 }
 
-#[derive(Debug, PartialEq, Clone)]
-pub(crate) enum JsonError {
-    InvalidToken { line: usize, col: usize },
-    UnexpectedEof { line: usize, col: usize }, // Eof - End of file
-    InvalidEscapeSequence { line: usize, col: usize },
-    InvalidNumber { line: usize, col: usize },
+/// Which of the [`Limits`](crate::Limits) a document went past.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Limit {
+    Depth,
+    InputBytes,
+    StringBytes,
+    Elements,
+    Nodes,
+    NumberLength,
 }
 
-impl fmt::Display for JsonError {
+impl fmt::Display for Limit {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Limit::Depth => "nesting depth",
+            Limit::InputBytes => "input size",
+            Limit::StringBytes => "string length",
+            Limit::Elements => "array/object length",
+            Limit::Nodes => "value count",
+            Limit::NumberLength => "number length",
+        })
+    }
+}
+
+/// Details of a parse error beyond its line and column.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ErrorContext {
+    /// Byte offset of the error from the start of the input.
+    pub offset: usize,
+    /// The offending input as it reads in messages, e.g. `` `tru` `` or
+    /// `` `"key"` ``; `None` at the end of input.
+    pub found: Option<String>,
+    /// What would have been accepted instead, e.g. `` `,` `` and `` `]` ``.
+    pub expected: Vec<&'static str>,
+    /// Normalized JSONPath of the value being read, e.g. `$['items'][2]`.
+    pub path: String,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[non_exhaustive]
+pub enum JsonError {
+    InvalidToken { line: usize, col: usize, context: Box<ErrorContext> },
+    UnexpectedEof { line: usize, col: usize, context: Box<ErrorContext> }, // Eof - End of file
+    InvalidEscapeSequence { line: usize, col: usize, context: Box<ErrorContext> },
+    InvalidNumber { line: usize, col: usize, context: Box<ErrorContext> },
+    InvalidUtf8 { line: usize, col: usize, context: Box<ErrorContext> },
+    Io { kind: io::ErrorKind, line: usize, col: usize, context: Box<ErrorContext> },
+    /// The document went past one of its [`Limits`](crate::Limits); `max` is
+    /// the value that limit was set to.
+    LimitExceeded { limit: Limit, max: usize, line: usize, col: usize, context: Box<ErrorContext> },
+    /// An object member repeats a name under
+    /// [`DuplicateKeyPolicy::Error`](crate::DuplicateKeyPolicy::Error);
+    /// `first` is where the name was first used.
+    DuplicateKey { line: usize, col: usize, first: Position, context: Box<ErrorContext> },
+    /// Serialization met NaN or ±Infinity under
+    /// [`NonFinitePolicy::Error`](crate::NonFinitePolicy::Error); `pointer`
+    /// is the JSON Pointer to the value.
+    NonFiniteNumber { pointer: String },
+    /// A message from outside the parser, such as a serde `Deserialize`
+    /// impl rejecting a value; `line`, `col` and `context` are those of the
+    /// value it was raised on, and 0 and `None` when there was none.
+    Custom { message: String, line: usize, col: usize, context: Option<Box<ErrorContext>> },
+}
+
+impl JsonError {
+    pub(crate) fn invalid_token(at: Position, found: Option<String>, expected: &[&'static str]) -> Self {
+        JsonError::InvalidToken { line: at.line, col: at.col, context: ErrorContext::boxed(at, found, expected) }
+    }
+
+    pub(crate) fn unexpected_eof(at: Position, expected: &[&'static str]) -> Self {
+        JsonError::UnexpectedEof { line: at.line, col: at.col, context: ErrorContext::boxed(at, None, expected) }
+    }
+
+    pub(crate) fn invalid_escape(at: Position, found: String, expected: &[&'static str]) -> Self {
+        let context = ErrorContext::boxed(at, Some(found), expected);
+        JsonError::InvalidEscapeSequence { line: at.line, col: at.col, context }
+    }
+
+    pub(crate) fn invalid_number(at: Position, found: String, expected: &[&'static str]) -> Self {
+        JsonError::InvalidNumber { line: at.line, col: at.col, context: ErrorContext::boxed(at, Some(found), expected) }
+    }
+
+    pub(crate) fn invalid_utf8(at: Position) -> Self {
+        JsonError::InvalidUtf8 { line: at.line, col: at.col, context: ErrorContext::boxed(at, None, &[]) }
+    }
+
+    pub(crate) fn io(kind: io::ErrorKind, at: Position) -> Self {
+        JsonError::Io { kind, line: at.line, col: at.col, context: ErrorContext::boxed(at, None, &[]) }
+    }
+
+    pub(crate) fn limit_exceeded(at: Position, limit: Limit, max: usize) -> Self {
+        let context = ErrorContext::boxed(at, None, &[]);
+        JsonError::LimitExceeded { limit, max, line: at.line, col: at.col, context }
+    }
+
+    pub(crate) fn duplicate_key(at: Position, found: Option<String>, first: Position) -> Self {
+        JsonError::DuplicateKey { line: at.line, col: at.col, first, context: ErrorContext::boxed(at, found, &[]) }
+    }
+
+    #[cfg(feature = "serde")]
+    pub(crate) fn custom(message: impl fmt::Display) -> Self {
+        JsonError::Custom { message: message.to_string(), line: 0, col: 0, context: None }
+    }
+
+    /// Places an error raised without a position at `at`.
+    pub(crate) fn located(mut self, at: Position) -> Self {
+        if let JsonError::Custom { line, col, context, .. } = &mut self
+            && context.is_none()
+        {
+            (*line, *col) = (at.line, at.col);
+            *context = Some(ErrorContext::boxed(at, None, &[]));
+        }
+        self
+    }
+
+    /// Records where in the document the error occurred.
+    pub(crate) fn with_path(mut self, path: String) -> Self {
+        if let Some(context) = self.context_mut() {
+            context.path = path;
+        }
+        self
+    }
+
+    /// Line (1-based) where the error was detected, or 0 for serialization errors.
+    pub fn line(&self) -> usize {
+        match *self {
+            JsonError::InvalidToken { line, .. }
+            | JsonError::UnexpectedEof { line, .. }
+            | JsonError::InvalidEscapeSequence { line, .. }
+            | JsonError::InvalidNumber { line, .. }
+            | JsonError::InvalidUtf8 { line, .. }
+            | JsonError::Io { line, .. }
+            | JsonError::LimitExceeded { line, .. }
+            | JsonError::DuplicateKey { line, .. }
+            | JsonError::Custom { line, .. } => line,
+            JsonError::NonFiniteNumber { .. } => 0,
+        }
+    }
+
+    /// Column (1-based) where the error was detected, or 0 for serialization errors.
+    pub fn col(&self) -> usize {
+        match *self {
+            JsonError::InvalidToken { col, .. }
+            | JsonError::UnexpectedEof { col, .. }
+            | JsonError::InvalidEscapeSequence { col, .. }
+            | JsonError::InvalidNumber { col, .. }
+            | JsonError::InvalidUtf8 { col, .. }
+            | JsonError::Io { col, .. }
+            | JsonError::LimitExceeded { col, .. }
+            | JsonError::DuplicateKey { col, .. }
+            | JsonError::Custom { col, .. } => col,
+            JsonError::NonFiniteNumber { .. } => 0,
+        }
+    }
+
+    /// Offset, found and expected tokens and path of a parse error; `None`
+    /// for serialization errors.
+    pub fn context(&self) -> Option<&ErrorContext> {
         match self {
-            JsonError::InvalidToken { line, col } => write!(f, "Invalid token at {}:{}", line, col),
-            JsonError::UnexpectedEof { line, col } => write!(f, "Unexpected EOF at {}:{}", line, col),
-            JsonError::InvalidEscapeSequence { line, col } => {
-                write!(f, "Invalid escape at {}:{}", line, col)
+            JsonError::InvalidToken { context, .. }
+            | JsonError::UnexpectedEof { context, .. }
+            | JsonError::InvalidEscapeSequence { context, .. }
+            | JsonError::InvalidNumber { context, .. }
+            | JsonError::InvalidUtf8 { context, .. }
+            | JsonError::Io { context, .. }
+            | JsonError::LimitExceeded { context, .. }
+            | JsonError::DuplicateKey { context, .. } => Some(context),
+            JsonError::Custom { context, .. } => context.as_deref(),
+            JsonError::NonFiniteNumber { .. } => None,
+        }
+    }
+
+    fn context_mut(&mut self) -> Option<&mut ErrorContext> {
+        match self {
+            JsonError::InvalidToken { context, .. }
+            | JsonError::UnexpectedEof { context, .. }
+            | JsonError::InvalidEscapeSequence { context, .. }
+            | JsonError::InvalidNumber { context, .. }
+            | JsonError::InvalidUtf8 { context, .. }
+            | JsonError::Io { context, .. }
+            | JsonError::LimitExceeded { context, .. }
+            | JsonError::DuplicateKey { context, .. } => Some(context),
+            JsonError::Custom { context, .. } => context.as_deref_mut(),
+            JsonError::NonFiniteNumber { .. } => None,
+        }
+    }
+
+    /// Byte offset where the error was detected, or 0 for serialization errors.
+    pub fn offset(&self) -> usize {
+        self.context().map_or(0, |context| context.offset)
+    }
+
+    pub fn found(&self) -> Option<&str> {
+        self.context()?.found.as_deref()
+    }
+
+    pub fn expected(&self) -> &[&'static str] {
+        self.context().map_or(&[], |context| &context.expected)
+    }
+
+    /// Normalized JSONPath of the value being parsed, e.g. `$['a'][1]`.
+    pub fn path(&self) -> Option<&str> {
+        Some(&self.context()?.path)
+    }
+
+    /// The message without its position, e.g. ``invalid token: found `tru`, expected `true` ``.
+    pub(crate) fn summary(&self) -> String {
+        match self.details() {
+            Some(details) => format!("{}: {}", self.kind_text(), details),
+            None => self.kind_text(),
+        }
+    }
+
+    fn kind_text(&self) -> String {
+        match self {
+            JsonError::InvalidToken { .. } => "invalid token".to_string(),
+            JsonError::UnexpectedEof { .. } => "unexpected end of input".to_string(),
+            JsonError::InvalidEscapeSequence { .. } => "invalid escape".to_string(),
+            JsonError::InvalidNumber { .. } => "invalid number".to_string(),
+            JsonError::InvalidUtf8 { .. } => "invalid UTF-8".to_string(),
+            JsonError::Io { kind, .. } => format!("I/O error ({})", kind),
+            JsonError::LimitExceeded { limit, max, .. } => format!("{} limit of {} exceeded", limit, max),
+            JsonError::DuplicateKey { .. } => "duplicate key".to_string(),
+            JsonError::Custom { message, .. } => message.clone(),
+            JsonError::NonFiniteNumber { pointer } => {
+                format!("cannot serialize NaN or infinite number at '{}'", pointer)
+            }
+        }
+    }
+
+    // "found `x`, expected `,` or `]`", or `None` when neither is known.
+    fn details(&self) -> Option<String> {
+        let mut details = Vec::new();
+        if let Some(found) = self.found() {
+            details.push(format!("found {}", found));
+        }
+        if let Some((last, rest)) = self.expected().split_last() {
+            match rest {
+                [] => details.push(format!("expected {}", last)),
+                _ => details.push(format!("expected {} or {}", rest.join(", "), last)),
             }
-            JsonError::InvalidNumber { line, col } => write!(f, "Invalid number at {}:{}", line, col),
         }
+        if let JsonError::DuplicateKey { first, .. } = self {
+            details.push(format!("first defined at {}:{}", first.line, first.col));
+        }
+        (!details.is_empty()).then(|| details.join(", "))
+    }
+}
+
+impl ErrorContext {
+    fn boxed(at: Position, found: Option<String>, expected: &[&'static str]) -> Box<Self> {
+        Box::new(Self { offset: at.offset, found, expected: expected.to_vec(), path: "$".to_string() })
+    }
+}
+
+/// Reads like ``Invalid token at 2:8: found `tru`, expected `true` in $['a']``.
+impl fmt::Display for JsonError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let kind = self.kind_text();
+        let mut chars = kind.chars();
+        if let Some(first) = chars.next() {
+            write!(f, "{}{}", first.to_uppercase(), chars.as_str())?;
+        }
+        let Some(context) = self.context() else {
+            return Ok(());
+        };
+        write!(f, " at {}:{}", self.line(), self.col())?;
+        if let Some(details) = self.details() {
+            write!(f, ": {}", details)?;
+        }
+        if context.path != "$" {
+            write!(f, " in {}", context.path)?;
+        }
+        Ok(())
     }
 }
 
@@ -0,0 +1,587 @@
+//! Reading Rust types with serde straight from JSON text, without building a
+//! [`JsonValue`] first. Enabled by the `serde` feature.
+
+use std::fmt;
+use std::io::Read;
+use serde::de::{self, DeserializeOwned, DeserializeSeed, IntoDeserializer, Unexpected, Visitor};
+use serde::forward_to_deserialize_any;
+use crate::core::{JsonError, JsonNumber, JsonValue, RawNumber, Token};
+use crate::engine::{Parser, SeenKeys, EXPECT_END_ARRAY, EXPECT_END_OBJECT, EXPECT_VALUE};
+use crate::options::{DuplicateKeyPolicy, LoneSurrogatePolicy, ParseOptions};
+use crate::ser::RAW_NUMBER;
+use crate::source::{ReadSource, Source, StrSource};
+
+/// Reads a `T` from `input`.
+pub fn from_str<T: DeserializeOwned>(input: &str) -> Result<T, JsonError> {
+    from_str_with(input, &ParseOptions::default())
+}
+
+/// Reads a `T` from `input`, accepting the extensions enabled in `options`.
+pub fn from_str_with<T: DeserializeOwned>(input: &str, options: &ParseOptions) -> Result<T, JsonError> {
+    let mut deserializer = Deserializer::new(input, options)?;
+    let value = deserializer.read()?;
+    deserializer.end()?;
+    Ok(value)
+}
+
+/// Reads a `T` from a document pulled incrementally from `reader`.
+pub fn from_reader<R: Read, T: DeserializeOwned>(reader: R) -> Result<T, JsonError> {
+    from_reader_with(reader, &ParseOptions::default())
+}
+
+/// Reads a `T` from a document pulled incrementally from `reader`, accepting the
+/// extensions enabled in `options`.
+pub fn from_reader_with<R: Read, T: DeserializeOwned>(reader: R, options: &ParseOptions) -> Result<T, JsonError> {
+    let mut deserializer = Deserializer::from_reader(reader, options)?;
+    let value = deserializer.read()?;
+    deserializer.end()?;
+    Ok(value)
+}
+
+/// A serde `Deserializer` reading tokens from the same parser as
+/// [`deserialize_with`](crate::deserialize_with), with the same options,
+/// limits and error positions.
+///
+/// Objects are handed to the visitor member by member as they are read, so
+/// [`DuplicateKeyPolicy::First`] and [`DuplicateKeyPolicy::Last`] behave like
+/// [`DuplicateKeyPolicy::All`]; [`DuplicateKeyPolicy::Error`] still rejects a
+/// repeated name. A number kept as [`JsonNumber::Raw`] reaches
+/// `deserialize_any` as a one-member map that only [`JsonValue`] and
+/// [`JsonNumber`] understand; typed fields read it as usual. Serde strings
+/// are UTF-8, so [`LoneSurrogatePolicy::Preserve`] fails like
+/// [`LoneSurrogatePolicy::Error`].
+pub struct Deserializer<S> {
+    parser: Parser<S>,
+    // What may start the next value, for the message when it is missing.
+    expected: &'static [&'static str],
+}
+
+impl<'a> Deserializer<StrSource<'a>> {
+    pub fn new(input: &'a str, options: &ParseOptions) -> Result<Self, JsonError> {
+        Ok(Self { parser: Parser::new(input, utf8_only(options))?, expected: &[EXPECT_VALUE] })
+    }
+}
+
+impl<R: Read> Deserializer<ReadSource<R>> {
+    pub fn from_reader(reader: R, options: &ParseOptions) -> Result<Self, JsonError> {
+        Ok(Self { parser: Parser::from_reader(reader, utf8_only(options))?, expected: &[EXPECT_VALUE] })
+    }
+}
+
+fn utf8_only(options: &ParseOptions) -> ParseOptions {
+    match options.lone_surrogates {
+        LoneSurrogatePolicy::Preserve => ParseOptions { lone_surrogates: LoneSurrogatePolicy::Error, ..*options },
+        _ => *options,
+    }
+}
+
+impl<S: Source> Deserializer<S> {
+    /// Reads the next value as a `T`. Errors carry the position and path of
+    /// the value they were raised on, which they lack when `T::deserialize`
+    /// is called on the deserializer directly.
+    pub fn read<T: DeserializeOwned>(&mut self) -> Result<T, JsonError> {
+        T::deserialize(&mut *self).map_err(|e| self.parser.locate(e))
+    }
+
+    /// Fails unless the input ends after the value read.
+    pub fn end(&self) -> Result<(), JsonError> {
+        self.parser.end().map_err(|e| self.parser.locate(e))
+    }
+
+    fn take_expected(&mut self) -> &'static [&'static str] {
+        std::mem::replace(&mut self.expected, &[EXPECT_VALUE])
+    }
+
+    fn seen_keys(&self) -> SeenKeys {
+        match self.parser.options().duplicate_keys {
+            DuplicateKeyPolicy::Error => SeenKeys::new(DuplicateKeyPolicy::Error),
+            _ => SeenKeys::new(DuplicateKeyPolicy::All),
+        }
+    }
+
+    // Hands the scalar at the current token to `visit`, leaving the parser
+    // on it until the visitor is done so its errors point there.
+    fn scalar<T>(&mut self, visit: impl FnOnce(Token) -> Result<T, JsonError>) -> Result<T, JsonError> {
+        let expected = self.take_expected();
+        let value = visit(self.parser.begin_scalar(expected)?)?;
+        self.parser.end_scalar()?;
+        Ok(value)
+    }
+
+    fn deserialize_number<'de, V: Visitor<'de>>(&mut self, visitor: V) -> Result<V::Value, JsonError> {
+        match self.parser.token() {
+            Token::Number(JsonNumber::Raw(_)) => self.scalar(|token| match token {
+                Token::Number(JsonNumber::Raw(text)) => visit_raw_number(text.as_str(), visitor),
+                _ => unreachable!("the current token is a raw number"),
+            }),
+            _ => de::Deserializer::deserialize_any(self, visitor),
+        }
+    }
+}
+
+impl<'de, S: Source> de::Deserializer<'de> for &mut Deserializer<S> {
+    type Error = JsonError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, JsonError> {
+        match self.parser.token() {
+            Token::SquareLeft => {
+                self.take_expected();
+                let mut elements = Elements { de: self, started: false, done: false, len: 0 };
+                let value = visitor.visit_seq(&mut elements)?;
+                elements.finish()?;
+                Ok(value)
+            }
+            Token::CurlyLeft => {
+                self.take_expected();
+                let seen = self.seen_keys();
+                let mut members = Members { de: self, seen, started: false, done: false, len: 0 };
+                let value = visitor.visit_map(&mut members)?;
+                members.finish()?;
+                Ok(value)
+            }
+            _ => self.scalar(|token| match token {
+                Token::Null => visitor.visit_unit(),
+                Token::True => visitor.visit_bool(true),
+                Token::False => visitor.visit_bool(false),
+                Token::String(s) => visitor.visit_string(s),
+                Token::Number(JsonNumber::Integer(i)) => visitor.visit_i64(i),
+                Token::Number(JsonNumber::UnsignedInteger(u)) => visitor.visit_u64(u),
+                Token::Number(JsonNumber::Float(f)) => visitor.visit_f64(f),
+                Token::Number(JsonNumber::Raw(text)) => visitor.visit_map(RawNumberAccess { text: Some(text.as_str().to_string()) }),
+                _ => unreachable!("begin_scalar only returns scalars"),
+            }),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, JsonError> {
+        match self.parser.token() {
+            Token::Null => self.scalar(|_| visitor.visit_none()),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, JsonError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    /// A unit variant is its name as a string; any other variant is an
+    /// object with its name as the only member.
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, JsonError> {
+        match self.parser.token() {
+            Token::String(_) => self.scalar(|token| match token {
+                Token::String(name) => visitor.visit_enum(name.into_deserializer()),
+                _ => unreachable!("the current token is a string"),
+            }),
+            Token::CurlyLeft => {
+                self.take_expected();
+                self.parser.begin_object()?;
+                let mut seen = self.seen_keys();
+                if self.parser.next_member(0, &mut seen)?.is_none() {
+                    return Err(de::Error::invalid_length(0, &"an object with one member"));
+                }
+                let value = visitor.visit_enum(Variant { de: &mut *self })?;
+                self.parser.end_member();
+                if !matches!(self.parser.token(), Token::CurlyRight) {
+                    return Err(self.parser.unexpected(&[EXPECT_END_OBJECT]));
+                }
+                self.parser.end_object()?;
+                Ok(value)
+            }
+            _ => Err(self.parser.unexpected(&["a string", "`{`"])),
+        }
+    }
+
+    fn deserialize_i8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, JsonError> {
+        self.deserialize_number(visitor)
+    }
+
+    fn deserialize_i16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, JsonError> {
+        self.deserialize_number(visitor)
+    }
+
+    fn deserialize_i32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, JsonError> {
+        self.deserialize_number(visitor)
+    }
+
+    fn deserialize_i64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, JsonError> {
+        self.deserialize_number(visitor)
+    }
+
+    fn deserialize_i128<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, JsonError> {
+        self.deserialize_number(visitor)
+    }
+
+    fn deserialize_u8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, JsonError> {
+        self.deserialize_number(visitor)
+    }
+
+    fn deserialize_u16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, JsonError> {
+        self.deserialize_number(visitor)
+    }
+
+    fn deserialize_u32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, JsonError> {
+        self.deserialize_number(visitor)
+    }
+
+    fn deserialize_u64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, JsonError> {
+        self.deserialize_number(visitor)
+    }
+
+    fn deserialize_u128<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, JsonError> {
+        self.deserialize_number(visitor)
+    }
+
+    fn deserialize_f32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, JsonError> {
+        self.deserialize_number(visitor)
+    }
+
+    fn deserialize_f64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, JsonError> {
+        self.deserialize_number(visitor)
+    }
+
+    forward_to_deserialize_any! {
+        bool char str string bytes byte_buf unit unit_struct seq tuple
+        tuple_struct map struct identifier ignored_any
+    }
+}
+
+// Visits a number kept as text as the narrowest type that holds it exactly.
+fn visit_raw_number<'de, V: Visitor<'de>>(text: &str, visitor: V) -> Result<V::Value, JsonError> {
+    if let Ok(i) = text.parse::<i64>() {
+        visitor.visit_i64(i)
+    } else if let Ok(u) = text.parse::<u64>() {
+        visitor.visit_u64(u)
+    } else if let Ok(i) = text.parse::<i128>() {
+        visitor.visit_i128(i)
+    } else if let Ok(u) = text.parse::<u128>() {
+        visitor.visit_u128(u)
+    } else {
+        visitor.visit_f64(text.parse().unwrap_or(f64::NAN))
+    }
+}
+
+// The elements of an array. The `[` is only consumed once the visitor asks
+// for an element, so a visitor rejecting arrays outright fails at the `[`.
+struct Elements<'a, S> {
+    de: &'a mut Deserializer<S>,
+    started: bool,
+    done: bool,
+    len: usize,
+}
+
+impl<S: Source> Elements<'_, S> {
+    fn start(&mut self) -> Result<(), JsonError> {
+        if !self.started {
+            self.started = true;
+            self.de.parser.begin_array()?;
+        }
+        Ok(())
+    }
+
+    // Consumes the `]`, failing on elements the visitor left unread.
+    fn finish(mut self) -> Result<(), JsonError> {
+        self.start()?;
+        if !self.done && self.de.parser.next_element(self.len)?.is_some() {
+            return Err(self.de.parser.unexpected(&[EXPECT_END_ARRAY]));
+        }
+        self.de.parser.end_array()
+    }
+}
+
+impl<'de, S: Source> de::SeqAccess<'de> for Elements<'_, S> {
+    type Error = JsonError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>, JsonError> {
+        if self.done {
+            return Ok(None);
+        }
+        self.start()?;
+        let Some(expected) = self.de.parser.next_element(self.len)? else {
+            self.done = true;
+            return Ok(None);
+        };
+        self.de.expected = expected;
+        let value = seed.deserialize(&mut *self.de)?;
+        self.de.parser.end_element();
+        self.len += 1;
+        Ok(Some(value))
+    }
+}
+
+// The members of an object; the `{` is consumed as lazily as `Elements` does its `[`.
+struct Members<'a, S> {
+    de: &'a mut Deserializer<S>,
+    seen: SeenKeys,
+    started: bool,
+    done: bool,
+    len: usize,
+}
+
+impl<S: Source> Members<'_, S> {
+    fn start(&mut self) -> Result<(), JsonError> {
+        if !self.started {
+            self.started = true;
+            self.de.parser.begin_object()?;
+        }
+        Ok(())
+    }
+
+    // Consumes the `}`, failing on members the visitor left unread.
+    fn finish(mut self) -> Result<(), JsonError> {
+        self.start()?;
+        if !self.done && self.de.parser.next_member(self.len, &mut self.seen)?.is_some() {
+            return Err(self.de.parser.unexpected(&[EXPECT_END_OBJECT]));
+        }
+        self.de.parser.end_object()
+    }
+}
+
+impl<'de, S: Source> de::MapAccess<'de> for Members<'_, S> {
+    type Error = JsonError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, JsonError> {
+        if self.done {
+            return Ok(None);
+        }
+        self.start()?;
+        if self.de.parser.next_member(self.len, &mut self.seen)?.is_none() {
+            self.done = true;
+            return Ok(None);
+        }
+        seed.deserialize(KeyDeserializer { key: self.de.parser.current_key() }).map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, JsonError> {
+        let value = seed.deserialize(&mut *self.de)?;
+        self.de.parser.end_member();
+        self.len += 1;
+        Ok(value)
+    }
+}
+
+// The member of an object standing for an enum variant other than a unit one.
+struct Variant<'a, S> {
+    de: &'a mut Deserializer<S>,
+}
+
+impl<'de, S: Source> de::EnumAccess<'de> for Variant<'_, S> {
+    type Error = JsonError;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self), JsonError> {
+        let variant = seed.deserialize(KeyDeserializer { key: self.de.parser.current_key() })?;
+        Ok((variant, self))
+    }
+}
+
+impl<'de, S: Source> de::VariantAccess<'de> for Variant<'_, S> {
+    type Error = JsonError;
+
+    fn unit_variant(self) -> Result<(), JsonError> {
+        de::Deserialize::deserialize(self.de)
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, JsonError> {
+        seed.deserialize(self.de)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, JsonError> {
+        de::Deserializer::deserialize_seq(self.de, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(self, _fields: &'static [&'static str], visitor: V) -> Result<V::Value, JsonError> {
+        de::Deserializer::deserialize_map(self.de, visitor)
+    }
+}
+
+// A member name. Names spelling a number or boolean can be read as one, so
+// maps keyed by those types round-trip.
+struct KeyDeserializer<'a> {
+    key: &'a str,
+}
+
+macro_rules! deserialize_parsed_key {
+    ($($method:ident => $visit:ident,)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, JsonError> {
+                match self.key.parse() {
+                    Ok(value) => visitor.$visit(value),
+                    Err(_) => Err(de::Error::invalid_value(Unexpected::Str(self.key), &visitor)),
+                }
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for KeyDeserializer<'_> {
+    type Error = JsonError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, JsonError> {
+        visitor.visit_str(self.key)
+    }
+
+    deserialize_parsed_key! {
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_i128 => visit_i128,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_u128 => visit_u128,
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, JsonError> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, JsonError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, JsonError> {
+        visitor.visit_enum(IntoDeserializer::<JsonError>::into_deserializer(self.key))
+    }
+
+    forward_to_deserialize_any! {
+        f32 f64 char str string bytes byte_buf unit unit_struct seq tuple
+        tuple_struct map struct identifier ignored_any
+    }
+}
+
+// A number kept as text, passed on as `{RAW_NUMBER: "text"}`.
+struct RawNumberAccess {
+    text: Option<String>,
+}
+
+impl<'de> de::MapAccess<'de> for RawNumberAccess {
+    type Error = JsonError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, JsonError> {
+        if self.text.is_none() {
+            return Ok(None);
+        }
+        seed.deserialize(de::value::BorrowedStrDeserializer::new(RAW_NUMBER)).map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, JsonError> {
+        let text = self.text.take().expect("the key is read before the value");
+        seed.deserialize(text.into_deserializer())
+    }
+}
+
+impl de::Error for JsonError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        JsonError::custom(msg)
+    }
+}
+
+impl<'de> de::Deserialize<'de> for JsonValue {
+    fn deserialize<D: de::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(ValueVisitor)
+    }
+}
+
+impl<'de> de::Deserialize<'de> for JsonNumber {
+    fn deserialize<D: de::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match deserializer.deserialize_any(ValueVisitor)? {
+            JsonValue::Number(n) => Ok(n),
+            _ => Err(de::Error::custom("expected a JSON number")),
+        }
+    }
+}
+
+struct ValueVisitor;
+
+impl<'de> Visitor<'de> for ValueVisitor {
+    type Value = JsonValue;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("any JSON value")
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<JsonValue, E> {
+        Ok(JsonValue::Null)
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<JsonValue, E> {
+        Ok(JsonValue::Null)
+    }
+
+    fn visit_some<D: de::Deserializer<'de>>(self, deserializer: D) -> Result<JsonValue, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+
+    fn visit_bool<E: de::Error>(self, b: bool) -> Result<JsonValue, E> {
+        Ok(JsonValue::Bool(b))
+    }
+
+    fn visit_i64<E: de::Error>(self, i: i64) -> Result<JsonValue, E> {
+        Ok(JsonValue::Number(JsonNumber::Integer(i)))
+    }
+
+    fn visit_u64<E: de::Error>(self, u: u64) -> Result<JsonValue, E> {
+        let n = i64::try_from(u).map_or(JsonNumber::UnsignedInteger(u), JsonNumber::Integer);
+        Ok(JsonValue::Number(n))
+    }
+
+    fn visit_i128<E: de::Error>(self, i: i128) -> Result<JsonValue, E> {
+        match i64::try_from(i) {
+            Ok(i) => self.visit_i64(i),
+            Err(_) if i > 0 => self.visit_u128(i as u128),
+            Err(_) => Ok(JsonValue::Number(JsonNumber::Raw(RawNumber::new(i.to_string())))),
+        }
+    }
+
+    fn visit_u128<E: de::Error>(self, u: u128) -> Result<JsonValue, E> {
+        match u64::try_from(u) {
+            Ok(u) => self.visit_u64(u),
+            Err(_) => Ok(JsonValue::Number(JsonNumber::Raw(RawNumber::new(u.to_string())))),
+        }
+    }
+
+    fn visit_f64<E: de::Error>(self, f: f64) -> Result<JsonValue, E> {
+        Ok(JsonValue::Number(JsonNumber::Float(f)))
+    }
+
+    fn visit_str<E: de::Error>(self, s: &str) -> Result<JsonValue, E> {
+        Ok(JsonValue::String(s.to_string()))
+    }
+
+    fn visit_string<E: de::Error>(self, s: String) -> Result<JsonValue, E> {
+        Ok(JsonValue::String(s))
+    }
+
+    fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<JsonValue, A::Error> {
+        let mut elements = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(element) = seq.next_element()? {
+            elements.push(element);
+        }
+        Ok(JsonValue::Array(elements))
+    }
+
+    fn visit_map<A: de::MapAccess<'de>>(self, mut map: A) -> Result<JsonValue, A::Error> {
+        let mut pairs = Vec::with_capacity(map.size_hint().unwrap_or(0));
+        while let Some(key) = map.next_key::<String>()? {
+            if pairs.is_empty() && key == RAW_NUMBER {
+                let text: String = map.next_value()?;
+                return JsonNumber::from_lexeme(&text).map(JsonValue::Number).map_err(de::Error::custom);
+            }
+            pairs.push((key, map.next_value()?));
+        }
+        Ok(JsonValue::Object(pairs))
+    }
+}
@@ -0,0 +1,86 @@
+use std::fmt::Write;
+use crate::core::JsonError;
+
+/// Characters of a long line shown on either side of the error.
+const EXCERPT_RADIUS: usize = 40;
+
+impl JsonError {
+    /// Formats the error the way compilers do, quoting the offending line of
+    /// `source` with a caret under the error:
+    ///
+    /// ```text
+    /// error: invalid token: found `tru`, expected `true`
+    ///  --> config.json:2:8
+    ///   |
+    /// 2 |   "a": tru
+    ///   |        ^^^
+    ///   = note: while reading $['a']
+    /// ```
+    ///
+    /// `source` is the text that was parsed and `origin` names it, e.g. a
+    /// file name. The excerpt is left out when `source` does not reach the
+    /// error's offset.
+    pub fn render(&self, source: &str, origin: &str) -> String {
+        let mut out = format!("error: {}\n", self.summary());
+        let Some(context) = self.context() else {
+            return out;
+        };
+        let line_number = self.line().to_string();
+        let gutter = " ".repeat(line_number.len());
+        let _ = writeln!(out, "{}--> {}:{}:{}", gutter, origin, self.line(), self.col());
+        if let Some((text, caret_prefix)) = excerpt(source, context.offset) {
+            let width = underline_width(source, context.offset, context.found.as_deref());
+            let _ = writeln!(out, "{} |", gutter);
+            let _ = writeln!(out, "{} | {}", line_number, text);
+            let _ = writeln!(out, "{} | {}{}", gutter, caret_prefix, "^".repeat(width));
+        }
+        if context.path != "$" {
+            let _ = writeln!(out, "{} = note: while reading {}", gutter, context.path);
+        }
+        out
+    }
+}
+
+// The line containing `offset`, cut down around it when long, and the
+// whitespace that puts a caret under `offset`.
+fn excerpt(source: &str, offset: usize) -> Option<(String, String)> {
+    if offset > source.len() || !source.is_char_boundary(offset) {
+        return None;
+    }
+    let start = source[..offset].rfind('\n').map_or(0, |i| i + 1);
+    let end = source[offset..].find('\n').map_or(source.len(), |i| offset + i);
+    let line = source[start..end].trim_end_matches('\r');
+    let before: Vec<char> = line[..offset - start].chars().collect();
+    let after = line.get(offset - start..).unwrap_or("");
+
+    let skipped = before.len().saturating_sub(EXCERPT_RADIUS);
+    let mut text = String::new();
+    let mut caret_prefix = String::new();
+    if skipped > 0 {
+        text.push_str("...");
+        caret_prefix.push_str("   ");
+    }
+    for &c in &before[skipped..] {
+        text.push(c);
+        // Tabs stay tabs so the caret lines up however they are displayed.
+        caret_prefix.push(if c == '\t' { '\t' } else { ' ' });
+    }
+    let mut rest = after.chars();
+    text.extend(rest.by_ref().take(EXCERPT_RADIUS));
+    if rest.next().is_some() {
+        text.push_str("...");
+    }
+    Some((text, caret_prefix))
+}
+
+// Underlines the whole offending token when the source spells it out at
+// `offset`, e.g. three carets under `tru`; a single caret otherwise.
+fn underline_width(source: &str, offset: usize, found: Option<&str>) -> usize {
+    let token = found.and_then(|found| found.strip_prefix('`')?.strip_suffix('`'));
+    match token {
+        Some(token) if !token.contains('\n') && source.get(offset..).is_some_and(|s| s.starts_with(token)) => {
+            token.chars().count().clamp(1, EXCERPT_RADIUS)
+        }
+        _ => 1,
+    }
+}
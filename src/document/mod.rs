@@ -0,0 +1,429 @@
+use std::fmt;
+use crate::core::{JsonNumber, JsonValue, RawNumber};
+use crate::pointer::{not_found, parse_index, JsonPointer, PointerError};
+use crate::wtf8::Wtf8String;
+
+/// A parsed document laid out as one flat tape of entries in document
+/// order, rather than a tree of separately allocated values. Produced by
+/// [`deserialize_document`](crate::deserialize_document) and read through
+/// [`Node`] cursors.
+///
+/// Containers record how many entries they span, so moving past one is a
+/// single step. Strings without escapes are left in the input; the others
+/// share one buffer.
+pub struct Document<'a> {
+    input: &'a str,
+    tape: Vec<Entry>,
+    // Strings that had escapes, member names that were not quoted and the
+    // text of raw numbers.
+    strings: String,
+    // Strings with lone surrogates, read under `LoneSurrogatePolicy::Preserve`.
+    wtf8: Vec<Wtf8String>,
+}
+
+/// One value, or the start of one container, on the tape. Offsets fit in
+/// `u32` because documents are limited to 4 GiB.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Entry {
+    Null,
+    Bool(bool),
+    Integer(i64),
+    UnsignedInteger(u64),
+    Float(f64),
+    Raw(Span),
+    // Text in the input.
+    String(Span),
+    // Text in `Document::strings`.
+    Copied(Span),
+    // Index into `Document::wtf8`.
+    Wtf8(u32),
+    // `len` elements or members over the next `width - 1` entries; each
+    // member is its name followed by its value.
+    Array { len: u32, width: u32 },
+    Object { len: u32, width: u32 },
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Span {
+    start: u32,
+    end: u32,
+}
+
+impl Span {
+    pub(crate) fn new(start: usize, end: usize) -> Self {
+        Self { start: start as u32, end: end as u32 }
+    }
+}
+
+impl Entry {
+    // Entries taken up by the value starting with this one.
+    #[inline]
+    fn width(&self) -> usize {
+        match *self {
+            Entry::Array { width, .. } | Entry::Object { width, .. } => width as usize,
+            _ => 1,
+        }
+    }
+}
+
+// The largest document whose offsets fit in a `Span`.
+pub(crate) const MAX_INPUT_BYTES: usize = u32::MAX as usize;
+
+// Building the tape, for `Parser::parse_document`.
+impl<'a> Document<'a> {
+    pub(crate) fn new(input: &'a str) -> Self {
+        Self { input, tape: Vec::new(), strings: String::new(), wtf8: Vec::new() }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.tape.len()
+    }
+
+    pub(crate) fn push(&mut self, entry: Entry) {
+        self.tape.push(entry);
+    }
+
+    pub(crate) fn number(&mut self, number: JsonNumber) -> Entry {
+        match number {
+            JsonNumber::Integer(i) => Entry::Integer(i),
+            JsonNumber::UnsignedInteger(u) => Entry::UnsignedInteger(u),
+            JsonNumber::Float(f) => Entry::Float(f),
+            JsonNumber::Raw(text) => Entry::Raw(self.copy(text.as_str())),
+        }
+    }
+
+    /// A string that is either left in the input between `in_place`, or
+    /// `text` to be copied.
+    pub(crate) fn string(&mut self, in_place: Option<(usize, usize)>, text: &str) -> Entry {
+        match in_place {
+            Some((start, end)) => Entry::String(Span::new(start, end)),
+            None => Entry::Copied(self.copy(text)),
+        }
+    }
+
+    pub(crate) fn wtf8(&mut self, text: Wtf8String) -> Entry {
+        self.wtf8.push(text);
+        Entry::Wtf8((self.wtf8.len() - 1) as u32)
+    }
+
+    fn copy(&mut self, text: &str) -> Span {
+        let start = self.strings.len();
+        self.strings.push_str(text);
+        Span::new(start, self.strings.len())
+    }
+
+    /// Leaves room for a container, filled in by `close`.
+    pub(crate) fn open(&mut self) -> usize {
+        self.tape.push(Entry::Null);
+        self.tape.len() - 1
+    }
+
+    pub(crate) fn close(&mut self, at: usize, len: usize, object: bool) {
+        let (len, width) = (len as u32, (self.tape.len() - at) as u32);
+        self.tape[at] = if object { Entry::Object { len, width } } else { Entry::Array { len, width } };
+    }
+
+    /// Drops everything from `at` on, such as a member that is not kept.
+    pub(crate) fn truncate(&mut self, at: usize) {
+        self.tape.truncate(at);
+    }
+
+    /// Moves the value of the member at `member`, the last on the tape, over
+    /// that of member `index` of the object opened at `object`.
+    pub(crate) fn replace(&mut self, object: usize, index: usize, member: usize) {
+        let value = self.tape.split_off(member + 1);
+        self.tape.truncate(member);
+        let mut at = object + 1;
+        for _ in 0..index {
+            at += 1 + self.tape[at + 1].width();
+        }
+        let old = at + 1..at + 1 + self.tape[at + 1].width();
+        self.tape.splice(old, value);
+    }
+}
+
+impl<'a> Document<'a> {
+    /// The top-level value.
+    #[inline]
+    pub fn root(&self) -> Node<'_> {
+        Node { document: self, tape: &self.tape }
+    }
+
+    /// Builds the tree the document describes, as
+    /// [`deserialize`](crate::deserialize) would have.
+    pub fn to_value(&self) -> JsonValue {
+        self.root().to_value()
+    }
+
+    /// The input the document was parsed from.
+    pub fn input(&self) -> &'a str {
+        self.input
+    }
+
+    #[inline]
+    fn text(&self, entry: Entry) -> Option<&str> {
+        match entry {
+            Entry::String(Span { start, end }) => Some(&self.input[start as usize..end as usize]),
+            Entry::Copied(Span { start, end }) | Entry::Raw(Span { start, end }) => {
+                Some(&self.strings[start as usize..end as usize])
+            }
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Debug for Document<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Document").field("entries", &self.tape.len()).field("root", &self.root()).finish()
+    }
+}
+
+impl From<&Document<'_>> for JsonValue {
+    fn from(document: &Document<'_>) -> Self {
+        document.to_value()
+    }
+}
+
+/// A cursor on one value of a [`Document`]. Copying it is free, and moving
+/// to a member or element never allocates.
+#[derive(Clone, Copy)]
+pub struct Node<'d> {
+    document: &'d Document<'d>,
+    // The tape from this value's entry on.
+    tape: &'d [Entry],
+}
+
+// The accessors are small enough to inline into callers in other crates,
+// which walk documents with them one value at a time.
+impl<'d> Node<'d> {
+    #[inline]
+    fn entry(&self) -> Entry {
+        self.tape[0]
+    }
+
+    #[inline]
+    pub fn is_null(&self) -> bool {
+        matches!(self.entry(), Entry::Null)
+    }
+
+    #[inline]
+    pub fn is_bool(&self) -> bool {
+        matches!(self.entry(), Entry::Bool(_))
+    }
+
+    #[inline]
+    pub fn is_number(&self) -> bool {
+        matches!(self.entry(), Entry::Integer(_) | Entry::UnsignedInteger(_) | Entry::Float(_) | Entry::Raw(_))
+    }
+
+    #[inline]
+    pub fn is_string(&self) -> bool {
+        matches!(self.entry(), Entry::String(_) | Entry::Copied(_) | Entry::Wtf8(_))
+    }
+
+    #[inline]
+    pub fn is_array(&self) -> bool {
+        matches!(self.entry(), Entry::Array { .. })
+    }
+
+    #[inline]
+    pub fn is_object(&self) -> bool {
+        matches!(self.entry(), Entry::Object { .. })
+    }
+
+    #[inline]
+    pub fn as_bool(&self) -> Option<bool> {
+        match self.entry() {
+            Entry::Bool(b) => Some(b),
+            _ => None,
+        }
+    }
+
+    /// The number here; a raw number's text is copied out.
+    pub fn as_number(&self) -> Option<JsonNumber> {
+        match self.entry() {
+            Entry::Integer(i) => Some(JsonNumber::Integer(i)),
+            Entry::UnsignedInteger(u) => Some(JsonNumber::UnsignedInteger(u)),
+            Entry::Float(f) => Some(JsonNumber::Float(f)),
+            entry @ Entry::Raw(_) => self.document.text(entry).map(|text| JsonNumber::Raw(RawNumber::new(text.to_string()))),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn as_i64(&self) -> Option<i64> {
+        self.as_number()?.as_i64()
+    }
+
+    #[inline]
+    pub fn as_u64(&self) -> Option<u64> {
+        self.as_number()?.as_u64()
+    }
+
+    #[inline]
+    pub fn as_f64(&self) -> Option<f64> {
+        match self.entry() {
+            Entry::Integer(i) => Some(i as f64),
+            Entry::UnsignedInteger(u) => Some(u as f64),
+            Entry::Float(f) => Some(f),
+            _ => self.as_number().map(|n| n.as_f64()),
+        }
+    }
+
+    #[inline]
+    pub fn as_str(&self) -> Option<&'d str> {
+        match self.entry() {
+            entry @ (Entry::String(_) | Entry::Copied(_)) => self.document.text(entry),
+            _ => None,
+        }
+    }
+
+    /// A string holding lone surrogates; see [`JsonValue::Wtf8`].
+    pub fn as_wtf8(&self) -> Option<&'d Wtf8String> {
+        match self.entry() {
+            Entry::Wtf8(index) => Some(&self.document.wtf8[index as usize]),
+            _ => None,
+        }
+    }
+
+    /// Number of elements in an array or members in an object; 0 for scalars.
+    #[inline]
+    pub fn len(&self) -> usize {
+        match self.entry() {
+            Entry::Array { len, .. } | Entry::Object { len, .. } => len as usize,
+            _ => 0,
+        }
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Looks up `key` in an object. When a key is repeated the last
+    /// occurrence wins, as with [`JsonValue::get`].
+    ///
+    /// Every member is compared, so this takes time linear in the size of
+    /// the object; iterate [`members`](Self::members) to read many of them.
+    pub fn get(&self, key: &str) -> Option<Node<'d>> {
+        self.members().filter(|&(name, _)| name == key).last().map(|(_, value)| value)
+    }
+
+    /// Returns the array element at `index`, stepping over the ones before it.
+    ///
+    /// That is one step per earlier element however deeply it nests, but
+    /// still linear in `index`, unlike [`JsonValue::get_index`]; iterate
+    /// [`elements`](Self::elements) to read many of them.
+    pub fn get_index(&self, index: usize) -> Option<Node<'d>> {
+        self.elements().nth(index)
+    }
+
+    /// The elements of an array; nothing for other values.
+    #[inline]
+    pub fn elements(&self) -> Elements<'d> {
+        let len = if self.is_array() { self.len() } else { 0 };
+        Elements { document: self.document, rest: &self.tape[1..], len }
+    }
+
+    /// The names and values of an object's members; nothing for other values.
+    #[inline]
+    pub fn members(&self) -> Members<'d> {
+        let len = if self.is_object() { self.len() } else { 0 };
+        Members { document: self.document, rest: &self.tape[1..], len }
+    }
+
+    /// Resolves an RFC 6901 pointer from this value, as [`JsonValue::pointer`] does.
+    pub fn pointer(&self, pointer: &str) -> Result<Node<'d>, PointerError> {
+        let pointer = JsonPointer::parse(pointer)?;
+        let mut current = *self;
+        for token in pointer.tokens() {
+            current = match current.entry() {
+                Entry::Object { .. } => current.get(token),
+                Entry::Array { len, .. } => current.get_index(parse_index(token, len as usize, false, &pointer)?),
+                _ => None,
+            }
+            .ok_or_else(|| not_found(&pointer))?;
+        }
+        Ok(current)
+    }
+
+    /// Builds this value as a tree.
+    pub fn to_value(&self) -> JsonValue {
+        match self.entry() {
+            Entry::Null => JsonValue::Null,
+            Entry::Bool(b) => JsonValue::Bool(b),
+            Entry::Array { .. } => JsonValue::Array(self.elements().map(|element| element.to_value()).collect()),
+            Entry::Object { .. } => {
+                JsonValue::Object(self.members().map(|(name, value)| (name.to_string(), value.to_value())).collect())
+            }
+            Entry::String(_) | Entry::Copied(_) => JsonValue::String(self.as_str().unwrap_or_default().to_string()),
+            Entry::Wtf8(index) => JsonValue::Wtf8(self.document.wtf8[index as usize].clone()),
+            _ => self.as_number().map_or(JsonValue::Null, JsonValue::Number),
+        }
+    }
+}
+
+impl fmt::Debug for Node<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.entry() {
+            Entry::Array { .. } => f.debug_list().entries(self.elements()).finish(),
+            Entry::Object { .. } => f.debug_map().entries(self.members()).finish(),
+            Entry::Null => f.write_str("null"),
+            Entry::Bool(b) => write!(f, "{}", b),
+            Entry::String(_) | Entry::Copied(_) => write!(f, "{:?}", self.as_str().unwrap_or_default()),
+            Entry::Wtf8(index) => write!(f, "{:?}", self.document.wtf8[index as usize]),
+            _ => self.as_number().map_or(Ok(()), |n| write!(f, "{:?}", n)),
+        }
+    }
+}
+
+/// Iterator over an array's elements, from [`Node::elements`].
+pub struct Elements<'d> {
+    document: &'d Document<'d>,
+    rest: &'d [Entry],
+    len: usize,
+}
+
+impl<'d> Iterator for Elements<'d> {
+    type Item = Node<'d>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Node<'d>> {
+        self.len = self.len.checked_sub(1)?;
+        let node = Node { document: self.document, tape: self.rest };
+        self.rest = &self.rest[node.entry().width()..];
+        Some(node)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl ExactSizeIterator for Elements<'_> {}
+
+/// Iterator over an object's members, from [`Node::members`].
+pub struct Members<'d> {
+    document: &'d Document<'d>,
+    rest: &'d [Entry],
+    len: usize,
+}
+
+impl<'d> Iterator for Members<'d> {
+    type Item = (&'d str, Node<'d>);
+
+    #[inline]
+    fn next(&mut self) -> Option<(&'d str, Node<'d>)> {
+        self.len = self.len.checked_sub(1)?;
+        let name = self.document.text(self.rest[0]).unwrap_or_default();
+        let value = Node { document: self.document, tape: &self.rest[1..] };
+        self.rest = &value.tape[value.entry().width()..];
+        Some((name, value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl ExactSizeIterator for Members<'_> {}
@@ -1,16 +1,91 @@
+use std::io::{self, Read, Write};
+use crate::borrowed::BorrowedJsonValue;
 use crate::core::{JsonValue, JsonError};
+use crate::document::{self, Document};
 use crate::engine::Parser;
+use crate::options::ParseOptions;
+use crate::recovery::RecoveringParser;
+use crate::writer::{PrettyConfig, WriteOptions};
 
 
 pub fn deserialize(input: &str) -> Result<JsonValue, JsonError> {
-    let mut parser = Parser::new(input)?;
+    deserialize_with(input, &ParseOptions::default())
+}
+
+/// Parses `input`, accepting the extensions enabled in `options`.
+pub fn deserialize_with(input: &str, options: &ParseOptions) -> Result<JsonValue, JsonError> {
+    let mut parser = Parser::new(input, *options)?;
+    parser.parse()
+}
+
+/// Parses `input` into a value whose strings without escapes borrow from
+/// `input` instead of being copied.
+pub fn deserialize_borrowed(input: &str) -> Result<BorrowedJsonValue<'_>, JsonError> {
+    deserialize_borrowed_with(input, &ParseOptions::default())
+}
+
+pub fn deserialize_borrowed_with<'a>(input: &'a str, options: &ParseOptions) -> Result<BorrowedJsonValue<'a>, JsonError> {
+    let mut parser = Parser::borrowing(input, *options)?;
+    parser.parse_borrowed()
+}
+
+/// Parses `input` into a flat [`Document`] tape for repeated querying.
+/// Inputs over 4 GiB are rejected as exceeding
+/// [`Limits::max_input_bytes`](crate::Limits::max_input_bytes).
+pub fn deserialize_document(input: &str) -> Result<Document<'_>, JsonError> {
+    deserialize_document_with(input, &ParseOptions::default())
+}
+
+pub fn deserialize_document_with<'a>(input: &'a str, options: &ParseOptions) -> Result<Document<'a>, JsonError> {
+    let mut options = *options;
+    options.limits.max_input_bytes = options.limits.max_input_bytes.min(document::MAX_INPUT_BYTES);
+    let mut parser = Parser::borrowing(input, options)?;
+    parser.parse_document()
+}
+
+/// Parses a document pulled incrementally from `reader`, decoding UTF-8 as it goes.
+pub fn deserialize_reader<R: Read>(reader: R) -> Result<JsonValue, JsonError> {
+    deserialize_reader_with(reader, &ParseOptions::default())
+}
+
+pub fn deserialize_reader_with<R: Read>(reader: R, options: &ParseOptions) -> Result<JsonValue, JsonError> {
+    let mut parser = Parser::from_reader(reader, *options)?;
     parser.parse()
 }
 
+/// Parses `input` without stopping at the first error, returning a
+/// best-effort value together with every error found. The document is valid
+/// exactly when the list is empty.
+pub fn deserialize_recovering(input: &str, options: &ParseOptions) -> (JsonValue, Vec<JsonError>) {
+    RecoveringParser::new(input, *options).parse()
+}
+
+/// Like [`deserialize_recovering`], reading the document from `reader`.
+pub fn deserialize_reader_recovering<R: Read>(reader: R, options: &ParseOptions) -> (JsonValue, Vec<JsonError>) {
+    RecoveringParser::from_reader(reader, *options).parse()
+}
+
 pub fn serialize_pretty(value: &JsonValue) -> String {
     value.to_json_string_pretty()
 }
 
+pub fn serialize_pretty_with(value: &JsonValue, config: &PrettyConfig) -> String {
+    value.to_json_string_with(config)
+}
+
 pub fn serialize(value: &JsonValue) -> String {
     value.to_json_string()
-}
\ No newline at end of file
+}
+
+/// Pretty output when `config` is given, compact otherwise.
+pub fn serialize_with_options(value: &JsonValue, config: Option<&PrettyConfig>, options: &WriteOptions) -> Result<String, JsonError> {
+    value.to_json_string_with_options(config, options)
+}
+
+pub fn serialize_to_writer<W: Write>(value: &JsonValue, writer: W) -> io::Result<()> {
+    value.write_json(writer)
+}
+
+pub fn serialize_pretty_to_writer<W: Write>(value: &JsonValue, writer: W) -> io::Result<()> {
+    value.write_json_pretty(writer)
+}
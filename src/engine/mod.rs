@@ -1,168 +1,991 @@
+use std::borrow::Cow;
+use std::cell::Cell;
+use std::collections::HashMap;
 use std::string::String;
-use std::iter::Peekable;
-use std::str::Chars;
-use crate::core::{JsonError, Token, JsonValue};
+use std::io::{self, Read};
+use crate::borrowed::BorrowedJsonValue;
+use crate::core::{describe_char, quote, JsonError, JsonNumber, Limit, Position, Token, JsonValue};
+use crate::document::{Document, Entry, Span};
+use crate::jsonpath::{normalized_path, PathElement};
+use crate::options::{DuplicateKeyPolicy, LoneSurrogatePolicy, ParseOptions};
 use crate::parser::{NumberParser, StringEscaper};
+use crate::scan;
+use crate::source::{ReadSource, Source, StrSource};
+use crate::wtf8::Wtf8String;
 
-pub struct Lexer<'a>{
-    chars: Peekable<Chars<'a>>,
-    line: usize,
-    col:usize,
+/// Splits the bytes of a [`Source`] into tokens. Only offsets are tracked
+/// while scanning; lines and columns are counted when a position is asked
+/// for, which mostly means when an error is reported.
+pub struct Lexer<S>{
+    source: S,
+    options: ParseOptions,
+    // Offset of the next byte to read, and of the last token returned.
+    offset: usize,
+    token_start: usize,
+    // The last two positions worked out, so lines and columns are counted
+    // on from there rather than from the start of the input.
+    known: Cell<(Position, Position)>,
+    // Errors recovered from in recovering mode; `None` fails on the first.
+    recovered: Option<Vec<JsonError>>,
+    // In borrowing mode strings without escapes are left in the input, and
+    // `in_place` says whether the last string token was.
+    borrowing: bool,
+    in_place: bool,
+    // Lone surrogates kept under `LoneSurrogatePolicy::Preserve` in the
+    // string being read, by the byte offset they go at.
+    surrogates: Vec<(usize, u16)>,
 }
 
-impl<'a> Lexer<'a> {
-    pub(crate) fn new(input: &'a str) -> Self {
+impl<'a> Lexer<StrSource<'a>> {
+    pub(crate) fn new(input: &'a str, options: ParseOptions) -> Self {
+        Self::with_source(StrSource::new(input), options)
+    }
+
+    /// Makes the lexer leave strings without escapes in the input: their
+    /// tokens come back empty, with the text at [`string_in_place`](Self::string_in_place).
+    pub(crate) fn set_borrowing(&mut self) {
+        self.borrowing = true;
+    }
+}
+
+impl<R: Read> Lexer<ReadSource<R>> {
+    pub(crate) fn from_reader(reader: R, options: ParseOptions) -> Self {
+        Self::with_source(ReadSource::new(reader), options)
+    }
+}
+
+impl<S: Source> Lexer<S> {
+    pub(crate) fn with_source(source: S, options: ParseOptions) -> Self {
+        let start = source.start();
         Self {
-            chars: input.chars().peekable(),
-            line: 1,
-            col:1,
+            source,
+            options,
+            offset: start.offset,
+            token_start: start.offset,
+            known: Cell::new((start, start)),
+            recovered: None,
+            borrowing: false,
+            in_place: false,
+            surrogates: Vec::new(),
         }
     }
 
-    fn advance_char(&mut self) -> Option<char> {
-        if let Some(c) = self.chars.next() {
-            if c == '\n' {
-                self.line +=1;
-                self.col = 1;
-            } else {
-                self.col +=1;
+    /// Makes the lexer carry on past malformed strings, numbers, literals
+    /// and comments, collecting the errors for [`take_recovered`](Self::take_recovered).
+    /// Malformed numbers and literals are read as `null`.
+    pub(crate) fn set_recovering(&mut self) {
+        self.recovered.get_or_insert_with(Vec::new);
+    }
+
+    /// The errors recovered from since the last call.
+    pub(crate) fn take_recovered(&mut self) -> Vec<JsonError> {
+        self.recovered.as_mut().map(std::mem::take).unwrap_or_default()
+    }
+
+    // Records `error` and carries on when recovering; fails otherwise. The
+    // end of input, invalid UTF-8 and I/O failures are never recovered from.
+    fn recover(&mut self, error: JsonError) -> Result<(), JsonError> {
+        match &mut self.recovered {
+            Some(errors) if matches!(
+                error,
+                JsonError::InvalidToken { .. } | JsonError::InvalidNumber { .. } | JsonError::InvalidEscapeSequence { .. }
+            ) => {
+                errors.push(error);
+                Ok(())
+            }
+            _ => Err(error),
+        }
+    }
+
+    pub(crate) fn options(&self) -> &ParseOptions {
+        &self.options
+    }
+
+    pub(crate) fn position(&self) -> Position {
+        self.position_at(self.offset)
+    }
+
+    /// Where the most recently returned token began.
+    pub(crate) fn token_start(&self) -> Position {
+        self.position_at(self.token_start)
+    }
+
+    // The line and column of `offset`, which must still be buffered: the
+    // source keeps everything from the start of the last token on.
+    fn position_at(&self, offset: usize) -> Position {
+        let start = self.source.start();
+        let (last, before) = self.known.get();
+        if last.offset == offset {
+            return last;
+        }
+        let from = [last, before]
+            .into_iter()
+            .filter(|known| (start.offset..=offset).contains(&known.offset))
+            .max_by_key(|known| known.offset)
+            .unwrap_or(start);
+        let position = scan::advance(from, &self.source.buffer()[from.offset - start.offset..offset - start.offset]);
+        self.known.set(if offset > last.offset { (position, last) } else { (last, position) });
+        position
+    }
+
+    /// Byte range of the text of the most recently returned token, when it
+    /// is a string left in the input.
+    pub(crate) fn string_in_place(&self) -> Option<(usize, usize)> {
+        // Past the opening quote, up to the closing one.
+        self.in_place.then(|| (self.token_start + 1, self.offset - 1))
+    }
+
+    /// Like [`Token::describe`], reading strings left in the input.
+    pub(crate) fn describe(&self, token: &Token) -> Option<String> {
+        match (token, self.string_in_place()) {
+            (Token::String(_), Some((start, end))) => {
+                Token::String(self.source.text(start, end).unwrap_or_default().to_string()).describe()
+            }
+            (token, _) => token.describe(),
+        }
+    }
+
+    // The buffered bytes from the current offset on, reading more until
+    // there are at least `want` or the input ends.
+    fn buffered(&mut self, want: usize) -> Result<&[u8], JsonError> {
+        while self.source.start().offset + self.source.buffer().len() < self.offset + want {
+            match self.source.refill(self.token_start) {
+                Ok(true) => {}
+                Ok(false) => break,
+                Err(e) => return Err(self.io_error(e)),
             }
-            Some(c)
-        } else {
-            None
+        }
+        Ok(&self.source.buffer()[self.offset - self.source.start().offset..])
+    }
+
+    // Bytes that can be consumed before `max_input_bytes` is reached.
+    fn room(&self) -> usize {
+        self.options.limits.max_input_bytes.saturating_sub(self.offset)
+    }
+
+    // Moves past `len` bytes that have been looked at. Input is counted
+    // against `max_input_bytes` as it is consumed, not as it is peeked at.
+    fn consume(&mut self, len: usize) -> Result<(), JsonError> {
+        self.options.limits.check(Limit::InputBytes, self.offset + len, || self.position())?;
+        self.offset += len;
+        Ok(())
+    }
+
+    fn io_error(&self, e: io::Error) -> JsonError {
+        JsonError::io(e.kind(), self.position())
+    }
+
+    // The next byte; when it starts a multi-byte character, that character
+    // must be valid UTF-8.
+    fn peek(&mut self) -> Result<Option<u8>, JsonError> {
+        match self.peek_byte()? {
+            Some(byte) if byte >= 0x80 => self.peek_char().map(|_| Some(byte)),
+            next => Ok(next),
+        }
+    }
+
+    fn peek_byte(&mut self) -> Result<Option<u8>, JsonError> {
+        if let Some(&byte) = self.source.buffer().get(self.offset - self.source.start().offset) {
+            return Ok(Some(byte));
+        }
+        Ok(self.buffered(1)?.first().copied())
+    }
+
+    fn peek_char(&mut self) -> Result<Option<char>, JsonError> {
+        let width = match self.peek_byte()? {
+            None => return Ok(None),
+            Some(byte @ 0x00..=0x7F) => return Ok(Some(char::from(byte))),
+            Some(0xC0..=0xDF) => 2,
+            Some(0xE0..=0xEF) => 3,
+            Some(0xF0..=0xF7) => 4,
+            Some(_) => 1,
+        };
+        let bytes = self.buffered(width)?;
+        let decoded = std::str::from_utf8(&bytes[..width.min(bytes.len())]).ok().and_then(|s| s.chars().next());
+        decoded.map(Some).ok_or_else(|| JsonError::invalid_utf8(self.position()))
+    }
+
+    fn advance_char(&mut self, c: char) -> Result<(), JsonError> {
+        self.consume(c.len_utf8())
+    }
+
+    fn next_char(&mut self) -> Result<Option<char>, JsonError> {
+        let next = self.peek_char()?;
+        if let Some(c) = next {
+            self.advance_char(c)?;
+        }
+        Ok(next)
+    }
+
+    // The input from `start` up to the current offset, which has been
+    // checked to be UTF-8 on the way.
+    fn consumed(&self, start: usize) -> &str {
+        self.source.text(start, self.offset).unwrap_or_default()
+    }
+
+    // The input from `start` up to the current offset, checking that it is
+    // UTF-8: string bodies are scanned a run of bytes at a time.
+    fn checked(&self, start: usize) -> Result<&str, JsonError> {
+        match self.source.text(start, self.offset) {
+            Ok(text) if start + text.len() == self.offset => Ok(text),
+            Ok(text) => Err(JsonError::invalid_utf8(self.position_at(start + text.len()))),
+            Err(at) => Err(JsonError::invalid_utf8(self.position_at(at))),
         }
     }
 
     pub(crate) fn next_token(&mut self) -> Result<Token, JsonError> {
-        self.skip_whitespace();
-
-        match self.chars.peek() {
-            Some('{') => {self.advance_char(); Ok(Token::CurlyLeft)}
-            Some('}') => {self.advance_char(); Ok(Token::CurlyRight)}
-            Some('[') => {self.advance_char(); Ok(Token::SquareLeft)}
-            Some(']') => {self.advance_char(); Ok(Token::SquareRight)}
-            Some(':') => {self.advance_char(); Ok(Token::Colon)}
-            Some(',') => {self.advance_char(); Ok(Token::Comma)}
-            Some('"') => self.parse_string(),
-            Some(c) if c.is_ascii_digit() || *c == '-' => self.parse_number(),
+        self.skip_whitespace()?;
+        self.token_start = self.offset;
+        self.in_place = false;
+
+        let token = match self.peek()? {
+            Some(b'{') => {self.consume(1)?; Ok(Token::CurlyLeft)}
+            Some(b'}') => {self.consume(1)?; Ok(Token::CurlyRight)}
+            Some(b'[') => {self.consume(1)?; Ok(Token::SquareLeft)}
+            Some(b']') => {self.consume(1)?; Ok(Token::SquareRight)}
+            Some(b':') => {self.consume(1)?; Ok(Token::Colon)}
+            Some(b',') => {self.consume(1)?; Ok(Token::Comma)}
+            Some(b'"') => self.parse_string(b'"'),
+            Some(b'\'') if self.options.single_quotes => self.parse_string(b'\''),
+            Some(b) if b.is_ascii_digit() || b == b'-' => self.parse_number(),
+            Some(b'+') if self.options.leading_plus => self.parse_number(),
+            Some(b'.') if self.options.bare_decimal_point => self.parse_number(),
+            Some(_) => self.parse_word(),
+            None => Ok(Token::Eof)
+        };
+        token.or_else(|e| {
+            self.recover(e)?;
+            Ok(Token::Null)
+        })
+    }
+
+    // Literals, identifiers and any other character.
+    fn parse_word(&mut self) -> Result<Token, JsonError> {
+        match self.peek_char()? {
+            Some(c) if self.allows_identifiers() && is_identifier_start(c) => self.parse_identifier(),
             Some('t') => self.parse_literal("true", Token::True),
             Some('f') => self.parse_literal("false", Token::False),
             Some('n') => self.parse_literal("null", Token::Null),
-            Some(_) => {
-                let (line, col) = (self.line, self.col);
-                self.advance_char();
-                Err(JsonError::InvalidToken { line, col })
+            Some(c) => {
+                self.advance_char(c)?;
+                if is_identifier_start(c) {
+                    self.take_word()?;
+                }
+                Ok(Token::Unexpected(self.consumed(self.token_start).to_string()))
             }
-            _ => Ok(Token::Eof)
+            None => Ok(Token::Eof),
         }
     }
 
-    fn skip_whitespace(&mut self) {
-        while let Some(&c) = self.chars.peek(){
-            if c.is_whitespace() {
-                self.advance_char();
-            } else {
-                break;
+    fn skip_whitespace(&mut self) -> Result<(), JsonError> {
+        loop {
+            match self.peek()? {
+                Some(b' ' | b'\t' | b'\n' | b'\r') => {
+                    let room = self.room();
+                    let bytes = self.buffered(1)?;
+                    match scan::whitespace_run(&bytes[..bytes.len().min(room)]) {
+                        0 => self.consume(1)?,
+                        run => self.offset += run,
+                    }
+                }
+                Some(b'/') if self.options.line_comments || self.options.block_comments => {
+                    self.skip_comment().or_else(|e| self.recover(e))?
+                }
+                Some(_) if self.options.extended_whitespace => match self.peek_char()? {
+                    Some(c) if self.is_whitespace(c) => self.advance_char(c)?,
+                    _ => return Ok(()),
+                },
+                _ => return Ok(()),
             }
         }
     }
-    fn parse_string(&mut self) -> Result<Token, JsonError> {
-        let (start_line, start_col) = (self.line, self.col);
-        self.advance_char();
+
+    fn skip_comment(&mut self) -> Result<(), JsonError> {
+        let start = self.offset;
+        self.consume(1)?; // consume '/'
+        match self.next_char()? {
+            Some('/') if self.options.line_comments => {
+                while let Some(c) = self.next_char()? {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+                Ok(())
+            }
+            Some('*') if self.options.block_comments => loop {
+                match self.next_char()? {
+                    Some('*') if self.peek()? == Some(b'/') => {
+                        self.consume(1)?;
+                        return Ok(());
+                    }
+                    Some(_) => {}
+                    None => return Err(JsonError::unexpected_eof(self.position_at(start), &["`*/`"])),
+                }
+            },
+            _ => {
+                let expected: &[&str] = match (self.options.line_comments, self.options.block_comments) {
+                    (true, true) => &["`//`", "`/*`"],
+                    (true, false) => &["`//`"],
+                    _ => &["`/*`"],
+                };
+                Err(JsonError::invalid_token(self.position_at(start), Some(quote("/")), expected))
+            }
+        }
+    }
+
+    // RFC 8259 only allows space, tab, LF and CR between tokens.
+    fn is_whitespace(&self, c: char) -> bool {
+        match c {
+            ' ' | '\t' | '\n' | '\r' => true,
+            '\u{FEFF}' => self.options.extended_whitespace,
+            '\u{0085}' => false,
+            c => self.options.extended_whitespace && c.is_whitespace(),
+        }
+    }
+
+    // Line breaks must be escaped even when other control characters may appear raw.
+    fn allows_raw_control(&self, c: char) -> bool {
+        self.options.unescaped_control_characters && c != '\n' && c != '\r'
+    }
+
+    fn allows_identifiers(&self) -> bool {
+        self.options.unquoted_keys || self.options.special_floats
+    }
+
+    fn parse_string(&mut self, quote: u8) -> Result<Token, JsonError> {
+        let start = self.offset;
+        self.consume(1)?;
 
         let mut string = String::new();
+        // Where the text not yet copied into `string` begins.
+        let mut pending = self.offset;
+        self.in_place = self.borrowing;
+        self.surrogates.clear();
         loop{
-            match self.advance_char() {
-                Some('"') => return Ok(Token::String(string)),
-                Some('\\') => {
-                    match self.advance_char(){
-                        Some('u') => {
-                            let mut hex_chars = String::with_capacity(4);
-                            for _ in 0..4 {
-                                match self.advance_char(){
-                                    Some(c) if c.is_ascii_hexdigit() => hex_chars.push(c),
-                                    _ => return Err(JsonError::InvalidEscapeSequence {
-                                        line: self.line,
-                                        col: self.col,
-                                    }),
-                                }
-                            }
-                            let ch = StringEscaper::parse_unicode_hex(&hex_chars)
-                                .map_err(|_| JsonError::InvalidEscapeSequence {
-                                    line:self.line,
-                                    col:self.col,
-                                })?;
-                            string.push(ch);
-                        }
-                        Some(c) => {
-                            let escaped = StringEscaper::unescape_char(c)
-                                .map_err(|_| JsonError::InvalidEscapeSequence {
-                                    line:self.line,
-                                    col:self.col,
-                                })?;
-                            string.push(escaped);
+            let len = string.len() + 3 * self.surrogates.len() + (self.offset - pending);
+            self.options.limits.check(Limit::StringBytes, len, || self.position_at(start))?;
+            // Take the plain text up to the next quote, backslash or control
+            // character in one run, short of where a limit would be reached.
+            let room = self.room().min(self.options.limits.max_string_bytes - len);
+            let bytes = self.buffered(1)?;
+            let bytes = &bytes[..bytes.len().min(room)];
+            let run = scan::char_boundary(bytes, scan::string_run(bytes, quote));
+            self.offset += run;
+            // Raw, so that bad UTF-8 in the run is reported before the byte after it.
+            match self.peek_byte()? {
+                Some(b) if b == quote => {
+                    if !self.in_place {
+                        string.push_str(self.checked(pending)?);
+                    }
+                    self.consume(1)?;
+                    if self.surrogates.is_empty() {
+                        return Ok(Token::String(string));
+                    }
+                    return Ok(Token::Wtf8(Wtf8String::splice(&string, &std::mem::take(&mut self.surrogates))));
+                }
+                Some(b'\\') => {
+                    // An escaped string cannot be borrowed; copy what came before.
+                    self.in_place = false;
+                    string.push_str(self.checked(pending)?);
+                    let at = self.offset;
+                    self.consume(1)?;
+                    self.parse_escape(at, &mut string).or_else(|e| self.recover(e))?;
+                    pending = self.offset;
+                }
+                Some(b) if b < 0x20 => {
+                    // Kept with the pending text when allowed or recovered from.
+                    let (c, at) = (char::from(b), self.offset);
+                    self.consume(1)?;
+                    if !self.allows_raw_control(c) {
+                        self.checked(pending)?;
+                        self.recover(JsonError::invalid_token(self.position_at(at), Some(describe_char(c)), &["an escape sequence"]))?;
+                    }
+                }
+                // The run stopped at a limit or where the buffer did; the
+                // character there decides which.
+                Some(_) if run == 0 => {
+                    self.checked(pending)?;
+                    self.next_char()?;
+                }
+                Some(_) => {}
+                None => {
+                    self.checked(pending)?;
+                    let closing = if quote == b'"' { "`\"`" } else { "`'`" };
+                    return Err(JsonError::unexpected_eof(self.position_at(start), &[closing]));
+                }
+            }
+        }
+    }
+
+    // Called after the backslash at `at` has been consumed.
+    fn parse_escape(&mut self, at: usize, string: &mut String) -> Result<(), JsonError> {
+        match self.next_char()? {
+            Some('u') => {
+                let unit = self.parse_code_unit(at)?;
+                self.push_code_unit(at, unit, string)
+            }
+            Some(c) => match StringEscaper::unescape_char(c) {
+                Some(escaped) => { string.push(escaped); Ok(()) }
+                None if c == '\'' && self.options.single_quotes => { string.push(c); Ok(()) }
+                None if self.options.extended_escapes => self.parse_extended_escape(at, c, string),
+                None => Err(JsonError::invalid_escape(self.position_at(at), quote(&format!("\\{}", c)), ESCAPES)),
+            },
+            None => Err(JsonError::invalid_escape(self.position_at(at), quote("\\"), ESCAPES)),
+        }
+    }
+
+    // The four hex digits after `\u`.
+    fn parse_code_unit(&mut self, at: usize) -> Result<u32, JsonError> {
+        let mut hex_chars = String::with_capacity(4);
+        for _ in 0..4 {
+            match self.next_char()? {
+                Some(c) if c.is_ascii_hexdigit() => hex_chars.push(c),
+                c => {
+                    let found = format!("\\u{}{}", hex_chars, c.map(String::from).unwrap_or_default());
+                    return Err(JsonError::invalid_escape(self.position_at(at), quote(&found), &["a hex digit"]));
+                }
+            }
+        }
+        StringEscaper::parse_unicode_hex(&hex_chars).ok_or_else(|| {
+            JsonError::invalid_escape(self.position_at(at), quote(&format!("\\u{}", hex_chars)), &["a hex digit"])
+        })
+    }
+
+    // Characters above U+FFFF arrive as a high surrogate escape immediately
+    // followed by a low surrogate escape; anything else is a lone surrogate.
+    fn push_code_unit(&mut self, mut at: usize, mut unit: u32, string: &mut String) -> Result<(), JsonError> {
+        loop {
+            if !(0xD800..0xDC00).contains(&unit) {
+                return match char::from_u32(unit) {
+                    Some(c) => { string.push(c); Ok(()) }
+                    None => self.lone_surrogate(at, unit, string),
+                };
+            }
+            if self.peek()? != Some(b'\\') {
+                return self.lone_surrogate(at, unit, string);
+            }
+            let next_at = self.offset;
+            self.consume(1)?;
+            if self.peek()? != Some(b'u') {
+                self.lone_surrogate(at, unit, string)?;
+                return self.parse_escape(next_at, string);
+            }
+            self.consume(1)?;
+            let next = self.parse_code_unit(next_at)?;
+            if let Some(c) = StringEscaper::combine_surrogates(unit, next) {
+                string.push(c);
+                return Ok(());
+            }
+            self.lone_surrogate(at, unit, string)?;
+            (at, unit) = (next_at, next);
+        }
+    }
+
+    fn lone_surrogate(&mut self, at: usize, unit: u32, string: &mut String) -> Result<(), JsonError> {
+        match self.options.lone_surrogates {
+            LoneSurrogatePolicy::Error => {
+                let found = quote(&format!("\\u{:04x}", unit));
+                Err(JsonError::invalid_escape(self.position_at(at), found, &["a surrogate pair"]))
+            }
+            LoneSurrogatePolicy::Replace => { string.push(char::REPLACEMENT_CHARACTER); Ok(()) }
+            LoneSurrogatePolicy::Preserve => { self.surrogates.push((string.len(), unit as u16)); Ok(()) }
+        }
+    }
+
+    // JSON5 escapes beyond RFC 8259: `\v`, `\0`, `\xHH`, line continuations
+    // and any other non-digit character standing for itself.
+    fn parse_extended_escape(&mut self, at: usize, c: char, string: &mut String) -> Result<(), JsonError> {
+        match c {
+            'v' => string.push('\u{000B}'),
+            '0' if !self.peek()?.is_some_and(|b| b.is_ascii_digit()) => string.push('\0'),
+            'x' => {
+                let mut hex = String::with_capacity(2);
+                for _ in 0..2 {
+                    match self.next_char()? {
+                        Some(c) if c.is_ascii_hexdigit() => hex.push(c),
+                        c => {
+                            let found = format!("\\x{}{}", hex, c.map(String::from).unwrap_or_default());
+                            return Err(JsonError::invalid_escape(self.position_at(at), quote(&found), &["a hex digit"]));
                         }
-                        None => return Err(JsonError::InvalidEscapeSequence {
-                            line:self.line,
-                            col:self.col,
-                        }),
                     }
                 }
-                Some(c) => string.push(c),
-                None => return Err(JsonError::UnexpectedEof{
-                    line: start_line,
-                    col: start_col,
-                }),
+                // Two hex digits are at most U+00FF, always a valid char.
+                string.push(char::from(u8::from_str_radix(&hex, 16).unwrap_or_default()));
+            }
+            '\r' => {
+                if self.peek()? == Some(b'\n') {
+                    self.consume(1)?;
+                }
             }
+            '\n' | '\u{2028}' | '\u{2029}' => {}
+            '0'..='9' => return Err(JsonError::invalid_escape(self.position_at(at), quote(&format!("\\{}", c)), &[])),
+            c => string.push(c),
         }
+        Ok(())
+    }
+
+    fn parse_identifier(&mut self) -> Result<Token, JsonError> {
+        while let Some(c) = self.peek_char()?
+            && is_identifier_part(c)
+        {
+            self.advance_char(c)?;
+            self.options.limits.check(Limit::StringBytes, self.offset - self.token_start, || self.token_start())?;
+        }
+        Ok(match self.consumed(self.token_start) {
+            "true" => Token::True,
+            "false" => Token::False,
+            "null" => Token::Null,
+            name => Token::Identifier(name.to_string()),
+        })
     }
 
     fn parse_number(&mut self) -> Result<Token, JsonError> {
-        let (start_line, start_col) = (self.line, self.col);
-        let mut number_str = String::new();
+        let start = self.offset;
+        let negative = match self.peek()? {
+            Some(sign @ (b'-' | b'+')) => {
+                self.consume(1)?;
+                sign == b'-'
+            }
+            _ => false,
+        };
+        let sign = if negative { -1.0 } else { 1.0 };
+        match self.peek()? {
+            Some(b'I') if self.options.special_floats => {
+                return self.parse_literal("Infinity", Token::Number(JsonNumber::Float(sign * f64::INFINITY)));
+            }
+            Some(b'N') if self.options.special_floats => {
+                return self.parse_literal("NaN", Token::Number(JsonNumber::Float(f64::NAN)));
+            }
+            _ => {}
+        }
+
+        // int = "0" / digit1-9 *DIGIT
+        let int_start = self.offset;
+        let int_digits = self.scan_digits()?;
+        if self.options.hex_numbers
+            && self.consumed(int_start) == "0"
+            && let Some(b'x' | b'X') = self.peek()?
+        {
+            self.consume(1)?;
+            return self.parse_hex(negative, start);
+        }
+        if int_digits > 1 && self.consumed(int_start).starts_with('0') {
+            return Err(self.invalid_number(start, &[]));
+        }
+
+        // frac = "." 1*DIGIT
+        if self.peek()? == Some(b'.') {
+            self.consume(1)?;
+            let frac_digits = self.scan_digits()?;
+            if frac_digits == 0 && !(self.options.bare_decimal_point && int_digits > 0) {
+                return Err(self.invalid_number(start, &["a digit"]));
+            }
+            if int_digits == 0 && !self.options.bare_decimal_point {
+                return Err(self.invalid_number(start, &["a digit before `.`"]));
+            }
+        } else if int_digits == 0 {
+            return Err(self.invalid_number(start, &["a digit"]));
+        }
 
-        while let Some(&c) = self.chars.peek() {
-            match c{
-                '0'..='9' | '.' | '-' | '+' | 'e' | 'E' => {
-                    number_str.push(c);
-                    self.advance_char();
+        // exp = ("e" / "E") ["-" / "+"] 1*DIGIT
+        if let Some(b'e' | b'E') = self.peek()? {
+            self.consume(1)?;
+            if let Some(b'-' | b'+') = self.peek()? {
+                self.consume(1)?;
+            }
+            if self.scan_digits()? == 0 {
+                return Err(self.invalid_number(start, &["a digit"]));
+            }
+        }
+
+        let text = self.consumed(start);
+        if self.options.arbitrary_precision {
+            return Ok(Token::Number(NumberParser::parse_lossless(text)));
+        }
+        // Magnitudes beyond f64 are rejected rather than read as infinity.
+        match NumberParser::parse(text) {
+            Some(JsonNumber::Float(f)) if f.is_infinite() => Err(self.invalid_number(start, &["a number within the f64 range"])),
+            Some(number) => Ok(Token::Number(number)),
+            None => Err(self.invalid_number(start, &[])),
+        }
+    }
+
+    // The number read so far from `start` is malformed.
+    fn invalid_number(&self, start: usize, expected: &[&'static str]) -> JsonError {
+        JsonError::invalid_number(self.position_at(start), quote(self.consumed(start)), expected)
+    }
+
+    // Consumes a run of ASCII digits, returning how many there were.
+    fn scan_digits(&mut self) -> Result<usize, JsonError> {
+        let start = self.offset;
+        loop {
+            // One run short of any limit, then a digit at a time.
+            let length = self.options.limits.max_number_length;
+            let room = self.room().min(self.token_start.saturating_add(length).saturating_sub(self.offset));
+            let bytes = self.buffered(1)?;
+            let run = scan::digit_run(&bytes[..bytes.len().min(room)]);
+            self.offset += run;
+            match self.peek()? {
+                Some(b) if b.is_ascii_digit() => {
+                    if run == 0 {
+                        self.consume(1)?;
+                        self.check_number_length()?;
+                    }
                 }
-                _ => break,
+                _ => return Ok(self.offset - start),
             }
         }
-        NumberParser::parse(&number_str)
-            .map(Token::Number)
-            .map_err(|_| JsonError::InvalidNumber {
-                line:start_line,
-                col:start_col,
-            })
     }
 
-    fn parse_literal(&mut self, expected: &str, token: Token) -> Result<Token, JsonError>{
-        let (start_line, start_col) = (self.line, self.col);
-        for expected_char in expected.chars() {
-            match self.advance_char(){
-                Some(c) if c ==expected_char => continue,
-                _=> return Err(JsonError::InvalidToken{
-                    line:start_line,
-                    col:start_col,
-                })
+    // Numbers are measured as written, from where the token began.
+    fn check_number_length(&self) -> Result<(), JsonError> {
+        self.options.limits.check(Limit::NumberLength, self.offset - self.token_start, || self.token_start())
+    }
+
+    fn parse_hex(&mut self, negative: bool, start: usize) -> Result<Token, JsonError> {
+        let digits_start = self.offset;
+        while let Some(b) = self.peek()?
+            && b.is_ascii_hexdigit()
+        {
+            self.consume(1)?;
+            self.check_number_length()?;
+        }
+        let digits = self.consumed(digits_start);
+        if digits.is_empty() {
+            let sign = if negative { "-" } else { "" };
+            return Err(JsonError::invalid_number(self.position_at(start), quote(&format!("{}0x", sign)), &["a hex digit"]));
+        }
+        let magnitude = u64::from_str_radix(digits, 16).ok();
+        let number = match (negative, magnitude) {
+            (false, Some(u)) => i64::try_from(u).map_or(JsonNumber::UnsignedInteger(u), JsonNumber::Integer),
+            (true, Some(u)) if u <= i64::MAX as u64 + 1 => JsonNumber::Integer((u as i64).wrapping_neg()),
+            _ => {
+                let value = digits.chars().fold(0.0, |acc, c| acc * 16.0 + f64::from(c.to_digit(16).unwrap_or(0)));
+                JsonNumber::Float(if negative { -value } else { value })
+            }
+        };
+        Ok(Token::Number(number))
+    }
+
+    fn parse_literal(&mut self, expected: &'static str, token: Token) -> Result<Token, JsonError>{
+        let start = self.offset;
+        for &byte in expected.as_bytes() {
+            if self.peek()? == Some(byte) {
+                self.consume(1)?;
+            } else {
+                self.take_word()?;
+                return Err(JsonError::invalid_token(self.position_at(start), Some(quote(self.consumed(start))), &[literal_name(expected)]));
             }
         }
+        // `trueish` is one bad word, not `true` followed by garbage.
+        if self.peek_char()?.is_some_and(is_identifier_part) {
+            self.take_word()?;
+            return Err(JsonError::invalid_token(self.position_at(start), Some(quote(self.consumed(start))), &[literal_name(expected)]));
+        }
         Ok(token)
     }
+
+    // Consumes the rest of a bare word so errors can quote all of it.
+    fn take_word(&mut self) -> Result<(), JsonError> {
+        while let Some(c) = self.peek_char()?
+            && is_identifier_part(c)
+        {
+            self.advance_char(c)?;
+        }
+        Ok(())
+    }
 }
 
-pub struct Parser <'a> {
-    lexer: Lexer<'a>,
+pub struct Parser <S> {
+    lexer: Lexer<S>,
     current_token: Token,
+    path: PathTracker,
+    // Containers open and values started so far, against `Limits`.
+    depth: usize,
+    nodes: usize,
+    // Spare buffers for the path's copies of borrowed member names.
+    key_buffers: Vec<String>,
+}
+
+/// Member names and element indices leading to the value being read, so
+/// errors can say where they happened. Steps past `entered` belong to values
+/// that are complete but not yet stored by their container.
+#[derive(Default)]
+pub(crate) struct PathTracker {
+    steps: Vec<PathStep>,
+    entered: usize,
+}
+
+impl PathTracker {
+    /// The next token belongs to the value at `step`.
+    pub(crate) fn enter(&mut self, step: PathStep) {
+        self.steps.push(step);
+        self.entered = self.steps.len();
+    }
+
+    /// The value being read ends with the current token; whatever follows
+    /// belongs to its container. Called before advancing past that token.
+    pub(crate) fn leave(&mut self) {
+        self.entered = self.entered.saturating_sub(1);
+    }
+
+    /// Drops the step of a value its container has finished with.
+    pub(crate) fn exit(&mut self) -> Option<PathStep> {
+        let step = self.steps.pop();
+        self.entered = self.entered.min(self.steps.len());
+        step
+    }
+
+    /// Name of the innermost member entered.
+    #[cfg(feature = "serde")]
+    pub(crate) fn key(&self) -> Option<&str> {
+        match self.steps.last() {
+            Some(PathStep::Key(key)) => Some(key),
+            _ => None,
+        }
+    }
+
+    /// The normalized path of the value the current token belongs to.
+    pub(crate) fn normalized(&self) -> String {
+        normalized_path(self.steps[..self.entered].iter().map(PathStep::element))
+    }
+}
+
+/// One step from a container to the value being parsed.
+#[derive(Debug, PartialEq, Clone)]
+pub(crate) enum PathStep {
+    Key(String),
+    Index(usize),
+}
+
+impl PathStep {
+    pub(crate) fn element(&self) -> PathElement<'_> {
+        match self {
+            PathStep::Key(key) => PathElement::Name(key),
+            PathStep::Index(i) => PathElement::Index(*i),
+        }
+    }
+}
+
+/// Member names already read in one object, so that a repeated one can be
+/// handled according to [`DuplicateKeyPolicy`].
+pub(crate) struct SeenKeys {
+    policy: DuplicateKeyPolicy,
+    // Member index and position of each name's first use.
+    first: HashMap<String, (usize, Position)>,
+}
+
+/// Where a member goes in the object being built.
+pub(crate) enum Slot {
+    Push,
+    Replace(usize),
+    Drop,
+}
+
+impl SeenKeys {
+    pub(crate) fn new(policy: DuplicateKeyPolicy) -> Self {
+        Self { policy, first: HashMap::new() }
+    }
+
+    /// Records `key`, spelled by `token` at `at`, as the member that would be
+    /// stored at `index`.
+    pub(crate) fn insert(&mut self, key: &str, token: &Token, at: impl FnOnce() -> Position, index: usize) -> Result<Slot, JsonError> {
+        if self.policy == DuplicateKeyPolicy::All {
+            return Ok(Slot::Push);
+        }
+        let at = at();
+        let Some(&(index, first)) = self.first.get(key) else {
+            self.first.insert(key.to_string(), (index, at));
+            return Ok(Slot::Push);
+        };
+        match self.policy {
+            DuplicateKeyPolicy::First => Ok(Slot::Drop),
+            DuplicateKeyPolicy::Last => Ok(Slot::Replace(index)),
+            _ => Err(JsonError::duplicate_key(at, token.describe(), first)),
+        }
+    }
 }
 
-impl<'a> Parser <'a> {
-    pub(crate) fn new(input: &'a str) -> Result<Self, JsonError> {
-        let mut lexer = Lexer::new(input);
+impl<'a> Parser <StrSource<'a>> {
+    pub(crate) fn new(input: &'a str, options: ParseOptions) -> Result<Self, JsonError> {
+        Self::with_lexer(Lexer::new(input, options))
+    }
+
+    /// A parser for [`parse_borrowed`](Self::parse_borrowed), which leaves
+    /// strings without escapes in `input`.
+    pub(crate) fn borrowing(input: &'a str, options: ParseOptions) -> Result<Self, JsonError> {
+        let mut lexer = Lexer::new(input, options);
+        lexer.set_borrowing();
+        Self::with_lexer(lexer)
+    }
+
+    pub(crate) fn parse_borrowed(&mut self) -> Result<BorrowedJsonValue<'a>, JsonError> {
+        let result = self.parse_borrowed_value(&[EXPECT_VALUE]).and_then(|value| self.end().map(|()| value));
+        result.map_err(|e| self.locate(e))
+    }
+
+    // The text of the current token when it is a string left in the input.
+    fn in_place(&self) -> Option<&'a str> {
+        let (start, end) = self.lexer.string_in_place()?;
+        Some(&self.lexer.source.input()[start..end])
+    }
+
+    fn parse_borrowed_value(&mut self, expected: &[&'static str]) -> Result<BorrowedJsonValue<'a>, JsonError> {
+        match self.current_token {
+            Token::SquareLeft => return self.parse_borrowed_array(),
+            Token::CurlyLeft => return self.parse_borrowed_object(),
+            _ => {}
+        }
+        let in_place = self.in_place();
+        let value = match self.begin_scalar(expected)? {
+            Token::True => BorrowedJsonValue::Bool(true),
+            Token::False => BorrowedJsonValue::Bool(false),
+            Token::Number(n) => BorrowedJsonValue::Number(n),
+            Token::String(s) => BorrowedJsonValue::String(in_place.map_or(Cow::Owned(s), Cow::Borrowed)),
+            Token::Wtf8(s) => BorrowedJsonValue::Wtf8(s),
+            _ => BorrowedJsonValue::Null,
+        };
+        self.end_scalar()?;
+        Ok(value)
+    }
+
+    fn parse_borrowed_array(&mut self) -> Result<BorrowedJsonValue<'a>, JsonError> {
+        self.begin_array()?;
+        let mut elements = Vec::new();
+        while let Some(expected) = self.next_element(elements.len())? {
+            elements.push(self.parse_borrowed_value(expected)?);
+            self.end_element();
+        }
+        self.end_array()?;
+        Ok(BorrowedJsonValue::Array(elements))
+    }
+
+    fn parse_borrowed_object(&mut self) -> Result<BorrowedJsonValue<'a>, JsonError> {
+        self.begin_object()?;
+        let mut pairs = Vec::new();
+        let mut seen = SeenKeys::new(self.lexer.options().duplicate_keys);
+        while self.find_member(pairs.len())? {
+            // The path needs its own copy of a borrowed name; reuse a buffer
+            // from an earlier member for it.
+            let borrowed = self.in_place();
+            if let Some(name) = borrowed
+                && let Token::String(key) = &mut self.current_token
+            {
+                *key = self.key_buffers.pop().unwrap_or_default();
+                key.push_str(name);
+            }
+            let slot = self.read_member_name(pairs.len(), &mut seen)?;
+            let value = self.parse_borrowed_value(&[EXPECT_VALUE])?;
+            let mut key = self.end_member();
+            let key = match borrowed {
+                Some(name) => {
+                    key.clear();
+                    self.key_buffers.push(key);
+                    Cow::Borrowed(name)
+                }
+                None => Cow::Owned(key),
+            };
+            match slot {
+                Slot::Push => pairs.push((key, value)),
+                Slot::Replace(index) => pairs[index].1 = value,
+                Slot::Drop => {}
+            }
+        }
+        self.end_object()?;
+        Ok(BorrowedJsonValue::Object(pairs))
+    }
+}
+
+impl<'a> Parser <StrSource<'a>> {
+    /// Parses into a [`Document`] tape; the parser must be
+    /// [`borrowing`](Self::borrowing).
+    pub(crate) fn parse_document(&mut self) -> Result<Document<'a>, JsonError> {
+        let mut document = Document::new(self.lexer.source.input());
+        let result = self.parse_tape_value(&mut document, &[EXPECT_VALUE]).and_then(|()| self.end());
+        result.map(|()| document).map_err(|e| self.locate(e))
+    }
+
+    fn parse_tape_value(&mut self, document: &mut Document<'a>, expected: &[&'static str]) -> Result<(), JsonError> {
+        match self.current_token {
+            Token::SquareLeft => return self.parse_tape_array(document),
+            Token::CurlyLeft => return self.parse_tape_object(document),
+            _ => {}
+        }
+        let in_place = self.lexer.string_in_place();
+        let entry = match self.begin_scalar(expected)? {
+            Token::True => Entry::Bool(true),
+            Token::False => Entry::Bool(false),
+            Token::Number(n) => document.number(n),
+            Token::String(s) => document.string(in_place, &s),
+            Token::Wtf8(s) => document.wtf8(s),
+            _ => Entry::Null,
+        };
+        document.push(entry);
+        self.end_scalar()
+    }
+
+    fn parse_tape_array(&mut self, document: &mut Document<'a>) -> Result<(), JsonError> {
+        self.begin_array()?;
+        let start = document.open();
+        let mut len = 0;
+        while let Some(expected) = self.next_element(len)? {
+            self.parse_tape_value(document, expected)?;
+            self.end_element();
+            len += 1;
+        }
+        self.end_array()?;
+        document.close(start, len, false);
+        Ok(())
+    }
+
+    fn parse_tape_object(&mut self, document: &mut Document<'a>) -> Result<(), JsonError> {
+        self.begin_object()?;
+        let start = document.open();
+        let mut len = 0;
+        let mut seen = SeenKeys::new(self.lexer.options().duplicate_keys);
+        while self.find_member(len)? {
+            // As in `parse_borrowed_object`, the path gets its own copy of a
+            // name left in the input.
+            let in_place = self.lexer.string_in_place();
+            let name = match (in_place, &mut self.current_token) {
+                (Some((start, end)), Token::String(key)) => {
+                    *key = self.key_buffers.pop().unwrap_or_default();
+                    key.push_str(&self.lexer.source.input()[start..end]);
+                    Entry::String(Span::new(start, end))
+                }
+                (_, token) => document.string(None, member_name(token, self.lexer.options()).unwrap_or_default()),
+            };
+            let slot = self.read_member_name(len, &mut seen)?;
+            let member = document.len();
+            document.push(name);
+            self.parse_tape_value(document, &[EXPECT_VALUE])?;
+            let mut key = self.end_member();
+            if in_place.is_some() {
+                key.clear();
+                self.key_buffers.push(key);
+            }
+            match slot {
+                Slot::Push => len += 1,
+                Slot::Replace(index) => document.replace(start, index, member),
+                Slot::Drop => document.truncate(member),
+            }
+        }
+        self.end_object()?;
+        document.close(start, len, true);
+        Ok(())
+    }
+}
+
+impl<R: Read> Parser <ReadSource<R>> {
+    pub(crate) fn from_reader(reader: R, options: ParseOptions) -> Result<Self, JsonError> {
+        Self::with_lexer(Lexer::from_reader(reader, options))
+    }
+}
+
+impl<S: Source> Parser <S> {
+    pub(crate) fn with_lexer(mut lexer: Lexer<S>) -> Result<Self, JsonError> {
         let current_token = lexer.next_token()?;
-        Ok(Self {lexer, current_token})
+        Ok(Self {lexer, current_token, path: PathTracker::default(), depth: 0, nodes: 0, key_buffers: Vec::new()})
     }
 
     fn advance(&mut self) -> Result<(), JsonError> {
@@ -171,81 +994,311 @@ impl<'a> Parser <'a> {
     }
 
     pub(crate) fn parse(&mut self) -> Result<JsonValue, JsonError>{
-        let value = self.parse_value()?;
-        if !matches!(self.current_token, Token::Eof) {
-            return Err(JsonError::InvalidToken {
-                line: self.lexer.line,
-                col: self.lexer.col,
-            });
+        let result = self.parse_value(&[EXPECT_VALUE]).and_then(|value| self.end().map(|()| value));
+        result.map_err(|e| self.locate(e))
+    }
+
+    #[cfg(feature = "serde")]
+    pub(crate) fn token(&self) -> &Token {
+        &self.current_token
+    }
+
+    #[cfg(feature = "serde")]
+    pub(crate) fn options(&self) -> &ParseOptions {
+        self.lexer.options()
+    }
+
+    /// Fails unless the document ends after the value just read.
+    pub(crate) fn end(&self) -> Result<(), JsonError> {
+        match self.current_token {
+            Token::Eof => Ok(()),
+            _ => Err(self.unexpected(&[EXPECT_EOF])),
         }
-        Ok(value)
     }
 
-    fn parse_value(&mut self) -> Result<JsonValue, JsonError> {
+    /// Gives `error` the path of the value being read and, when it has no
+    /// position yet, the position of the current token.
+    pub(crate) fn locate(&self, error: JsonError) -> JsonError {
+        error.located(self.lexer.token_start()).with_path(self.path.normalized())
+    }
+
+    // The current token starts a value; `nested` when it opens a container.
+    fn start_value(&mut self, nested: bool) -> Result<(), JsonError> {
+        let limits = self.lexer.options().limits;
+        self.nodes += 1;
+        limits.check(Limit::Nodes, self.nodes, || self.lexer.token_start())?;
+        if nested {
+            self.depth += 1;
+            limits.check(Limit::Depth, self.depth, || self.lexer.token_start())?;
+        }
+        Ok(())
+    }
+
+    fn check_elements(&self, len: usize) -> Result<(), JsonError> {
+        self.lexer.options().limits.check(Limit::Elements, len, || self.lexer.token_start())
+    }
+
+    pub(crate) fn unexpected(&self, expected: &[&'static str]) -> JsonError {
+        let at = self.lexer.token_start();
         match &self.current_token {
-            Token::Null => { self.advance()?; Ok(JsonValue::Null) }
-            Token::True => { self.advance()?; Ok(JsonValue::Bool(true)) }
-            Token::False => { self.advance()?; Ok(JsonValue::Bool(false)) }
-            Token::Number(n) => { let value = n.clone(); self.advance()?; Ok(JsonValue::Number(value)) }
-            Token::String(s) => { let value = s.clone(); self.advance()?; Ok(JsonValue::String(value)) }
-            Token::SquareLeft => self.parse_array(),
-            Token::CurlyLeft => self.parse_object(),
-            _ => Err(JsonError::InvalidToken { line: self.lexer.line, col: self.lexer.col }),
+            Token::Eof => JsonError::unexpected_eof(at, expected),
+            token => JsonError::invalid_token(at, self.lexer.describe(token), expected),
+        }
+    }
+
+    fn parse_value(&mut self, expected: &[&'static str]) -> Result<JsonValue, JsonError> {
+        match self.current_token {
+            Token::SquareLeft => return self.parse_array(),
+            Token::CurlyLeft => return self.parse_object(),
+            _ => {}
         }
+        let value = match self.begin_scalar(expected)? {
+            Token::True => JsonValue::Bool(true),
+            Token::False => JsonValue::Bool(false),
+            Token::Number(n) => JsonValue::Number(n),
+            Token::String(s) => JsonValue::String(s),
+            Token::Wtf8(s) => JsonValue::Wtf8(s),
+            _ => JsonValue::Null,
+        };
+        self.end_scalar()?;
+        Ok(value)
     }
 
     fn parse_array(&mut self) -> Result<JsonValue, JsonError> {
-        self.advance()?; // consume '['
+        self.begin_array()?;
         let mut elements = Vec::new();
+        while let Some(expected) = self.next_element(elements.len())? {
+            elements.push(self.parse_value(expected)?);
+            self.end_element();
+        }
+        self.end_array()?;
+        Ok(JsonValue::Array(elements))
+    }
 
-        if matches!(self.current_token, Token::SquareRight) {
-            self.advance()?; // consume ']'
-            return Ok(JsonValue::Array(elements));
+    fn parse_object(&mut self) -> Result<JsonValue, JsonError> {
+        self.begin_object()?;
+        let mut pairs = Vec::new();
+        let mut seen = SeenKeys::new(self.lexer.options().duplicate_keys);
+        while let Some(slot) = self.next_member(pairs.len(), &mut seen)? {
+            let value = self.parse_value(&[EXPECT_VALUE])?;
+            let key = self.end_member();
+            match slot {
+                Slot::Push => pairs.push((key, value)),
+                Slot::Replace(index) => pairs[index].1 = value,
+                Slot::Drop => {}
+            }
         }
+        self.end_object()?;
+        Ok(JsonValue::Object(pairs))
+    }
 
-        loop {
-            elements.push(self.parse_value()?);
+    // The steps below are shared by `parse_value` and the serde
+    // deserializer, which reads values without building a tree.
 
-            match self.current_token {
-                Token::Comma => { self.advance()?; }
-                Token::SquareRight => { self.advance()?; break; }
-                _ => return Err(JsonError::InvalidToken { line: self.lexer.line, col: self.lexer.col }),
+    /// Takes the `null`, boolean, number or string at the current token;
+    /// `Infinity` and `NaN` come back as numbers. The parser stays on the
+    /// token until [`end_scalar`](Self::end_scalar), so errors raised in
+    /// between point at it.
+    pub(crate) fn begin_scalar(&mut self, expected: &[&'static str]) -> Result<Token, JsonError> {
+        let token = match self.current_token {
+            Token::Null | Token::True | Token::False | Token::Number(_) | Token::String(_) | Token::Wtf8(_) => {
+                std::mem::replace(&mut self.current_token, Token::Eof)
             }
-        }
+            Token::Identifier(ref name) => match identifier_number(name, self.lexer.options()) {
+                Some(n) => Token::Number(n),
+                None => return Err(self.unexpected(expected)),
+            },
+            _ => return Err(self.unexpected(expected)),
+        };
+        self.start_value(false)?;
+        Ok(token)
+    }
 
-        Ok(JsonValue::Array(elements))
+    pub(crate) fn end_scalar(&mut self) -> Result<(), JsonError> {
+        self.path.leave();
+        self.advance()
     }
 
-    fn parse_object(&mut self) -> Result<JsonValue, JsonError> {
-        self.advance()?; // consume '{'
-        let mut pairs = Vec::new();
+    /// Consumes the `[` at the current token.
+    pub(crate) fn begin_array(&mut self) -> Result<(), JsonError> {
+        self.start_value(true)?;
+        self.path.enter(PathStep::Index(0));
+        self.advance() // consume '['
+    }
 
-        if matches!(self.current_token, Token::CurlyRight) {
-            self.advance()?; // consume '}'
-            return Ok(JsonValue::Object(pairs));
+    /// Moves to the element following the `len` read so far, returning what
+    /// may start it, or `None` at the closing `]`.
+    pub(crate) fn next_element(&mut self, len: usize) -> Result<Option<&'static [&'static str]>, JsonError> {
+        let trailing_commas = self.lexer.options().trailing_commas;
+        if len > 0 {
+            match self.current_token {
+                Token::Comma => {
+                    self.path.enter(PathStep::Index(len));
+                    self.advance()?;
+                }
+                Token::SquareRight => return Ok(None),
+                _ => return Err(self.unexpected(&[EXPECT_COMMA, EXPECT_END_ARRAY])),
+            }
         }
+        if (len == 0 || trailing_commas) && matches!(self.current_token, Token::SquareRight) {
+            self.path.exit();
+            return Ok(None);
+        }
+        self.check_elements(len + 1)?;
+        Ok(Some(if len == 0 || trailing_commas { &[EXPECT_VALUE, EXPECT_END_ARRAY] } else { &[EXPECT_VALUE] }))
+    }
 
-        loop {
-            let key = match &self.current_token {
-                Token::String(s) => { let key = s.clone(); self.advance()?; key }
-                _ => return Err(JsonError::InvalidToken { line: self.lexer.line, col: self.lexer.col }),
-            };
+    /// Called once an element has been read.
+    pub(crate) fn end_element(&mut self) {
+        self.path.exit();
+    }
 
-            if !matches!(self.current_token, Token::Colon) {
-                return Err(JsonError::InvalidToken { line: self.lexer.line, col: self.lexer.col });
-            }
-            self.advance()?; // consume ':'
+    /// Consumes the `]` that [`next_element`](Self::next_element) stopped at.
+    pub(crate) fn end_array(&mut self) -> Result<(), JsonError> {
+        if !matches!(self.current_token, Token::SquareRight) {
+            return Err(self.unexpected(&[EXPECT_END_ARRAY]));
+        }
+        self.path.leave();
+        self.depth -= 1;
+        self.advance() // consume ']'
+    }
+
+    /// Consumes the `{` at the current token.
+    pub(crate) fn begin_object(&mut self) -> Result<(), JsonError> {
+        self.start_value(true)?;
+        self.advance() // consume '{'
+    }
 
-            let value = self.parse_value()?;
-            pairs.push((key, value));
+    /// Reads the name and colon of the member following the `len` read so
+    /// far, returning where it goes, or `None` at the closing `}`.
+    pub(crate) fn next_member(&mut self, len: usize, seen: &mut SeenKeys) -> Result<Option<Slot>, JsonError> {
+        match self.find_member(len)? {
+            true => self.read_member_name(len, seen).map(Some),
+            false => Ok(None),
+        }
+    }
 
+    // Moves to the name of the member following the `len` read so far;
+    // `false` at the closing `}`.
+    fn find_member(&mut self, len: usize) -> Result<bool, JsonError> {
+        if len > 0 {
             match self.current_token {
-                Token::Comma => { self.advance()?; }
-                Token::CurlyRight => { self.advance()?; break; }
-                _ => return Err(JsonError::InvalidToken { line: self.lexer.line, col: self.lexer.col }),
+                Token::Comma => self.advance()?,
+                Token::CurlyRight => return Ok(false),
+                _ => return Err(self.unexpected(&[EXPECT_COMMA, EXPECT_END_OBJECT])),
             }
         }
+        let closable = len == 0 || self.lexer.options().trailing_commas;
+        Ok(!(closable && matches!(self.current_token, Token::CurlyRight)))
+    }
 
-        Ok(JsonValue::Object(pairs))
+    // Reads the name and colon of the member `find_member` moved to.
+    fn read_member_name(&mut self, len: usize, seen: &mut SeenKeys) -> Result<Slot, JsonError> {
+        let closable = len == 0 || self.lexer.options().trailing_commas;
+        let expected = [member_expectation(self.lexer.options()), EXPECT_END_OBJECT];
+        let Some(key) = member_name(&self.current_token, self.lexer.options()) else {
+            let at = self.lexer.token_start();
+            return Err(lone_surrogate_in_name(at, &self.current_token)
+                .unwrap_or_else(|| self.unexpected(if closable { &expected } else { &expected[..1] })));
+        };
+        self.check_elements(len + 1)?;
+        let slot = seen.insert(key, &self.current_token, || self.lexer.token_start(), len)?;
+        // The name moves from the token into the path rather than being copied.
+        let key = match std::mem::replace(&mut self.current_token, Token::Eof) {
+            Token::String(key) | Token::Identifier(key) => key,
+            token => member_name(&token, self.lexer.options()).unwrap_or_default().to_string(),
+        };
+        self.path.enter(PathStep::Key(key));
+        self.advance()?;
+
+        if !matches!(self.current_token, Token::Colon) {
+            return Err(self.unexpected(&[EXPECT_COLON]));
+        }
+        self.advance()?; // consume ':'
+        Ok(slot)
+    }
+
+    /// Name of the member whose value is being read.
+    #[cfg(feature = "serde")]
+    pub(crate) fn current_key(&self) -> &str {
+        self.path.key().unwrap_or_default()
+    }
+
+    /// Called once a member's value has been read; returns its name.
+    pub(crate) fn end_member(&mut self) -> String {
+        let Some(PathStep::Key(key)) = self.path.exit() else {
+            unreachable!("object members are entered by key");
+        };
+        key
     }
+
+    /// Consumes the `}` that [`next_member`](Self::next_member) stopped at.
+    pub(crate) fn end_object(&mut self) -> Result<(), JsonError> {
+        if !matches!(self.current_token, Token::CurlyRight) {
+            return Err(self.unexpected(&[EXPECT_COMMA, EXPECT_END_OBJECT]));
+        }
+        self.path.leave();
+        self.depth -= 1;
+        self.advance() // consume '}'
+    }
+}
+
+// How errors describe what the parser was looking for.
+pub(crate) const EXPECT_VALUE: &str = "a value";
+pub(crate) const EXPECT_COMMA: &str = "`,`";
+pub(crate) const EXPECT_COLON: &str = "`:`";
+pub(crate) const EXPECT_END_ARRAY: &str = "`]`";
+pub(crate) const EXPECT_END_OBJECT: &str = "`}`";
+pub(crate) const EXPECT_EOF: &str = "end of input";
+const ESCAPES: &[&str] = &["`\\\"`", "`\\\\`", "`\\/`", "`\\b`", "`\\f`", "`\\n`", "`\\r`", "`\\t`", "`\\u`"];
+
+/// What may start an object member under `options`.
+pub(crate) fn member_expectation(options: &ParseOptions) -> &'static str {
+    if options.unquoted_keys { "a member name" } else { "a string" }
+}
+
+fn literal_name(literal: &str) -> &'static str {
+    match literal {
+        "true" => "`true`",
+        "false" => "`false`",
+        "null" => "`null`",
+        "Infinity" => "`Infinity`",
+        _ => "`NaN`",
+    }
+}
+
+/// The member name `token` spells when it starts an object member.
+pub(crate) fn member_name<'t>(token: &'t Token, options: &ParseOptions) -> Option<&'t str> {
+    match token {
+        Token::String(s) => Some(s),
+        Token::Identifier(name) if options.unquoted_keys => Some(name),
+        Token::True if options.unquoted_keys => Some("true"),
+        Token::False if options.unquoted_keys => Some("false"),
+        Token::Null if options.unquoted_keys => Some("null"),
+        _ => None,
+    }
+}
+
+/// Member names are `String`s, so one holding a lone surrogate fails even
+/// under [`LoneSurrogatePolicy::Preserve`].
+pub(crate) fn lone_surrogate_in_name(at: Position, token: &Token) -> Option<JsonError> {
+    let Token::Wtf8(_) = token else { return None };
+    Some(JsonError::invalid_escape(at, token.describe().unwrap_or_default(), &["a surrogate pair"]))
+}
+
+/// The number a bare `Infinity` or `NaN` stands for in value position.
+pub(crate) fn identifier_number(name: &str, options: &ParseOptions) -> Option<JsonNumber> {
+    match name {
+        "Infinity" if options.special_floats => Some(JsonNumber::Float(f64::INFINITY)),
+        "NaN" if options.special_floats => Some(JsonNumber::Float(f64::NAN)),
+        _ => None,
+    }
+}
+
+fn is_identifier_start(c: char) -> bool {
+    c.is_alphabetic() || c == '$' || c == '_'
+}
+
+fn is_identifier_part(c: char) -> bool {
+    c.is_alphanumeric() || c == '$' || c == '_' || c == '\u{200C}' || c == '\u{200D}'
 }
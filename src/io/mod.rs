@@ -1,5 +1,5 @@
-use std::io::{self, Write, Read};
-use std::fs::File;
+use std::io::{self, BufWriter, Write, Read};
+use std::fs::{self, File};
 
 pub(crate)  fn read_from_file(path: &str) -> Result<String, io::Error> {
     let mut file = File::open(path)?;
@@ -8,9 +8,36 @@ pub(crate)  fn read_from_file(path: &str) -> Result<String, io::Error> {
     Ok(contents)
 }
 
+/// Opens `path` for reading, treating `-` as standard input.
+pub(crate) fn open_input(path: &str) -> Result<Box<dyn Read>, io::Error> {
+    if path == "-" {
+        Ok(Box::new(io::stdin().lock()))
+    } else {
+        Ok(Box::new(File::open(path)?))
+    }
+}
+
 pub(crate) fn write_to_file(path: &str, s: &str) -> Result<(), io::Error> {
     let mut file = File::create(path)?;
     file.write_all(s.as_bytes())?;
     Ok(())
 }
 
+/// Streams output to `path` through a temporary file next to it, which
+/// replaces `path` only once `write` succeeds; a failure partway through
+/// leaves `path` as it was.
+pub(crate) fn write_output(path: &str, write: impl FnOnce(&mut BufWriter<File>) -> io::Result<()>) -> Result<(), io::Error> {
+    let temp = format!("{}.tmp", path);
+    let written = File::create(&temp).and_then(|file| {
+        let mut output = BufWriter::new(file);
+        write(&mut output)?;
+        output.flush()
+    });
+    match written {
+        Ok(()) => fs::rename(&temp, path),
+        Err(e) => {
+            let _ = fs::remove_file(&temp);
+            Err(e)
+        }
+    }
+}
@@ -0,0 +1,367 @@
+// RFC 9485 I-Regexp, the pattern language of the JSONPath `match()` and
+// `search()` functions. Patterns compile to a small NFA that is run
+// breadth-first, so matching takes time linear in the text whatever the
+// pattern.
+
+// Repetition counts and compiled size are capped so that patterns such as
+// `(a{1000}){1000}` cannot blow up.
+const MAX_REPEAT: u32 = 1000;
+const MAX_PROGRAM: usize = 100_000;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub(crate) enum RegexError {
+    /// Not an I-Regexp.
+    Invalid,
+    /// Valid, but outside what this implementation handles.
+    Unsupported(&'static str),
+}
+
+/// A compiled I-Regexp.
+#[derive(Debug, PartialEq, Clone)]
+pub(crate) struct Regex {
+    program: Vec<Inst>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+enum Inst {
+    Class(Class),
+    Split(usize, usize),
+    Jump(usize),
+    Match,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+struct Class {
+    negated: bool,
+    ranges: Vec<(char, char)>,
+}
+
+impl Class {
+    fn char(c: char) -> Self {
+        Self { negated: false, ranges: vec![(c, c)] }
+    }
+
+    fn matches(&self, c: char) -> bool {
+        self.ranges.iter().any(|&(low, high)| low <= c && c <= high) != self.negated
+    }
+}
+
+#[derive(Debug)]
+enum Node {
+    Class(Class),
+    Concat(Vec<Node>),
+    Alternation(Vec<Node>),
+    Repeat { node: Box<Node>, min: u32, max: Option<u32> },
+}
+
+impl Regex {
+    pub(crate) fn parse(pattern: &str) -> Result<Self, RegexError> {
+        let mut parser = RegexParser { chars: pattern.chars().collect(), pos: 0 };
+        let node = parser.parse_alternation()?;
+        if parser.pos != parser.chars.len() {
+            return Err(RegexError::Invalid);
+        }
+        let mut program = Vec::new();
+        compile(&node, &mut program)?;
+        program.push(Inst::Match);
+        Ok(Self { program })
+    }
+
+    /// Whether all of `text` matches, or with `search` some substring of it.
+    pub(crate) fn is_match(&self, text: &str, search: bool) -> bool {
+        let mut current = Threads::new(self.program.len());
+        let mut next = Threads::new(self.program.len());
+        let mut chars = text.chars();
+        let mut start = true;
+        loop {
+            // An anchored match only ever starts at the first char.
+            if search || std::mem::take(&mut start) {
+                self.add_thread(&mut current, 0);
+            }
+            let c = chars.next();
+            if current.pcs.iter().any(|&pc| self.program[pc] == Inst::Match) && (search || c.is_none()) {
+                return true;
+            }
+            let Some(c) = c else { return false };
+            for &pc in &current.pcs {
+                if let Inst::Class(class) = &self.program[pc]
+                    && class.matches(c)
+                {
+                    self.add_thread(&mut next, pc + 1);
+                }
+            }
+            if next.pcs.is_empty() && !search {
+                return false;
+            }
+            std::mem::swap(&mut current, &mut next);
+            next.clear();
+        }
+    }
+
+    // Adds `pc` and everything reachable from it without reading a char.
+    fn add_thread(&self, threads: &mut Threads, pc: usize) {
+        let mut stack = vec![pc];
+        while let Some(pc) = stack.pop() {
+            if !threads.insert(pc) {
+                continue;
+            }
+            match self.program[pc] {
+                Inst::Jump(to) => stack.push(to),
+                Inst::Split(first, second) => stack.extend([second, first]),
+                _ => {}
+            }
+        }
+    }
+}
+
+// The instructions live at one position in the text, each at most once.
+struct Threads {
+    pcs: Vec<usize>,
+    seen: Vec<bool>,
+}
+
+impl Threads {
+    fn new(len: usize) -> Self {
+        Self { pcs: Vec::new(), seen: vec![false; len] }
+    }
+
+    fn insert(&mut self, pc: usize) -> bool {
+        let new = !std::mem::replace(&mut self.seen[pc], true);
+        if new {
+            self.pcs.push(pc);
+        }
+        new
+    }
+
+    fn clear(&mut self) {
+        for &pc in &self.pcs {
+            self.seen[pc] = false;
+        }
+        self.pcs.clear();
+    }
+}
+
+fn compile(node: &Node, program: &mut Vec<Inst>) -> Result<(), RegexError> {
+    if program.len() > MAX_PROGRAM {
+        return Err(RegexError::Unsupported("regular expression is too large"));
+    }
+    match node {
+        Node::Class(class) => program.push(Inst::Class(class.clone())),
+        Node::Concat(nodes) => {
+            for node in nodes {
+                compile(node, program)?;
+            }
+        }
+        Node::Alternation(branches) => {
+            let mut jumps = Vec::new();
+            for (i, branch) in branches.iter().enumerate() {
+                let split = program.len();
+                if i + 1 < branches.len() {
+                    program.push(Inst::Split(split + 1, 0));
+                }
+                compile(branch, program)?;
+                if i + 1 < branches.len() {
+                    jumps.push(program.len());
+                    program.push(Inst::Jump(0));
+                    program[split] = Inst::Split(split + 1, program.len());
+                }
+            }
+            for jump in jumps {
+                program[jump] = Inst::Jump(program.len());
+            }
+        }
+        Node::Repeat { node, min, max } => {
+            for _ in 0..*min {
+                compile(node, program)?;
+            }
+            match max {
+                // x* : L1: split L2, L3; L2: x; jump L1; L3:
+                None => {
+                    let split = program.len();
+                    program.push(Inst::Split(split + 1, 0));
+                    compile(node, program)?;
+                    program.push(Inst::Jump(split));
+                    program[split] = Inst::Split(split + 1, program.len());
+                }
+                // Each optional copy skips to the end when not taken.
+                Some(max) => {
+                    let mut splits = Vec::new();
+                    for _ in *min..*max {
+                        splits.push(program.len());
+                        program.push(Inst::Split(program.len() + 1, 0));
+                        compile(node, program)?;
+                    }
+                    for split in splits {
+                        program[split] = Inst::Split(split + 1, program.len());
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+struct RegexParser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl RegexParser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn parse_alternation(&mut self) -> Result<Node, RegexError> {
+        let mut branches = vec![self.parse_branch()?];
+        while self.eat('|') {
+            branches.push(self.parse_branch()?);
+        }
+        Ok(if branches.len() == 1 { branches.remove(0) } else { Node::Alternation(branches) })
+    }
+
+    fn parse_branch(&mut self) -> Result<Node, RegexError> {
+        let mut pieces = Vec::new();
+        while !matches!(self.peek(), None | Some('|' | ')')) {
+            let atom = self.parse_atom()?;
+            pieces.push(self.parse_quantifier(atom)?);
+        }
+        Ok(Node::Concat(pieces))
+    }
+
+    fn parse_atom(&mut self) -> Result<Node, RegexError> {
+        let Some(c) = self.peek() else { return Err(RegexError::Invalid) };
+        self.pos += 1;
+        let class = match c {
+            '(' => {
+                let node = self.parse_alternation()?;
+                return if self.eat(')') { Ok(node) } else { Err(RegexError::Invalid) };
+            }
+            // I-Regexp follows XML Schema: `.` stops at line ends.
+            '.' => Class { negated: true, ranges: vec![('\n', '\n'), ('\r', '\r')] },
+            '[' => self.parse_class_expr()?,
+            '\\' => self.parse_escape()?,
+            '*' | '+' | '?' | '{' | '}' | ']' => return Err(RegexError::Invalid),
+            c => Class::char(c),
+        };
+        Ok(Node::Class(class))
+    }
+
+    fn parse_quantifier(&mut self, node: Node) -> Result<Node, RegexError> {
+        let (min, max) = match self.peek() {
+            Some('*') => (0, None),
+            Some('+') => (1, None),
+            Some('?') => (0, Some(1)),
+            Some('{') => {
+                self.pos += 1;
+                let min = self.parse_count()?;
+                let max = if self.eat(',') {
+                    if self.peek() == Some('}') { None } else { Some(self.parse_count()?) }
+                } else {
+                    Some(min)
+                };
+                if self.peek() != Some('}') || max.is_some_and(|max| max < min) {
+                    return Err(RegexError::Invalid);
+                }
+                (min, max)
+            }
+            _ => return Ok(node),
+        };
+        self.pos += 1;
+        Ok(Node::Repeat { node: Box::new(node), min, max })
+    }
+
+    fn parse_count(&mut self) -> Result<u32, RegexError> {
+        let start = self.pos;
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        if start == self.pos {
+            return Err(RegexError::Invalid);
+        }
+        let digits: String = self.chars[start..self.pos].iter().collect();
+        match digits.parse::<u32>() {
+            Ok(count) if count <= MAX_REPEAT => Ok(count),
+            _ => Err(RegexError::Unsupported("repetition count is too large")),
+        }
+    }
+
+    // After the backslash.
+    fn parse_escape(&mut self) -> Result<Class, RegexError> {
+        match self.single_char_escape()? {
+            Some(c) => Ok(Class::char(c)),
+            None => Err(RegexError::Unsupported("Unicode category escapes are not supported")),
+        }
+    }
+
+    // A single-character escape after the backslash, or `None` for `\p`/`\P`.
+    fn single_char_escape(&mut self) -> Result<Option<char>, RegexError> {
+        let c = self.peek().ok_or(RegexError::Invalid)?;
+        self.pos += 1;
+        match c {
+            'n' => Ok(Some('\n')),
+            'r' => Ok(Some('\r')),
+            't' => Ok(Some('\t')),
+            '(' | ')' | '*' | '+' | '-' | '.' | '?' | '[' | '\\' | ']' | '^' | '{' | '|' | '}' => Ok(Some(c)),
+            'p' | 'P' => Ok(None),
+            _ => Err(RegexError::Invalid),
+        }
+    }
+
+    // After the `[`, through the closing `]`.
+    fn parse_class_expr(&mut self) -> Result<Class, RegexError> {
+        let negated = self.eat('^');
+        let mut ranges = Vec::new();
+        let mut first = true;
+        loop {
+            match self.peek() {
+                Some(']') if !first => {
+                    self.pos += 1;
+                    return Ok(Class { negated, ranges });
+                }
+                // `-` is literal only first or last.
+                Some('-') if first || self.chars.get(self.pos + 1) == Some(&']') => {
+                    self.pos += 1;
+                    ranges.push(('-', '-'));
+                }
+                _ => {
+                    let low = self.parse_class_char()?;
+                    let high = if self.peek() == Some('-') && self.chars.get(self.pos + 1) != Some(&']') {
+                        self.pos += 1;
+                        self.parse_class_char()?
+                    } else {
+                        low
+                    };
+                    if high < low {
+                        return Err(RegexError::Invalid);
+                    }
+                    ranges.push((low, high));
+                }
+            }
+            first = false;
+        }
+    }
+
+    fn parse_class_char(&mut self) -> Result<char, RegexError> {
+        match self.peek() {
+            None | Some('-' | '[' | ']') => Err(RegexError::Invalid),
+            Some('\\') => {
+                self.pos += 1;
+                self.single_char_escape()?
+                    .ok_or(RegexError::Unsupported("Unicode category escapes are not supported"))
+            }
+            Some(c) => {
+                self.pos += 1;
+                Ok(c)
+            }
+        }
+    }
+}
@@ -0,0 +1,883 @@
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::fmt;
+use std::fmt::{Formatter, Write};
+use std::str::FromStr;
+use crate::core::{JsonNumber, JsonValue};
+use crate::iregexp::{Regex, RegexError};
+use crate::parser::NumberParser;
+use crate::pointer::JsonPointer;
+use crate::value::{compare_numbers, values_equal};
+
+// Integers in queries are limited to the I-JSON exact range.
+const MAX_SAFE_INT: i64 = (1 << 53) - 1;
+
+/// A compiled RFC 9535 JSONPath query such as `$.users[?@.id > 2].name`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct JsonPath {
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+struct Segment {
+    descendant: bool,
+    selectors: Vec<Selector>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+enum Selector {
+    Name(String),
+    Wildcard,
+    Index(i64),
+    Slice { start: Option<i64>, end: Option<i64>, step: Option<i64> },
+    Filter(Box<LogicalExpr>),
+}
+
+#[derive(Debug, PartialEq, Clone)]
+enum LogicalExpr {
+    Or(Vec<LogicalExpr>),
+    And(Vec<LogicalExpr>),
+    Not(Box<LogicalExpr>),
+    Exists(Query),
+    Compare(Comparable, CompareOp, Comparable),
+    // A function returning a logical result, such as `match()`.
+    Test(Function),
+}
+
+#[derive(Debug, PartialEq, Clone)]
+struct Query {
+    relative: bool,
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+enum Comparable {
+    Literal(JsonValue),
+    Query(Query),
+    Function(Function),
+}
+
+#[derive(Debug, PartialEq, Clone)]
+enum Function {
+    Length(Box<Comparable>),
+    Count(Query),
+    Value(Query),
+    Match(Box<Comparable>, Pattern),
+    Search(Box<Comparable>, Pattern),
+}
+
+impl Function {
+    fn is_logical(&self) -> bool {
+        matches!(self, Function::Match(..) | Function::Search(..))
+    }
+}
+
+// The regular expression of `match()` or `search()`.
+#[derive(Debug, PartialEq, Clone)]
+enum Pattern {
+    // A string literal, compiled with the query; `None` if it is not an I-Regexp.
+    Literal(Option<Regex>),
+    // Anything else, compiled for each node it is tested against.
+    Value(Box<Comparable>),
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[non_exhaustive]
+pub enum PathError {
+    Syntax { offset: usize, reason: &'static str },
+    UnknownFunction { offset: usize, name: String },
+    /// The query parses but is not well-typed, e.g. `@.*` used in a comparison.
+    TypeError { offset: usize, reason: &'static str },
+    /// Valid JSONPath this implementation does not handle, such as a
+    /// `match()` pattern with a Unicode category escape like `\p{L}`.
+    Unsupported { offset: usize, reason: &'static str },
+}
+
+impl fmt::Display for PathError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            PathError::Syntax { offset, reason } => write!(f, "Invalid JSONPath at offset {}: {}", offset, reason),
+            PathError::UnknownFunction { offset, name } => {
+                write!(f, "Unknown JSONPath function '{}' at offset {}", name, offset)
+            }
+            PathError::TypeError { offset, reason } => write!(f, "Ill-typed JSONPath at offset {}: {}", offset, reason),
+            PathError::Unsupported { offset, reason } => write!(f, "Unsupported JSONPath at offset {}: {}", offset, reason),
+        }
+    }
+}
+
+impl std::error::Error for PathError {}
+
+/// One step of a node's location: an object member name or an array index.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum PathElement<'a> {
+    Name(&'a str),
+    Index(usize),
+}
+
+/// Renders `location` as an RFC 9535 normalized path, e.g. `$['users'][0]`.
+pub(crate) fn normalized_path<'a>(location: impl IntoIterator<Item = PathElement<'a>>) -> String {
+    let mut path = String::from("$");
+    for element in location {
+        match element {
+            PathElement::Name(name) => {
+                path.push_str("['");
+                for c in name.chars() {
+                    match c {
+                        '\'' => path.push_str("\\'"),
+                        '\\' => path.push_str("\\\\"),
+                        '\u{0008}' => path.push_str("\\b"),
+                        '\u{000C}' => path.push_str("\\f"),
+                        '\n' => path.push_str("\\n"),
+                        '\r' => path.push_str("\\r"),
+                        '\t' => path.push_str("\\t"),
+                        c if (c as u32) < 0x20 => {
+                            let _ = write!(path, "\\u{:04x}", c as u32);
+                        }
+                        c => path.push(c),
+                    }
+                }
+                path.push_str("']");
+            }
+            PathElement::Index(i) => {
+                let _ = write!(path, "[{}]", i);
+            }
+        }
+    }
+    path
+}
+
+/// A node selected by a query, together with its location in the document.
+#[derive(Debug, PartialEq, Clone)]
+pub struct PathNode<'a> {
+    location: Vec<PathElement<'a>>,
+    value: &'a JsonValue,
+}
+
+impl<'a> PathNode<'a> {
+    fn root(value: &'a JsonValue) -> Self {
+        Self { location: Vec::new(), value }
+    }
+
+    fn child(&self, element: PathElement<'a>, value: &'a JsonValue) -> Self {
+        let mut location = self.location.clone();
+        location.push(element);
+        Self { location, value }
+    }
+
+    fn children(&self) -> Vec<PathNode<'a>> {
+        match self.value {
+            JsonValue::Array(items) => items.iter().enumerate().map(|(i, v)| self.child(PathElement::Index(i), v)).collect(),
+            JsonValue::Object(pairs) => pairs.iter().map(|(k, v)| self.child(PathElement::Name(k), v)).collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn value(&self) -> &'a JsonValue {
+        self.value
+    }
+
+    pub fn location(&self) -> &[PathElement<'a>] {
+        &self.location
+    }
+
+    /// The RFC 9535 normalized path, e.g. `$['users'][0]['name']`.
+    pub fn normalized_path(&self) -> String {
+        normalized_path(self.location.iter().copied())
+    }
+
+    /// The same location as an RFC 6901 JSON Pointer.
+    pub fn pointer(&self) -> JsonPointer {
+        let mut pointer = JsonPointer::root();
+        for element in &self.location {
+            match element {
+                PathElement::Name(name) => pointer.push(*name),
+                PathElement::Index(i) => pointer.push(i.to_string()),
+            }
+        }
+        pointer
+    }
+}
+
+impl JsonPath {
+    pub fn parse(query: &str) -> Result<Self, PathError> {
+        let mut parser = PathParser { input: query, pos: 0 };
+        if !parser.eat('$') {
+            return parser.syntax("query must start with '$'");
+        }
+        let segments = parser.parse_segments()?;
+        if parser.pos != query.len() {
+            return parser.syntax("unexpected character");
+        }
+        Ok(Self { segments })
+    }
+
+    /// Evaluates the query, returning matched nodes in document order.
+    pub fn query<'a>(&self, root: &'a JsonValue) -> Vec<PathNode<'a>> {
+        select_segments(&self.segments, PathNode::root(root), root)
+    }
+}
+
+impl FromStr for JsonPath {
+    type Err = PathError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl JsonValue {
+    /// Parses and evaluates a JSONPath query against this value.
+    pub fn query(&self, path: &str) -> Result<Vec<PathNode<'_>>, PathError> {
+        Ok(JsonPath::parse(path)?.query(self))
+    }
+}
+
+impl Query {
+    fn is_singular(&self) -> bool {
+        self.segments.iter().all(|s| {
+            !s.descendant && matches!(s.selectors.as_slice(), [Selector::Name(_)] | [Selector::Index(_)])
+        })
+    }
+}
+
+fn select_segments<'a>(segments: &[Segment], start: PathNode<'a>, root: &'a JsonValue) -> Vec<PathNode<'a>> {
+    let mut nodes = vec![start];
+    for segment in segments {
+        let mut next = Vec::new();
+        for node in &nodes {
+            apply_segment(segment, node, root, &mut next);
+        }
+        nodes = next;
+    }
+    nodes
+}
+
+fn apply_segment<'a>(segment: &Segment, node: &PathNode<'a>, root: &'a JsonValue, out: &mut Vec<PathNode<'a>>) {
+    for selector in &segment.selectors {
+        apply_selector(selector, node, root, out);
+    }
+    if segment.descendant {
+        for child in node.children() {
+            apply_segment(segment, &child, root, out);
+        }
+    }
+}
+
+fn apply_selector<'a>(selector: &Selector, node: &PathNode<'a>, root: &'a JsonValue, out: &mut Vec<PathNode<'a>>) {
+    match (selector, node.value) {
+        (Selector::Name(name), JsonValue::Object(pairs)) => {
+            if let Some((k, v)) = pairs.iter().rev().find(|(k, _)| k == name) {
+                out.push(node.child(PathElement::Name(k), v));
+            }
+        }
+        (Selector::Wildcard, _) => out.extend(node.children()),
+        (Selector::Index(i), JsonValue::Array(items)) => {
+            if let Some(index) = normalize_index(*i, items.len()) {
+                out.push(node.child(PathElement::Index(index), &items[index]));
+            }
+        }
+        (Selector::Slice { start, end, step }, JsonValue::Array(items)) => {
+            for index in slice_indices(*start, *end, *step, items.len()) {
+                out.push(node.child(PathElement::Index(index), &items[index]));
+            }
+        }
+        (Selector::Filter(expr), _) => {
+            out.extend(node.children().into_iter().filter(|child| eval_logical(expr, child.value, root)));
+        }
+        _ => {}
+    }
+}
+
+fn normalize_index(i: i64, len: usize) -> Option<usize> {
+    let len = len as i64;
+    let index = if i < 0 { len + i } else { i };
+    (0..len).contains(&index).then_some(index as usize)
+}
+
+fn slice_indices(start: Option<i64>, end: Option<i64>, step: Option<i64>, len: usize) -> Vec<usize> {
+    let len = len as i64;
+    let step = step.unwrap_or(1);
+    let normalize = |i: i64| if i >= 0 { i } else { len + i };
+    let mut indices = Vec::new();
+
+    if step > 0 {
+        let lower = normalize(start.unwrap_or(0)).clamp(0, len);
+        let upper = normalize(end.unwrap_or(len)).clamp(0, len);
+        let mut i = lower;
+        while i < upper {
+            indices.push(i as usize);
+            i += step;
+        }
+    } else if step < 0 {
+        let upper = normalize(start.unwrap_or(len - 1)).clamp(-1, len - 1);
+        let lower = normalize(end.unwrap_or(-len - 1)).clamp(-1, len - 1);
+        let mut i = upper;
+        while lower < i {
+            indices.push(i as usize);
+            i += step;
+        }
+    }
+    indices
+}
+
+fn eval_logical(expr: &LogicalExpr, current: &JsonValue, root: &JsonValue) -> bool {
+    match expr {
+        LogicalExpr::Or(terms) => terms.iter().any(|t| eval_logical(t, current, root)),
+        LogicalExpr::And(terms) => terms.iter().all(|t| eval_logical(t, current, root)),
+        LogicalExpr::Not(inner) => !eval_logical(inner, current, root),
+        LogicalExpr::Exists(query) => !eval_query(query, current, root).is_empty(),
+        LogicalExpr::Compare(left, op, right) => {
+            let left = eval_comparable(left, current, root);
+            let right = eval_comparable(right, current, root);
+            compare(left.as_deref(), *op, right.as_deref())
+        }
+        LogicalExpr::Test(Function::Match(subject, pattern)) => regex_test(subject, pattern, false, current, root),
+        LogicalExpr::Test(Function::Search(subject, pattern)) => regex_test(subject, pattern, true, current, root),
+        LogicalExpr::Test(_) => false,
+    }
+}
+
+// `match()` and `search()`: false unless both arguments are strings and the
+// pattern is an I-Regexp.
+fn regex_test(subject: &Comparable, pattern: &Pattern, search: bool, current: &JsonValue, root: &JsonValue) -> bool {
+    let subject = eval_comparable(subject, current, root);
+    let Some(JsonValue::String(text)) = subject.as_deref() else {
+        return false;
+    };
+    match pattern {
+        Pattern::Literal(regex) => regex.as_ref().is_some_and(|regex| regex.is_match(text, search)),
+        Pattern::Value(pattern) => match eval_comparable(pattern, current, root).as_deref() {
+            Some(JsonValue::String(pattern)) => Regex::parse(pattern).is_ok_and(|regex| regex.is_match(text, search)),
+            _ => false,
+        },
+    }
+}
+
+fn eval_query<'a>(query: &Query, current: &'a JsonValue, root: &'a JsonValue) -> Vec<PathNode<'a>> {
+    let start = if query.relative { current } else { root };
+    select_segments(&query.segments, PathNode::root(start), root)
+}
+
+// `None` is the RFC's "Nothing": an empty node list or a function without a result.
+fn eval_comparable<'a>(comparable: &'a Comparable, current: &'a JsonValue, root: &'a JsonValue) -> Option<Cow<'a, JsonValue>> {
+    match comparable {
+        Comparable::Literal(value) => Some(Cow::Borrowed(value)),
+        Comparable::Query(query) => eval_query(query, current, root).first().map(|n| Cow::Borrowed(n.value)),
+        Comparable::Function(Function::Length(arg)) => {
+            let len = match eval_comparable(arg, current, root)?.as_ref() {
+                JsonValue::String(s) => s.chars().count(),
+                JsonValue::Wtf8(s) => s.code_points().count(),
+                JsonValue::Array(items) => items.len(),
+                JsonValue::Object(pairs) => pairs.len(),
+                _ => return None,
+            };
+            Some(Cow::Owned(JsonValue::Number(JsonNumber::Integer(len as i64))))
+        }
+        Comparable::Function(Function::Count(query)) => {
+            let count = eval_query(query, current, root).len();
+            Some(Cow::Owned(JsonValue::Number(JsonNumber::Integer(count as i64))))
+        }
+        Comparable::Function(Function::Value(query)) => match eval_query(query, current, root).as_slice() {
+            [node] => Some(Cow::Borrowed(node.value)),
+            _ => None,
+        },
+        // Logical results are only tested, which `check_comparable` enforces.
+        Comparable::Function(Function::Match(..) | Function::Search(..)) => None,
+    }
+}
+
+fn compare(left: Option<&JsonValue>, op: CompareOp, right: Option<&JsonValue>) -> bool {
+    match op {
+        CompareOp::Eq => equal(left, right),
+        CompareOp::Ne => !equal(left, right),
+        CompareOp::Lt => less(left, right),
+        CompareOp::Le => less(left, right) || equal(left, right),
+        CompareOp::Gt => less(right, left),
+        CompareOp::Ge => less(right, left) || equal(left, right),
+    }
+}
+
+fn equal(left: Option<&JsonValue>, right: Option<&JsonValue>) -> bool {
+    match (left, right) {
+        (None, None) => true,
+        (Some(a), Some(b)) => values_equal(a, b),
+        _ => false,
+    }
+}
+
+fn less(left: Option<&JsonValue>, right: Option<&JsonValue>) -> bool {
+    match (left, right) {
+        (Some(JsonValue::Number(a)), Some(JsonValue::Number(b))) => compare_numbers(a, b) == Some(Ordering::Less),
+        (Some(JsonValue::String(a)), Some(JsonValue::String(b))) => a < b,
+        _ => false,
+    }
+}
+
+struct PathParser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+fn is_name_first(c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '_' || (c as u32) >= 0x80
+}
+
+impl<'a> PathParser<'a> {
+    fn peek(&self) -> Option<char> {
+        self.input[self.pos..].chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn eat_str(&mut self, s: &str) -> bool {
+        if self.input[self.pos..].starts_with(s) {
+            self.pos += s.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn eat_digits(&mut self) -> usize {
+        let start = self.pos;
+        while matches!(self.peek(), Some(c) if c.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        self.pos - start
+    }
+
+    fn skip_blank(&mut self) {
+        while matches!(self.peek(), Some(' ' | '\t' | '\n' | '\r')) {
+            self.pos += 1;
+        }
+    }
+
+    fn syntax<T>(&self, reason: &'static str) -> Result<T, PathError> {
+        Err(PathError::Syntax { offset: self.pos, reason })
+    }
+
+    fn parse_segments(&mut self) -> Result<Vec<Segment>, PathError> {
+        let mut segments = Vec::new();
+        loop {
+            let saved = self.pos;
+            self.skip_blank();
+            match self.peek() {
+                Some('.' | '[') => segments.push(self.parse_segment()?),
+                _ => {
+                    self.pos = saved;
+                    return Ok(segments);
+                }
+            }
+        }
+    }
+
+    fn parse_segment(&mut self) -> Result<Segment, PathError> {
+        if self.eat_str("..") {
+            let selectors = match self.peek() {
+                Some('[') => self.parse_bracketed()?,
+                Some('*') => {
+                    self.bump();
+                    vec![Selector::Wildcard]
+                }
+                _ => vec![Selector::Name(self.parse_shorthand_name()?)],
+            };
+            Ok(Segment { descendant: true, selectors })
+        } else if self.eat('.') {
+            let selector = if self.eat('*') {
+                Selector::Wildcard
+            } else {
+                Selector::Name(self.parse_shorthand_name()?)
+            };
+            Ok(Segment { descendant: false, selectors: vec![selector] })
+        } else {
+            Ok(Segment { descendant: false, selectors: self.parse_bracketed()? })
+        }
+    }
+
+    fn parse_shorthand_name(&mut self) -> Result<String, PathError> {
+        let start = self.pos;
+        match self.peek() {
+            Some(c) if is_name_first(c) => self.bump(),
+            _ => return self.syntax("expected member name"),
+        };
+        while matches!(self.peek(), Some(c) if is_name_first(c) || c.is_ascii_digit()) {
+            self.bump();
+        }
+        Ok(self.input[start..self.pos].to_string())
+    }
+
+    fn parse_bracketed(&mut self) -> Result<Vec<Selector>, PathError> {
+        if !self.eat('[') {
+            return self.syntax("expected '['");
+        }
+        let mut selectors = Vec::new();
+        loop {
+            self.skip_blank();
+            selectors.push(self.parse_selector()?);
+            self.skip_blank();
+            if self.eat(']') {
+                return Ok(selectors);
+            }
+            if !self.eat(',') {
+                return self.syntax("expected ',' or ']'");
+            }
+        }
+    }
+
+    fn parse_selector(&mut self) -> Result<Selector, PathError> {
+        match self.peek() {
+            Some(quote @ ('\'' | '"')) => Ok(Selector::Name(self.parse_string_literal(quote)?)),
+            Some('*') => {
+                self.bump();
+                Ok(Selector::Wildcard)
+            }
+            Some('?') => {
+                self.bump();
+                self.skip_blank();
+                Ok(Selector::Filter(Box::new(self.parse_logical_or()?)))
+            }
+            Some(c) if c == '-' || c == ':' || c.is_ascii_digit() => self.parse_index_or_slice(),
+            _ => self.syntax("expected selector"),
+        }
+    }
+
+    fn parse_index_or_slice(&mut self) -> Result<Selector, PathError> {
+        let start = self.parse_optional_int()?;
+        self.skip_blank();
+        if !self.eat(':') {
+            return match start {
+                Some(i) => Ok(Selector::Index(i)),
+                None => self.syntax("expected index"),
+            };
+        }
+        self.skip_blank();
+        let end = self.parse_optional_int()?;
+        self.skip_blank();
+        let step = if self.eat(':') {
+            self.skip_blank();
+            self.parse_optional_int()?
+        } else {
+            None
+        };
+        Ok(Selector::Slice { start, end, step })
+    }
+
+    fn parse_optional_int(&mut self) -> Result<Option<i64>, PathError> {
+        match self.peek() {
+            Some(c) if c == '-' || c.is_ascii_digit() => self.parse_int().map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    fn parse_int(&mut self) -> Result<i64, PathError> {
+        let start = self.pos;
+        let negative = self.eat('-');
+        let digits_start = self.pos;
+        let count = self.eat_digits();
+        let digits = &self.input[digits_start..self.pos];
+        if count == 0 || (digits.starts_with('0') && (count > 1 || negative)) {
+            self.pos = start;
+            return self.syntax("invalid integer");
+        }
+        match self.input[start..self.pos].parse::<i64>() {
+            Ok(i) if (-MAX_SAFE_INT..=MAX_SAFE_INT).contains(&i) => Ok(i),
+            _ => {
+                self.pos = start;
+                self.syntax("integer out of range")
+            }
+        }
+    }
+
+    fn parse_string_literal(&mut self, quote: char) -> Result<String, PathError> {
+        self.bump();
+        let mut string = String::new();
+        loop {
+            match self.bump() {
+                None => return self.syntax("unterminated string"),
+                Some(c) if c == quote => return Ok(string),
+                Some('\\') => {
+                    let escaped = match self.bump() {
+                        Some('b') => '\u{0008}',
+                        Some('f') => '\u{000C}',
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('/') => '/',
+                        Some('\\') => '\\',
+                        Some('u') => self.parse_unicode_escape()?,
+                        Some(c) if c == quote => c,
+                        _ => return self.syntax("invalid escape sequence"),
+                    };
+                    string.push(escaped);
+                }
+                Some(c) if (c as u32) < 0x20 => return self.syntax("control character in string"),
+                Some(c) => string.push(c),
+            }
+        }
+    }
+
+    fn parse_hex4(&mut self) -> Result<u32, PathError> {
+        match self.input.get(self.pos..self.pos + 4) {
+            Some(hex) if hex.bytes().all(|b| b.is_ascii_hexdigit()) => {
+                self.pos += 4;
+                Ok(u32::from_str_radix(hex, 16).unwrap_or_default())
+            }
+            _ => self.syntax("expected four hex digits"),
+        }
+    }
+
+    fn parse_unicode_escape(&mut self) -> Result<char, PathError> {
+        let high = self.parse_hex4()?;
+        let code = if (0xD800..0xDC00).contains(&high) {
+            if !self.eat_str("\\u") {
+                return self.syntax("unpaired surrogate");
+            }
+            let low = self.parse_hex4()?;
+            if !(0xDC00..0xE000).contains(&low) {
+                return self.syntax("unpaired surrogate");
+            }
+            0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+        } else {
+            high
+        };
+        match char::from_u32(code) {
+            Some(c) => Ok(c),
+            None => self.syntax("unpaired surrogate"),
+        }
+    }
+
+    fn parse_logical_or(&mut self) -> Result<LogicalExpr, PathError> {
+        let mut terms = vec![self.parse_logical_and()?];
+        loop {
+            let saved = self.pos;
+            self.skip_blank();
+            if !self.eat_str("||") {
+                self.pos = saved;
+                break;
+            }
+            self.skip_blank();
+            terms.push(self.parse_logical_and()?);
+        }
+        Ok(if terms.len() == 1 { terms.remove(0) } else { LogicalExpr::Or(terms) })
+    }
+
+    fn parse_logical_and(&mut self) -> Result<LogicalExpr, PathError> {
+        let mut terms = vec![self.parse_basic()?];
+        loop {
+            let saved = self.pos;
+            self.skip_blank();
+            if !self.eat_str("&&") {
+                self.pos = saved;
+                break;
+            }
+            self.skip_blank();
+            terms.push(self.parse_basic()?);
+        }
+        Ok(if terms.len() == 1 { terms.remove(0) } else { LogicalExpr::And(terms) })
+    }
+
+    fn parse_basic(&mut self) -> Result<LogicalExpr, PathError> {
+        if self.eat('!') {
+            self.skip_blank();
+            if self.eat('(') {
+                return Ok(LogicalExpr::Not(Box::new(self.parse_paren_rest()?)));
+            }
+            let start = self.pos;
+            return match self.parse_comparable()? {
+                Comparable::Query(query) => Ok(LogicalExpr::Not(Box::new(LogicalExpr::Exists(query)))),
+                Comparable::Function(function) if function.is_logical() => {
+                    Ok(LogicalExpr::Not(Box::new(LogicalExpr::Test(function))))
+                }
+                _ => Err(PathError::TypeError { offset: start, reason: "'!' must be applied to a query or a parenthesized expression" }),
+            };
+        }
+        if self.eat('(') {
+            return self.parse_paren_rest();
+        }
+
+        let start = self.pos;
+        let left = self.parse_comparable()?;
+        let saved = self.pos;
+        self.skip_blank();
+        match self.parse_compare_op() {
+            Some(op) => {
+                self.skip_blank();
+                let right_start = self.pos;
+                let right = self.parse_comparable()?;
+                check_comparable(&left, start)?;
+                check_comparable(&right, right_start)?;
+                Ok(LogicalExpr::Compare(left, op, right))
+            }
+            None => {
+                self.pos = saved;
+                match left {
+                    Comparable::Query(query) => Ok(LogicalExpr::Exists(query)),
+                    Comparable::Function(function) if function.is_logical() => Ok(LogicalExpr::Test(function)),
+                    _ => Err(PathError::TypeError { offset: start, reason: "expected a comparison or an existence test" }),
+                }
+            }
+        }
+    }
+
+    fn parse_paren_rest(&mut self) -> Result<LogicalExpr, PathError> {
+        self.skip_blank();
+        let expr = self.parse_logical_or()?;
+        self.skip_blank();
+        if !self.eat(')') {
+            return self.syntax("expected ')'");
+        }
+        Ok(expr)
+    }
+
+    fn parse_compare_op(&mut self) -> Option<CompareOp> {
+        const OPS: [(&str, CompareOp); 6] = [
+            ("==", CompareOp::Eq),
+            ("!=", CompareOp::Ne),
+            ("<=", CompareOp::Le),
+            (">=", CompareOp::Ge),
+            ("<", CompareOp::Lt),
+            (">", CompareOp::Gt),
+        ];
+        OPS.into_iter().find(|(text, _)| self.eat_str(text)).map(|(_, op)| op)
+    }
+
+    fn parse_comparable(&mut self) -> Result<Comparable, PathError> {
+        match self.peek() {
+            Some(c @ ('@' | '$')) => {
+                self.bump();
+                let segments = self.parse_segments()?;
+                Ok(Comparable::Query(Query { relative: c == '@', segments }))
+            }
+            Some(quote @ ('\'' | '"')) => Ok(Comparable::Literal(JsonValue::String(self.parse_string_literal(quote)?))),
+            Some(c) if c == '-' || c.is_ascii_digit() => Ok(Comparable::Literal(JsonValue::Number(self.parse_number_literal()?))),
+            Some(c) if c.is_ascii_lowercase() => self.parse_keyword_or_function(),
+            _ => self.syntax("expected a query, literal or function"),
+        }
+    }
+
+    fn parse_number_literal(&mut self) -> Result<JsonNumber, PathError> {
+        let start = self.pos;
+        self.eat('-');
+        let int_start = self.pos;
+        let count = self.eat_digits();
+        let valid_int = count == 1 || (count > 1 && !self.input[int_start..].starts_with('0'));
+        let valid_frac = !self.eat('.') || self.eat_digits() > 0;
+        let valid_exp = !(self.eat('e') || self.eat('E')) || {
+            let _ = self.eat('+') || self.eat('-');
+            self.eat_digits() > 0
+        };
+        if !(valid_int && valid_frac && valid_exp) {
+            self.pos = start;
+            return self.syntax("invalid number");
+        }
+        NumberParser::parse(&self.input[start..self.pos])
+            .ok_or(PathError::Syntax { offset: start, reason: "invalid number" })
+    }
+
+    fn parse_keyword_or_function(&mut self) -> Result<Comparable, PathError> {
+        let input = self.input;
+        let start = self.pos;
+        while matches!(self.peek(), Some(c) if c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_') {
+            self.pos += 1;
+        }
+        let name = &input[start..self.pos];
+        if self.peek() == Some('(') {
+            return self.parse_function(name, start);
+        }
+        match name {
+            "true" => Ok(Comparable::Literal(JsonValue::Bool(true))),
+            "false" => Ok(Comparable::Literal(JsonValue::Bool(false))),
+            "null" => Ok(Comparable::Literal(JsonValue::Null)),
+            _ => {
+                self.pos = start;
+                self.syntax("expected a query, literal or function")
+            }
+        }
+    }
+
+    fn parse_function(&mut self, name: &str, start: usize) -> Result<Comparable, PathError> {
+        self.bump(); // consume '('
+        self.skip_blank();
+        let arg_start = self.pos;
+        let function = match name {
+            "length" => {
+                let arg = self.parse_comparable()?;
+                check_comparable(&arg, arg_start)?;
+                Function::Length(Box::new(arg))
+            }
+            "count" | "value" => {
+                let Comparable::Query(query) = self.parse_comparable()? else {
+                    return Err(PathError::TypeError { offset: arg_start, reason: "expected a query argument" });
+                };
+                if name == "count" { Function::Count(query) } else { Function::Value(query) }
+            }
+            "match" | "search" => {
+                let subject = self.parse_comparable()?;
+                check_comparable(&subject, arg_start)?;
+                self.skip_blank();
+                if !self.eat(',') {
+                    return self.syntax("expected ','");
+                }
+                self.skip_blank();
+                let pattern_start = self.pos;
+                let pattern = match self.parse_comparable()? {
+                    Comparable::Literal(JsonValue::String(text)) => match Regex::parse(&text) {
+                        Ok(regex) => Pattern::Literal(Some(regex)),
+                        Err(RegexError::Invalid) => Pattern::Literal(None),
+                        Err(RegexError::Unsupported(reason)) => {
+                            return Err(PathError::Unsupported { offset: pattern_start, reason });
+                        }
+                    },
+                    pattern => {
+                        check_comparable(&pattern, pattern_start)?;
+                        Pattern::Value(Box::new(pattern))
+                    }
+                };
+                let subject = Box::new(subject);
+                if name == "match" { Function::Match(subject, pattern) } else { Function::Search(subject, pattern) }
+            }
+            _ => return Err(PathError::UnknownFunction { offset: start, name: name.to_string() }),
+        };
+        self.skip_blank();
+        if !self.eat(')') {
+            return self.syntax("expected ')'");
+        }
+        Ok(Comparable::Function(function))
+    }
+}
+
+fn check_comparable(comparable: &Comparable, offset: usize) -> Result<(), PathError> {
+    match comparable {
+        Comparable::Query(query) if !query.is_singular() => {
+            Err(PathError::TypeError { offset, reason: "non-singular query used as a value" })
+        }
+        Comparable::Function(function) if function.is_logical() => {
+            Err(PathError::TypeError { offset, reason: "match() and search() can only be tested, not used as a value" })
+        }
+        _ => Ok(()),
+    }
+}
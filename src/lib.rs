@@ -1,11 +1,94 @@
+mod borrowed;
 mod core;
+mod convert;
+#[cfg(feature = "serde")]
+pub mod de;
+mod diagnostic;
+mod document;
 mod io;
+mod iregexp;
 mod engine;
 mod driver;
+mod jsonpath;
+mod merge;
+mod number;
+mod options;
 mod parser;
+mod patch;
+mod pointer;
+mod recovery;
+mod scan;
+#[cfg(feature = "serde")]
+pub mod ser;
 mod serialization;
+mod source;
+mod stream;
+mod value;
+mod writer;
+mod wtf8;
 pub mod cli;
 
+pub use crate::borrowed::BorrowedJsonValue;
+pub use crate::convert::{FromJson, FromJsonError, FromJsonErrorKind, ToJson};
+#[cfg(feature = "derive")]
+pub use basic_json_parser_derive::{FromJson, ToJson};
+pub use crate::document::{Document, Elements, Members, Node};
+pub use crate::core::{ErrorContext, JsonValue, JsonNumber, JsonError, Limit, Position, RawNumber};
+pub use crate::driver::{deserialize, deserialize_with, deserialize_borrowed, deserialize_borrowed_with, deserialize_document, deserialize_document_with, deserialize_reader, deserialize_reader_with, deserialize_recovering, deserialize_reader_recovering, serialize, serialize_pretty, serialize_pretty_with, serialize_with_options, serialize_to_writer, serialize_pretty_to_writer};
+pub use crate::pointer::{JsonPointer, PointerError};
+pub use crate::jsonpath::{JsonPath, PathElement, PathError, PathNode};
+pub use crate::patch::{JsonPatch, PatchError, PatchOperation};
+pub use crate::merge::create_merge_patch;
+pub use crate::options::{DuplicateKeyPolicy, Limits, LoneSurrogatePolicy, ParseOptions};
+pub use crate::stream::{JsonEvent, JsonReader, Scalar};
+pub use crate::source::{ReadSource, Source, StrSource};
+pub use crate::writer::{JsonWriter, NewlineStyle, NonFinitePolicy, PrettyConfig, WriteOptions};
+pub use crate::wtf8::Wtf8String;
+
+pub use crate::core::JsonValue as Value;
+pub use crate::core::JsonNumber as Number;
+pub use crate::core::JsonError as Error;
+
+/// Parses a JSON document into a [`Value`].
+pub fn from_str(input: &str) -> Result<Value, Error> {
+    deserialize(input)
+}
+
+/// Parses a document accepting the extensions enabled in `options`, e.g.
+/// [`ParseOptions::json5`].
+pub fn from_str_with(input: &str, options: &ParseOptions) -> Result<Value, Error> {
+    deserialize_with(input, options)
+}
+
+/// Parses a JSON document from any `std::io::Read` without buffering it all first.
+pub fn from_reader<R: std::io::Read>(reader: R) -> Result<Value, Error> {
+    deserialize_reader(reader)
+}
+
+/// Serializes a [`Value`] into compact JSON text.
+pub fn to_string(value: &Value) -> String {
+    serialize(value)
+}
+
+/// Serializes a [`Value`] into indented, human-readable JSON text.
+pub fn to_string_pretty(value: &Value) -> String {
+    serialize_pretty(value)
+}
+
+/// Serializes a [`Value`] into pretty JSON text laid out according to `config`.
+pub fn to_string_with(value: &Value, config: &PrettyConfig) -> String {
+    serialize_pretty_with(value, config)
+}
+
+/// Streams a [`Value`] as compact JSON into any `std::io::Write`.
+pub fn to_writer<W: std::io::Write>(writer: W, value: &Value) -> std::io::Result<()> {
+    serialize_to_writer(value, writer)
+}
+
+/// Streams a [`Value`] as indented JSON into any `std::io::Write`.
+pub fn to_writer_pretty<W: std::io::Write>(writer: W, value: &Value) -> std::io::Result<()> {
+    serialize_pretty_to_writer(value, writer)
+}
 
 #[cfg(test)]
-mod tests;
\ No newline at end of file
+mod tests;
@@ -1,5 +1,6 @@
+use std::process::ExitCode;
 use basic_json_parser::cli::handle_cli;
 
-fn main() {
-    handle_cli();
+fn main() -> ExitCode {
+    handle_cli()
 }
@@ -0,0 +1,59 @@
+use crate::core::JsonValue;
+use crate::value::{distinct_members, values_equal};
+
+impl JsonValue {
+    /// Applies an RFC 7396 JSON Merge Patch in place.
+    ///
+    /// `null` members delete keys, object members merge recursively and any
+    /// other value replaces the target. Existing keys keep their position and
+    /// new keys are appended in patch order.
+    pub fn merge_patch(&mut self, patch: &JsonValue) {
+        let JsonValue::Object(patch_pairs) = patch else {
+            *self = patch.clone();
+            return;
+        };
+        if !self.is_object() {
+            *self = JsonValue::Object(Vec::new());
+        }
+        if let JsonValue::Object(pairs) = self {
+            for (key, value) in patch_pairs {
+                if value.is_null() {
+                    pairs.retain(|(k, _)| k != key);
+                    continue;
+                }
+                match pairs.iter_mut().rev().find(|(k, _)| k == key) {
+                    Some((_, slot)) => slot.merge_patch(value),
+                    None => {
+                        let mut slot = JsonValue::Null;
+                        slot.merge_patch(value);
+                        pairs.push((key.clone(), slot));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Computes a merge patch that turns `from` into `to`.
+///
+/// Merge patches cannot express a `null` member value, so such members in
+/// `to` come out as deletions. A repeated key counts by its last value.
+pub fn create_merge_patch(from: &JsonValue, to: &JsonValue) -> JsonValue {
+    let (JsonValue::Object(old), JsonValue::Object(new)) = (from, to) else {
+        return to.clone();
+    };
+    let mut pairs = Vec::new();
+    for (key, new_value) in distinct_members(new) {
+        match from.get(key) {
+            Some(old_value) if values_equal(old_value, new_value) => {}
+            Some(old_value) => pairs.push((key.clone(), create_merge_patch(old_value, new_value))),
+            None => pairs.push((key.clone(), new_value.clone())),
+        }
+    }
+    for (key, _) in distinct_members(old) {
+        if to.get(key).is_none() {
+            pairs.push((key.clone(), JsonValue::Null));
+        }
+    }
+    JsonValue::Object(pairs)
+}
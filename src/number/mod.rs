@@ -0,0 +1,187 @@
+use std::cmp::Ordering;
+use std::iter;
+use crate::core::JsonNumber;
+
+/// Longest plain-decimal expansion [`JsonNumber::to_decimal_string`] produces.
+const MAX_DECIMAL_DIGITS: i64 = 4096;
+
+impl JsonNumber {
+    /// The exact integer value, if the number is whole and fits `i128`.
+    pub fn to_i128(&self) -> Option<i128> {
+        match *self {
+            JsonNumber::Integer(i) => Some(i as i128),
+            JsonNumber::UnsignedInteger(u) => Some(u as i128),
+            JsonNumber::Float(f) => {
+                let whole = f.is_finite() && f.fract() == 0.0;
+                (whole && f >= -(2f64.powi(127)) && f < 2f64.powi(127)).then_some(f as i128)
+            }
+            JsonNumber::Raw(_) => {
+                let plain = self.to_decimal_string()?;
+                if plain.contains('.') { None } else { plain.parse().ok() }
+            }
+        }
+    }
+
+    /// The value written out without an exponent, e.g. `1.5e-3` as `0.0015`.
+    ///
+    /// Returns `None` for NaN and infinities, and when the expansion would
+    /// exceed 4096 digits.
+    pub fn to_decimal_string(&self) -> Option<String> {
+        Decimal::of(self)?.to_plain()
+    }
+
+    /// The nearest `f64`. Raw numbers beyond the `f64` range become infinite
+    /// and extra digits are rounded away.
+    pub fn to_f64_lossy(&self) -> f64 {
+        self.as_f64()
+    }
+}
+
+/// Formats a finite float using the shortest digits that round-trip.
+///
+/// Magnitudes from 1e-6 up to 1e21 are written out in full and everything
+/// else in exponent notation, the same split JavaScript uses. With
+/// `keep_point`, whole values get a `.0` so they parse back as floats.
+/// Negative zero always does, as `-0` would read back as the integer 0.
+pub(crate) fn format_float(value: f64, keep_point: bool) -> String {
+    // `{:e}` produces the shortest round-trip digits, e.g. "-1.2345e-8".
+    let scientific = format!("{:e}", value);
+    let (mantissa, exponent) = scientific.split_once('e').unwrap_or((&scientific, "0"));
+    let exponent: i64 = exponent.parse().unwrap_or(0);
+    let (negative, mantissa) = match mantissa.strip_prefix('-') {
+        Some(mantissa) => (true, mantissa),
+        None => (false, mantissa),
+    };
+    let digits: String = mantissa.chars().filter(|&c| c != '.').collect();
+    let len = digits.len() as i64;
+
+    let mut text = String::with_capacity(digits.len() + 8);
+    if negative {
+        text.push('-');
+    }
+    if !(-6..21).contains(&exponent) {
+        text.push_str(&digits[..1]);
+        if len > 1 {
+            text.push('.');
+            text.push_str(&digits[1..]);
+        }
+        text.push('e');
+        text.push_str(&exponent.to_string());
+        return text;
+    }
+    let point = exponent + 1;
+    if point <= 0 {
+        text.push_str("0.");
+        text.extend(iter::repeat_n('0', (-point) as usize));
+        text.push_str(&digits);
+    } else if point >= len {
+        text.push_str(&digits);
+        text.extend(iter::repeat_n('0', (point - len) as usize));
+        if keep_point || (negative && value == 0.0) {
+            text.push_str(".0");
+        }
+    } else {
+        text.push_str(&digits[..point as usize]);
+        text.push('.');
+        text.push_str(&digits[point as usize..]);
+    }
+    text
+}
+
+/// Compares two finite numbers exactly, digit by digit. Floats take part
+/// through their shortest round-trip representation.
+pub(crate) fn compare_decimal(a: &JsonNumber, b: &JsonNumber) -> Option<Ordering> {
+    Some(Decimal::of(a)?.cmp(&Decimal::of(b)?))
+}
+
+// A finite number as ±0.DIGITS × 10^point, with no leading or trailing
+// zeros in `digits`. Zero has no digits and is never negative.
+struct Decimal {
+    negative: bool,
+    digits: Vec<u8>,
+    point: i64,
+}
+
+impl Decimal {
+    fn of(number: &JsonNumber) -> Option<Self> {
+        match number {
+            JsonNumber::Integer(i) => Self::parse(&i.to_string()),
+            JsonNumber::UnsignedInteger(u) => Self::parse(&u.to_string()),
+            JsonNumber::Float(f) if f.is_finite() => Self::parse(&format!("{:e}", f)),
+            JsonNumber::Float(_) => None,
+            JsonNumber::Raw(s) => Self::parse(s.as_str()),
+        }
+    }
+
+    fn parse(text: &str) -> Option<Self> {
+        let (negative, rest) = match text.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, text.strip_prefix('+').unwrap_or(text)),
+        };
+        let (mantissa, exponent) = match rest.find(['e', 'E']) {
+            Some(i) => (&rest[..i], rest[i + 1..].parse::<i64>().ok()?),
+            None => (rest, 0),
+        };
+        let (int, frac) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+        let mut digits: Vec<u8> = int.bytes().chain(frac.bytes()).collect();
+        if digits.is_empty() || !digits.iter().all(u8::is_ascii_digit) {
+            return None;
+        }
+        let leading = digits.iter().take_while(|&&d| d == b'0').count();
+        digits.drain(..leading);
+        while digits.last() == Some(&b'0') {
+            digits.pop();
+        }
+        if digits.is_empty() {
+            return Some(Self { negative: false, digits, point: 0 });
+        }
+        let point = (int.len() as i64).checked_add(exponent)?.checked_sub(leading as i64)?;
+        Some(Self { negative, digits, point })
+    }
+
+    fn signum(&self) -> i8 {
+        match (self.digits.is_empty(), self.negative) {
+            (true, _) => 0,
+            (false, true) => -1,
+            (false, false) => 1,
+        }
+    }
+
+    fn cmp(&self, other: &Self) -> Ordering {
+        let by_sign = self.signum().cmp(&other.signum());
+        if by_sign != Ordering::Equal || self.signum() == 0 {
+            return by_sign;
+        }
+        let magnitude = self.point.cmp(&other.point).then_with(|| self.digits.cmp(&other.digits));
+        if self.negative { magnitude.reverse() } else { magnitude }
+    }
+
+    fn to_plain(&self) -> Option<String> {
+        if self.digits.is_empty() {
+            return Some("0".to_string());
+        }
+        let len = self.digits.len() as i64;
+        if self.point.max(len) - self.point.min(0) > MAX_DECIMAL_DIGITS {
+            return None;
+        }
+        let digits = std::str::from_utf8(&self.digits).ok()?;
+        let mut plain = String::new();
+        if self.negative {
+            plain.push('-');
+        }
+        if self.point <= 0 {
+            plain.push_str("0.");
+            plain.extend(iter::repeat_n('0', (-self.point) as usize));
+            plain.push_str(digits);
+        } else if self.point >= len {
+            plain.push_str(digits);
+            plain.extend(iter::repeat_n('0', (self.point - len) as usize));
+        } else {
+            let (int, frac) = digits.split_at(self.point as usize);
+            plain.push_str(int);
+            plain.push('.');
+            plain.push_str(frac);
+        }
+        Some(plain)
+    }
+}
@@ -0,0 +1,170 @@
+use crate::core::{JsonError, Limit, Position};
+
+/// What to do with a `\u` escape naming half of a UTF-16 surrogate pair
+/// without its other half, e.g. `"\ud800"`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum LoneSurrogatePolicy {
+    /// Fail with `InvalidEscapeSequence`.
+    #[default]
+    Error,
+    /// Substitute U+FFFD REPLACEMENT CHARACTER.
+    Replace,
+    /// Keep the surrogate: a string value holding one is read as a
+    /// [`JsonValue::Wtf8`](crate::JsonValue::Wtf8) and written back with the
+    /// same escapes. Member names must be valid text and still fail.
+    Preserve,
+}
+
+/// What to do when an object names the same member twice, e.g.
+/// `{"a": 1, "a": 2}`. RFC 8259 leaves the outcome to the implementation.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum DuplicateKeyPolicy {
+    /// Keep every member in document order, as written.
+    #[default]
+    All,
+    /// Keep the first value; later ones are parsed and dropped.
+    First,
+    /// Keep the last value, at the position of the first member.
+    Last,
+    /// Fail with [`JsonError::DuplicateKey`], which knows where both are.
+    Error,
+}
+
+/// Caps on the size and shape of a document, so that untrusted input cannot
+/// exhaust the stack or memory. Going past one fails with
+/// [`JsonError::LimitExceeded`].
+///
+/// Only nesting depth is limited by default; set the other fields to what
+/// the documents you expect need.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Limits {
+    /// Arrays and objects open at once; `[[1]]` has depth 2. The parser
+    /// recurses per level, so this is what keeps deep input from
+    /// overflowing the stack.
+    pub max_depth: usize,
+    /// Bytes of input read.
+    pub max_input_bytes: usize,
+    /// Bytes of a single string or member name once unescaped.
+    pub max_string_bytes: usize,
+    /// Elements of a single array or members of a single object.
+    pub max_elements: usize,
+    /// Values in the whole document, containers included.
+    pub max_nodes: usize,
+    /// Characters of a single number as written.
+    pub max_number_length: usize,
+}
+
+impl Limits {
+    /// The most nesting the parser accepts by default.
+    pub const DEFAULT_MAX_DEPTH: usize = 512;
+
+    /// No limits at all except [`DEFAULT_MAX_DEPTH`](Self::DEFAULT_MAX_DEPTH).
+    pub fn new() -> Self {
+        Self {
+            max_depth: Self::DEFAULT_MAX_DEPTH,
+            max_input_bytes: usize::MAX,
+            max_string_bytes: usize::MAX,
+            max_elements: usize::MAX,
+            max_nodes: usize::MAX,
+            max_number_length: usize::MAX,
+        }
+    }
+
+    /// The value `limit` is set to.
+    pub fn max(&self, limit: Limit) -> usize {
+        match limit {
+            Limit::Depth => self.max_depth,
+            Limit::InputBytes => self.max_input_bytes,
+            Limit::StringBytes => self.max_string_bytes,
+            Limit::Elements => self.max_elements,
+            Limit::Nodes => self.max_nodes,
+            Limit::NumberLength => self.max_number_length,
+        }
+    }
+
+    /// Fails with an error at `at` when `value` goes past `limit`.
+    pub(crate) fn check(&self, limit: Limit, value: usize, at: impl FnOnce() -> Position) -> Result<(), JsonError> {
+        let max = self.max(limit);
+        if value > max { Err(JsonError::limit_exceeded(at(), limit, max)) } else { Ok(()) }
+    }
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Controls which extensions to RFC 8259 the parser accepts.
+///
+/// The default is strict RFC 8259 JSON; [`ParseOptions::json5`] enables
+/// everything.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct ParseOptions {
+    /// `// comment` up to the end of the line.
+    pub line_comments: bool,
+    /// `/* comment */`, possibly spanning lines.
+    pub block_comments: bool,
+    /// Unicode whitespace (NBSP, U+2028, BOM, ...) between tokens, not just
+    /// space, tab, LF and CR.
+    pub extended_whitespace: bool,
+    /// Raw control characters other than line breaks inside strings.
+    pub unescaped_control_characters: bool,
+    /// A `,` before the closing `]` or `}`.
+    pub trailing_commas: bool,
+    /// Object keys written as bare identifiers: `{port: 8080}`.
+    pub unquoted_keys: bool,
+    /// Strings delimited by `'`.
+    pub single_quotes: bool,
+    /// `Infinity`, `-Infinity` and `NaN` number literals.
+    pub special_floats: bool,
+    /// Hexadecimal integers such as `0x1F`.
+    pub hex_numbers: bool,
+    /// A leading `+` sign on numbers.
+    pub leading_plus: bool,
+    /// Numbers starting or ending with a decimal point: `.5`, `5.`.
+    pub bare_decimal_point: bool,
+    /// JSON5 string escapes: `\v`, `\0`, `\xHH`, escaped line breaks and
+    /// any other escaped character standing for itself.
+    pub extended_escapes: bool,
+    pub lone_surrogates: LoneSurrogatePolicy,
+    /// Only [`DuplicateKeyPolicy::Error`] changes what
+    /// [`JsonReader`](crate::JsonReader) and the serde deserializer report;
+    /// the other policies decide
+    /// which members end up in a [`JsonValue`](crate::JsonValue).
+    pub duplicate_keys: DuplicateKeyPolicy,
+    /// Keep numbers that do not fit `i64`/`u64` exactly as
+    /// [`JsonNumber::Raw`](crate::JsonNumber::Raw) source text, so they
+    /// serialize back unchanged, instead of rounding them to `f64`.
+    pub arbitrary_precision: bool,
+    pub limits: Limits,
+}
+
+impl ParseOptions {
+    /// Strict RFC 8259 JSON.
+    pub fn strict() -> Self {
+        Self::default()
+    }
+
+    /// Every extension needed to read JSON5 documents.
+    pub fn json5() -> Self {
+        Self {
+            line_comments: true,
+            block_comments: true,
+            extended_whitespace: true,
+            unescaped_control_characters: true,
+            trailing_commas: true,
+            unquoted_keys: true,
+            single_quotes: true,
+            special_floats: true,
+            hex_numbers: true,
+            leading_plus: true,
+            bare_decimal_point: true,
+            extended_escapes: true,
+            lone_surrogates: LoneSurrogatePolicy::Error,
+            duplicate_keys: DuplicateKeyPolicy::All,
+            arbitrary_precision: false,
+            limits: Limits::new(),
+        }
+    }
+}
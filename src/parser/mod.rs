@@ -1,11 +1,13 @@
-use crate::core::{JsonError, JsonNumber};
+use crate::core::{JsonNumber, RawNumber};
+use crate::wtf8::{Wtf8Piece, Wtf8String};
 
 pub struct NumberParser;
 
 impl NumberParser{
-    pub(crate) fn parse(s:&str) -> Result<JsonNumber, JsonError> {
+    /// `None` when `s` is not a number; callers report the error with its position.
+    pub(crate) fn parse(s:&str) -> Option<JsonNumber> {
         if s.is_empty() {
-            return Err(JsonError::InvalidNumber {line:0, col:0});
+            return None;
         }
 
         // integer detection without decimal or scientific notation
@@ -13,18 +15,76 @@ impl NumberParser{
         if !s.contains('.') && !s.contains('e') && !s.contains('E') {
             //Tries i64 first (most common case)
             if let Ok(i) = s.parse::<i64>() {
-                return Ok(JsonNumber::Integer(i));
+                return Some(JsonNumber::Integer(i));
             }
             // Try u64 for large positive numbers
-            if !s.starts_with('-') {
-                if let Ok(u) = s.parse::<u64>(){
-                    return Ok(JsonNumber::UnsignedInteger(u));
-                }
+            if !s.starts_with('-')
+                && let Ok(u) = s.parse::<u64>() {
+                return Some(JsonNumber::UnsignedInteger(u));
+            }
+        }
+        s.parse::<f64>().ok().map(JsonNumber::Float)
+    }
+
+    /// Like [`parse`](Self::parse), but numbers that do not fit `i64`/`u64`
+    /// exactly are kept as [`JsonNumber::Raw`] text instead of being rounded
+    /// to `f64`. `s` must already have passed the lexer's grammar check.
+    pub(crate) fn parse_lossless(s: &str) -> JsonNumber {
+        match Self::parse(s) {
+            Some(n @ (JsonNumber::Integer(_) | JsonNumber::UnsignedInteger(_))) => n,
+            _ => JsonNumber::Raw(RawNumber::new(Self::to_json_lexeme(s))),
+        }
+    }
+
+    /// Checks `s` against the RFC 8259 number grammar, failing with the
+    /// offset of the first character that does not fit, or `s.len()` when
+    /// it ends too soon.
+    pub(crate) fn check_lexeme(s: &str) -> Result<(), usize> {
+        let bytes = s.as_bytes();
+        let digits = |i: usize| bytes[i..].iter().take_while(|b| b.is_ascii_digit()).count();
+        let mut i = usize::from(bytes.first() == Some(&b'-'));
+        match bytes.get(i) {
+            Some(b'0') => i += 1,
+            Some(b'1'..=b'9') => i += digits(i),
+            _ => return Err(i),
+        }
+        if bytes.get(i) == Some(&b'.') {
+            i += 1;
+            match digits(i) {
+                0 => return Err(i),
+                n => i += n,
+            }
+        }
+        if let Some(b'e' | b'E') = bytes.get(i) {
+            i += 1;
+            if let Some(b'+' | b'-') = bytes.get(i) {
+                i += 1;
+            }
+            match digits(i) {
+                0 => return Err(i),
+                n => i += n,
             }
         }
-        s.parse::<f64>()
-            .map(JsonNumber::Float)
-            .map_err(|_| JsonError::InvalidNumber { line: 0, col: 0 })
+        if i == bytes.len() { Ok(()) } else { Err(i) }
+    }
+
+    // Rewrites the JSON5 forms `+1`, `.5` and `5.` as plain JSON numbers.
+    fn to_json_lexeme(s: &str) -> String {
+        let s = s.strip_prefix('+').unwrap_or(s);
+        let mut lexeme = String::with_capacity(s.len() + 2);
+        let mut chars = s.chars().peekable();
+        let mut previous = None;
+        while let Some(c) = chars.next() {
+            if c == '.' && !previous.is_some_and(|p: char| p.is_ascii_digit()) {
+                lexeme.push('0');
+            }
+            lexeme.push(c);
+            if c == '.' && !chars.peek().is_some_and(char::is_ascii_digit) {
+                lexeme.push('0');
+            }
+            previous = Some(c);
+        }
+        lexeme
     }
 }
 
@@ -66,33 +126,52 @@ impl StringEscaper {
         result
     }
 
-    pub(crate) fn unescape_char(c: char) -> Result<char, JsonError> {
+    /// Like [`escape`](Self::escape), writing lone surrogates as `\udXXX`.
+    pub(crate) fn escape_wtf8(s: &Wtf8String) -> String {
+        let mut result = String::with_capacity(s.as_bytes().len() + 8);
+        for piece in s.pieces() {
+            match piece {
+                Wtf8Piece::Text(text) => result.push_str(&Self::escape(text)),
+                Wtf8Piece::Surrogate(unit) => result.push_str(&format!("\\u{:04x}", unit)),
+            }
+        }
+        result
+    }
+
+    /// The character a single-character escape such as `\n` stands for.
+    pub(crate) fn unescape_char(c: char) -> Option<char> {
         match c {
-            '"' => Ok('"'),
-            '\\' => Ok('\\'),
-            '/' => Ok('/'),
-            'b' => Ok('\x08'),
-            'f' => Ok('\x0C'),
-            'n' => Ok('\n'),
-            'r' => Ok('\r'),
-            't' => Ok('\t'),
-            _ => Err(JsonError::InvalidEscapeSequence {line: 0 , col: 0}),
+            '"' => Some('"'),
+            '\\' => Some('\\'),
+            '/' => Some('/'),
+            'b' => Some('\x08'),
+            'f' => Some('\x0C'),
+            'n' => Some('\n'),
+            'r' => Some('\r'),
+            't' => Some('\t'),
+            _ => None,
         }
     }
 
-    pub(crate) fn parse_unicode_hex(hex:&str) -> Result<char, JsonError> {
+    /// Parses the four hex digits of a `\u` escape into a UTF-16 code unit,
+    /// which may be one half of a surrogate pair.
+    pub(crate) fn parse_unicode_hex(hex:&str) -> Option<u32> {
         if hex.len() != 4 {
-            return Err(JsonError::InvalidEscapeSequence {line: 0, col: 0});
+            return None;
         }
 
-        let code_point = u32::from_str_radix(hex, 16)
-            .map_err(|_| JsonError::InvalidEscapeSequence {line: 0, col: 0})?;
+        u32::from_str_radix(hex, 16).ok()
+    }
 
-        char::from_u32(code_point)
-            .ok_or(JsonError::InvalidEscapeSequence {line: 0, col: 0})
+    /// Joins a high and a low surrogate into the character they encode.
+    pub(crate) fn combine_surrogates(high: u32, low: u32) -> Option<char> {
+        if !(0xD800..0xDC00).contains(&high) || !(0xDC00..0xE000).contains(&low) {
+            return None;
+        }
+        char::from_u32(0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00))
     }
 
-    fn needs_escaping(s: &str) -> bool {
+    pub(crate) fn needs_escaping(s: &str) -> bool {
         s.chars().any(|c| match c{
             '"' | '\\' | '\u{0008}' |  '\u{000C}' | '\n' | '\r' | '\t' => true,
             c if c.is_control() && (c as u32) <= 0x1F => true,
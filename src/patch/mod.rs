@@ -0,0 +1,337 @@
+use std::fmt;
+use std::fmt::Formatter;
+use crate::core::{JsonError, JsonValue};
+use crate::driver::deserialize;
+use crate::pointer::{parse_index, JsonPointer, PointerError};
+use crate::value::{distinct_members, values_equal};
+
+/// A single RFC 6902 operation.
+#[derive(Debug, PartialEq, Clone)]
+pub enum PatchOperation {
+    Add { path: JsonPointer, value: JsonValue },
+    Remove { path: JsonPointer },
+    Replace { path: JsonPointer, value: JsonValue },
+    Move { from: JsonPointer, path: JsonPointer },
+    Copy { from: JsonPointer, path: JsonPointer },
+    Test { path: JsonPointer, value: JsonValue },
+}
+
+/// An RFC 6902 JSON Patch document: an ordered list of operations.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct JsonPatch {
+    pub operations: Vec<PatchOperation>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[non_exhaustive]
+pub enum PatchError {
+    /// The patch text is not valid JSON.
+    Parse(JsonError),
+    /// The patch document is not a well-formed list of operations.
+    InvalidOperation { index: usize, reason: &'static str },
+    /// An operation's `path` or `from` could not be applied.
+    Pointer { index: usize, error: PointerError },
+    /// A `test` operation did not match.
+    TestFailed { index: usize, path: String },
+}
+
+impl fmt::Display for PatchError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            PatchError::Parse(e) => write!(f, "Invalid patch document: {}", e),
+            PatchError::InvalidOperation { index, reason } => write!(f, "Invalid operation #{}: {}", index, reason),
+            PatchError::Pointer { index, error } => write!(f, "Operation #{} failed: {}", index, error),
+            PatchError::TestFailed { index, path } => write!(f, "Test operation #{} failed at '{}'", index, path),
+        }
+    }
+}
+
+impl std::error::Error for PatchError {}
+
+impl From<JsonError> for PatchError {
+    fn from(e: JsonError) -> Self {
+        PatchError::Parse(e)
+    }
+}
+
+impl JsonPatch {
+    /// Parses patch text such as `[{"op":"remove","path":"/a"}]`.
+    pub fn parse(input: &str) -> Result<Self, PatchError> {
+        Self::from_value(&deserialize(input)?)
+    }
+
+    pub fn from_value(value: &JsonValue) -> Result<Self, PatchError> {
+        let Some(items) = value.as_array() else {
+            return Err(PatchError::InvalidOperation { index: 0, reason: "patch must be an array" });
+        };
+        let operations = items
+            .iter()
+            .enumerate()
+            .map(|(index, item)| parse_operation(index, item))
+            .collect::<Result<_, _>>()?;
+        Ok(Self { operations })
+    }
+
+    pub fn to_value(&self) -> JsonValue {
+        JsonValue::Array(self.operations.iter().map(PatchOperation::to_value).collect())
+    }
+
+    /// Applies every operation in order. The document is only modified if
+    /// all operations succeed; otherwise it is left untouched.
+    pub fn apply(&self, document: &mut JsonValue) -> Result<(), PatchError> {
+        let mut working = document.clone();
+        for (index, operation) in self.operations.iter().enumerate() {
+            operation.apply(&mut working, index)?;
+        }
+        *document = working;
+        Ok(())
+    }
+
+    /// Computes a patch that turns `from` into `to`.
+    pub fn diff(from: &JsonValue, to: &JsonValue) -> Self {
+        let mut patch = Self::default();
+        diff_values(from, to, &mut JsonPointer::root(), &mut patch.operations);
+        patch
+    }
+}
+
+impl PatchOperation {
+    fn apply(&self, document: &mut JsonValue, index: usize) -> Result<(), PatchError> {
+        let pointer_error = |error| PatchError::Pointer { index, error };
+        match self {
+            PatchOperation::Add { path, value } => add(document, path, value.clone()).map_err(pointer_error),
+            PatchOperation::Remove { path } => document.remove_at(path).map(drop).map_err(pointer_error),
+            PatchOperation::Replace { path, value } => {
+                *document.resolve_mut(path).map_err(pointer_error)? = value.clone();
+                Ok(())
+            }
+            PatchOperation::Move { from, path } => {
+                if from == path {
+                    return document.resolve(from).map(drop).map_err(pointer_error);
+                }
+                if from.is_prefix_of(path) {
+                    return Err(PatchError::InvalidOperation { index, reason: "cannot move a value into one of its children" });
+                }
+                let value = document.remove_at(from).map_err(pointer_error)?;
+                add(document, path, value).map_err(pointer_error)
+            }
+            PatchOperation::Copy { from, path } => {
+                let value = document.resolve(from).map_err(pointer_error)?.clone();
+                add(document, path, value).map_err(pointer_error)
+            }
+            PatchOperation::Test { path, value } => {
+                if values_equal(document.resolve(path).map_err(pointer_error)?, value) {
+                    Ok(())
+                } else {
+                    Err(PatchError::TestFailed { index, path: path.to_string() })
+                }
+            }
+        }
+    }
+
+    fn to_value(&self) -> JsonValue {
+        let op = |name: &str| ("op".to_string(), JsonValue::String(name.to_string()));
+        let pointer = |key: &str, p: &JsonPointer| (key.to_string(), JsonValue::String(p.to_string()));
+        let value = |v: &JsonValue| ("value".to_string(), v.clone());
+        JsonValue::Object(match self {
+            PatchOperation::Add { path, value: v } => vec![op("add"), pointer("path", path), value(v)],
+            PatchOperation::Remove { path } => vec![op("remove"), pointer("path", path)],
+            PatchOperation::Replace { path, value: v } => vec![op("replace"), pointer("path", path), value(v)],
+            PatchOperation::Move { from, path } => vec![op("move"), pointer("from", from), pointer("path", path)],
+            PatchOperation::Copy { from, path } => vec![op("copy"), pointer("from", from), pointer("path", path)],
+            PatchOperation::Test { path, value: v } => vec![op("test"), pointer("path", path), value(v)],
+        })
+    }
+}
+
+fn parse_operation(index: usize, item: &JsonValue) -> Result<PatchOperation, PatchError> {
+    let invalid = |reason| PatchError::InvalidOperation { index, reason };
+    if !item.is_object() {
+        return Err(invalid("operation must be an object"));
+    }
+    let pointer = |key: &str, missing| {
+        let text = item.get(key).and_then(JsonValue::as_str).ok_or_else(|| invalid(missing))?;
+        JsonPointer::parse(text).map_err(|error| PatchError::Pointer { index, error })
+    };
+    let value = || item.get("value").cloned().ok_or_else(|| invalid("missing 'value'"));
+
+    let path = pointer("path", "missing or non-string 'path'")?;
+    match item.get("op").and_then(JsonValue::as_str) {
+        Some("add") => Ok(PatchOperation::Add { path, value: value()? }),
+        Some("remove") => Ok(PatchOperation::Remove { path }),
+        Some("replace") => Ok(PatchOperation::Replace { path, value: value()? }),
+        Some("move") => Ok(PatchOperation::Move { from: pointer("from", "missing or non-string 'from'")?, path }),
+        Some("copy") => Ok(PatchOperation::Copy { from: pointer("from", "missing or non-string 'from'")?, path }),
+        Some("test") => Ok(PatchOperation::Test { path, value: value()? }),
+        Some(_) => Err(invalid("unknown 'op'")),
+        None => Err(invalid("missing or non-string 'op'")),
+    }
+}
+
+// RFC 6902 "add": members are created or replaced, array elements are inserted.
+fn add(document: &mut JsonValue, path: &JsonPointer, value: JsonValue) -> Result<(), PointerError> {
+    let Some((parent, last)) = path.split_last() else {
+        *document = value;
+        return Ok(());
+    };
+    match document.resolve_mut(&parent)? {
+        JsonValue::Object(pairs) => {
+            match pairs.iter_mut().rev().find(|(k, _)| k == last) {
+                Some((_, slot)) => *slot = value,
+                None => pairs.push((last.to_string(), value)),
+            }
+            Ok(())
+        }
+        JsonValue::Array(items) => {
+            let index = parse_index(last, items.len(), true, path)?;
+            if index > items.len() {
+                return Err(PointerError::NotFound { pointer: path.to_string() });
+            }
+            items.insert(index, value);
+            Ok(())
+        }
+        _ => Err(PointerError::NotFound { pointer: path.to_string() }),
+    }
+}
+
+fn diff_values(from: &JsonValue, to: &JsonValue, path: &mut JsonPointer, ops: &mut Vec<PatchOperation>) {
+    if values_equal(from, to) {
+        return;
+    }
+    match (from, to) {
+        (JsonValue::Object(old), JsonValue::Object(new)) => {
+            for (key, old_value) in distinct_members(old) {
+                path.push(key.as_str());
+                match to.get(key) {
+                    Some(new_value) => diff_values(old_value, new_value, path, ops),
+                    None => ops.push(PatchOperation::Remove { path: path.clone() }),
+                }
+                path.pop();
+            }
+            for (key, new_value) in distinct_members(new) {
+                if from.get(key).is_none() {
+                    path.push(key.as_str());
+                    ops.push(PatchOperation::Add { path: path.clone(), value: new_value.clone() });
+                    path.pop();
+                }
+            }
+        }
+        (JsonValue::Array(old), JsonValue::Array(new)) => diff_arrays(old, new, path, ops),
+        _ => ops.push(PatchOperation::Replace { path: path.clone(), value: to.clone() }),
+    }
+}
+
+// Beyond this many insertions and removals an array diff stops looking for
+// the shortest edit script and pairs the differing elements up in order.
+const MAX_ARRAY_EDITS: usize = 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Edit {
+    Keep,
+    Remove,
+    Add,
+}
+
+// Trims the common prefix and suffix, then walks the shortest edit script
+// between the middles. A run of removals next to a run of additions is an
+// aligned change: those elements are diffed pairwise, the rest removed or added.
+fn diff_arrays(old: &[JsonValue], new: &[JsonValue], path: &mut JsonPointer, ops: &mut Vec<PatchOperation>) {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| values_equal(a, b)).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| values_equal(a, b))
+        .count();
+    let old = &old[prefix..old.len() - suffix];
+    let new = &new[prefix..new.len() - suffix];
+    let edits = shortest_edits(old, new).unwrap_or_else(|| {
+        let removes = std::iter::repeat_n(Edit::Remove, old.len());
+        removes.chain(std::iter::repeat_n(Edit::Add, new.len())).collect()
+    });
+
+    // `at` indexes the array as patched so far, since operations apply in order.
+    let (mut at, mut i, mut j) = (prefix, 0, 0);
+    let mut edits = edits.into_iter().peekable();
+    while let Some(edit) = edits.next() {
+        if edit == Edit::Keep {
+            (at, i, j) = (at + 1, i + 1, j + 1);
+            continue;
+        }
+        let (mut removed, mut added) = (0, 0);
+        for edit in std::iter::once(edit).chain(std::iter::from_fn(|| edits.next_if(|&e| e != Edit::Keep))) {
+            match edit {
+                Edit::Remove => removed += 1,
+                _ => added += 1,
+            }
+        }
+        let paired = removed.min(added);
+        for k in 0..paired {
+            path.push((at + k).to_string());
+            diff_values(&old[i + k], &new[j + k], path, ops);
+            path.pop();
+        }
+        at += paired;
+        path.push(at.to_string());
+        for _ in paired..removed {
+            ops.push(PatchOperation::Remove { path: path.clone() });
+        }
+        path.pop();
+        for value in &new[j + paired..j + added] {
+            path.push(at.to_string());
+            ops.push(PatchOperation::Add { path: path.clone(), value: value.clone() });
+            path.pop();
+            at += 1;
+        }
+        (i, j) = (i + removed, j + added);
+    }
+}
+
+// Myers' O(ND) diff: the fewest removals and additions turning `old` into
+// `new`, or `None` past `MAX_ARRAY_EDITS`.
+fn shortest_edits(old: &[JsonValue], new: &[JsonValue]) -> Option<Vec<Edit>> {
+    let (n, m) = (old.len() as isize, new.len() as isize);
+    let max = (old.len() + new.len()).min(MAX_ARRAY_EDITS) as isize;
+    let offset = max + 1;
+    // The furthest `x` reached on each diagonal `k = x - y`, at `k + offset`.
+    let mut v = vec![0isize; 2 * offset as usize + 1];
+    // Diagonals -d..=d of `v` as they stood before round `d`.
+    let mut trace = Vec::new();
+    for d in 0..=max {
+        trace.push(v[(offset - d) as usize..=(offset + d) as usize].to_vec());
+        for k in (-d..=d).step_by(2) {
+            let slot = (k + offset) as usize;
+            let mut x = if k == -d || (k != d && v[slot - 1] < v[slot + 1]) { v[slot + 1] } else { v[slot - 1] + 1 };
+            let mut y = x - k;
+            while x < n && y < m && values_equal(&old[x as usize], &new[y as usize]) {
+                (x, y) = (x + 1, y + 1);
+            }
+            v[slot] = x;
+            if x >= n && y >= m {
+                return Some(backtrack(&trace, n, m));
+            }
+        }
+    }
+    None
+}
+
+fn backtrack(trace: &[Vec<isize>], mut x: isize, mut y: isize) -> Vec<Edit> {
+    let mut edits = Vec::new();
+    for (d, v) in trace.iter().enumerate().skip(1).rev() {
+        let d = d as isize;
+        let furthest = |k: isize| v[(k + d) as usize];
+        let k = x - y;
+        let prev_k = if k == -d || (k != d && furthest(k - 1) < furthest(k + 1)) { k + 1 } else { k - 1 };
+        let (prev_x, prev_y) = (furthest(prev_k), furthest(prev_k) - prev_k);
+        while x > prev_x && y > prev_y {
+            edits.push(Edit::Keep);
+            (x, y) = (x - 1, y - 1);
+        }
+        edits.push(if x == prev_x { Edit::Add } else { Edit::Remove });
+        (x, y) = (prev_x, prev_y);
+    }
+    edits.extend((0..x).map(|_| Edit::Keep));
+    edits.reverse();
+    edits
+}
+
@@ -0,0 +1,233 @@
+use std::fmt;
+use std::fmt::Formatter;
+use crate::core::JsonValue;
+
+/// A parsed RFC 6901 JSON Pointer such as `/config/security/keys/0`.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct JsonPointer {
+    tokens: Vec<String>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[non_exhaustive]
+pub enum PointerError {
+    /// Pointer is non-empty but does not start with `/`, or has a `~` not followed by `0`/`1`.
+    Malformed { pointer: String },
+    /// An array was addressed with something other than a canonical index.
+    InvalidIndex { pointer: String, token: String },
+    /// Nothing exists at the pointer.
+    NotFound { pointer: String },
+    /// The whole document cannot be removed.
+    RootNotRemovable,
+}
+
+impl fmt::Display for PointerError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            PointerError::Malformed { pointer } => write!(f, "Malformed JSON pointer '{}'", pointer),
+            PointerError::InvalidIndex { pointer, token } => {
+                write!(f, "Invalid array index '{}' in '{}'", token, pointer)
+            }
+            PointerError::NotFound { pointer } => write!(f, "No value at '{}'", pointer),
+            PointerError::RootNotRemovable => write!(f, "Cannot remove the document root"),
+        }
+    }
+}
+
+impl std::error::Error for PointerError {}
+
+impl JsonPointer {
+    /// Parses pointer text, decoding `~1` to `/` and `~0` to `~`.
+    pub fn parse(pointer: &str) -> Result<Self, PointerError> {
+        if pointer.is_empty() {
+            return Ok(Self::default());
+        }
+        let Some(rest) = pointer.strip_prefix('/') else {
+            return Err(PointerError::Malformed { pointer: pointer.to_string() });
+        };
+        let tokens = rest
+            .split('/')
+            .map(|t| unescape_token(t).ok_or_else(|| PointerError::Malformed { pointer: pointer.to_string() }))
+            .collect::<Result<_, _>>()?;
+        Ok(Self { tokens })
+    }
+
+    pub fn root() -> Self {
+        Self::default()
+    }
+
+    pub fn tokens(&self) -> &[String] {
+        &self.tokens
+    }
+
+    pub fn is_root(&self) -> bool {
+        self.tokens.is_empty()
+    }
+
+    pub fn push(&mut self, token: impl Into<String>) {
+        self.tokens.push(token.into());
+    }
+
+    pub fn pop(&mut self) -> Option<String> {
+        self.tokens.pop()
+    }
+
+    /// Splits off the last token, returning the parent pointer and the token.
+    pub fn split_last(&self) -> Option<(JsonPointer, &str)> {
+        let (last, parent) = self.tokens.split_last()?;
+        Some((JsonPointer { tokens: parent.to_vec() }, last))
+    }
+
+    /// True when `self` is a proper prefix of `other`.
+    pub fn is_prefix_of(&self, other: &JsonPointer) -> bool {
+        self.tokens.len() < other.tokens.len() && other.tokens.starts_with(&self.tokens)
+    }
+}
+
+impl fmt::Display for JsonPointer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for token in &self.tokens {
+            write!(f, "/{}", token.replace('~', "~0").replace('/', "~1"))?;
+        }
+        Ok(())
+    }
+}
+
+fn unescape_token(token: &str) -> Option<String> {
+    let mut out = String::with_capacity(token.len());
+    let mut chars = token.chars();
+    while let Some(c) = chars.next() {
+        if c == '~' {
+            match chars.next() {
+                Some('0') => out.push('~'),
+                Some('1') => out.push('/'),
+                _ => return None,
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    Some(out)
+}
+
+/// Parses an array reference token. Leading zeros are rejected and `-`
+/// is only meaningful where the caller allows appending.
+pub(crate) fn parse_index(token: &str, len: usize, allow_end: bool, pointer: &JsonPointer) -> Result<usize, PointerError> {
+    if token == "-" && allow_end {
+        return Ok(len);
+    }
+    let canonical = !token.is_empty()
+        && token.bytes().all(|b| b.is_ascii_digit())
+        && (token == "0" || !token.starts_with('0'));
+    match token.parse::<usize>() {
+        Ok(i) if canonical => Ok(i),
+        _ => Err(PointerError::InvalidIndex { pointer: pointer.to_string(), token: token.to_string() }),
+    }
+}
+
+pub(crate) fn not_found(pointer: &JsonPointer) -> PointerError {
+    PointerError::NotFound { pointer: pointer.to_string() }
+}
+
+impl JsonValue {
+    /// Resolves an RFC 6901 pointer against this value.
+    pub fn pointer(&self, pointer: &str) -> Result<&JsonValue, PointerError> {
+        self.resolve(&JsonPointer::parse(pointer)?)
+    }
+
+    pub fn pointer_mut(&mut self, pointer: &str) -> Result<&mut JsonValue, PointerError> {
+        self.resolve_mut(&JsonPointer::parse(pointer)?)
+    }
+
+    /// Stores `value` at `pointer`, returning whatever it replaced.
+    ///
+    /// Object members are created or overwritten. For arrays, an existing
+    /// index is overwritten and `-` (or an index equal to the length) appends.
+    pub fn pointer_set(&mut self, pointer: &str, value: JsonValue) -> Result<Option<JsonValue>, PointerError> {
+        let pointer = JsonPointer::parse(pointer)?;
+        let Some((parent, last)) = pointer.split_last() else {
+            return Ok(Some(std::mem::replace(self, value)));
+        };
+        match self.resolve_mut(&parent)? {
+            JsonValue::Object(pairs) => {
+                if let Some((_, slot)) = pairs.iter_mut().rev().find(|(k, _)| k == last) {
+                    return Ok(Some(std::mem::replace(slot, value)));
+                }
+                pairs.push((last.to_string(), value));
+                Ok(None)
+            }
+            JsonValue::Array(items) => {
+                let index = parse_index(last, items.len(), true, &pointer)?;
+                if index < items.len() {
+                    Ok(Some(std::mem::replace(&mut items[index], value)))
+                } else if index == items.len() {
+                    items.push(value);
+                    Ok(None)
+                } else {
+                    Err(not_found(&pointer))
+                }
+            }
+            _ => Err(not_found(&pointer)),
+        }
+    }
+
+    /// Removes and returns the value at `pointer`. Array elements after it shift left;
+    /// a repeated member name is removed with all its earlier occurrences.
+    pub fn pointer_remove(&mut self, pointer: &str) -> Result<JsonValue, PointerError> {
+        self.remove_at(&JsonPointer::parse(pointer)?)
+    }
+
+    pub(crate) fn resolve(&self, pointer: &JsonPointer) -> Result<&JsonValue, PointerError> {
+        let mut current = self;
+        for token in pointer.tokens() {
+            current = match current {
+                JsonValue::Object(_) => current.get(token),
+                JsonValue::Array(items) => items.get(parse_index(token, items.len(), false, pointer)?),
+                _ => None,
+            }
+            .ok_or_else(|| not_found(pointer))?;
+        }
+        Ok(current)
+    }
+
+    pub(crate) fn resolve_mut(&mut self, pointer: &JsonPointer) -> Result<&mut JsonValue, PointerError> {
+        let mut current = self;
+        for token in pointer.tokens() {
+            current = match current {
+                JsonValue::Object(_) => current.get_mut(token),
+                JsonValue::Array(items) => {
+                    let index = parse_index(token, items.len(), false, pointer)?;
+                    items.get_mut(index)
+                }
+                _ => None,
+            }
+            .ok_or_else(|| not_found(pointer))?;
+        }
+        Ok(current)
+    }
+
+    pub(crate) fn remove_at(&mut self, pointer: &JsonPointer) -> Result<JsonValue, PointerError> {
+        let Some((parent, last)) = pointer.split_last() else {
+            return Err(PointerError::RootNotRemovable);
+        };
+        match self.resolve_mut(&parent)? {
+            JsonValue::Object(pairs) => {
+                let position = pairs.iter().rposition(|(k, _)| k == last).ok_or_else(|| not_found(pointer))?;
+                // Earlier members under a repeated key go too, rather than
+                // coming back into view.
+                let value = pairs.remove(position).1;
+                pairs.retain(|(k, _)| k != last);
+                Ok(value)
+            }
+            JsonValue::Array(items) => {
+                let index = parse_index(last, items.len(), false, pointer)?;
+                if index < items.len() {
+                    Ok(items.remove(index))
+                } else {
+                    Err(not_found(pointer))
+                }
+            }
+            _ => Err(not_found(pointer)),
+        }
+    }
+}
@@ -0,0 +1,324 @@
+use std::io::Read;
+use crate::core::{JsonError, JsonValue, Limit, Token};
+use crate::engine::{
+    identifier_number, lone_surrogate_in_name, member_expectation, member_name, Lexer, PathStep, PathTracker, SeenKeys,
+    Slot, EXPECT_COLON, EXPECT_COMMA, EXPECT_END_ARRAY, EXPECT_END_OBJECT, EXPECT_EOF, EXPECT_VALUE,
+};
+use crate::options::ParseOptions;
+use crate::source::{ReadSource, Source, StrSource};
+
+/// A parser that reports every syntax error in a document instead of stopping
+/// at the first one.
+///
+/// After an error it skips ahead to the next `,`, `]` or `}` of the container
+/// being read and carries on from there. A missing comma or colon is reported
+/// and assumed, and a closing bracket of the wrong kind is taken for the right
+/// one. Values that cannot be read become `null`. Only the
+/// first error found at any one position is kept, so a single mistake does not
+/// bury the report in follow-on errors. Going past a limit ends the parse;
+/// a duplicate key under [`DuplicateKeyPolicy::Error`](crate::DuplicateKeyPolicy::Error)
+/// is reported and the first value kept.
+pub(crate) struct RecoveringParser<S> {
+    lexer: Lexer<S>,
+    current_token: Token,
+    path: PathTracker,
+    errors: Vec<JsonError>,
+    depth: usize,
+    nodes: usize,
+    // Set once the end of input has been reported, or the input can no
+    // longer be read; every token from then on is `Eof`.
+    halted: bool,
+}
+
+impl<'a> RecoveringParser<StrSource<'a>> {
+    pub(crate) fn new(input: &'a str, options: ParseOptions) -> Self {
+        Self::with_lexer(Lexer::new(input, options))
+    }
+}
+
+impl<R: Read> RecoveringParser<ReadSource<R>> {
+    pub(crate) fn from_reader(reader: R, options: ParseOptions) -> Self {
+        Self::with_lexer(Lexer::from_reader(reader, options))
+    }
+}
+
+impl<S: Source> RecoveringParser<S> {
+    pub(crate) fn with_lexer(mut lexer: Lexer<S>) -> Self {
+        lexer.set_recovering();
+        let mut parser = Self { lexer, current_token: Token::Eof, path: PathTracker::default(), errors: Vec::new(), depth: 0, nodes: 0, halted: false };
+        parser.advance();
+        parser
+    }
+
+    /// Reads the whole document, returning as much of it as could be made out
+    /// along with the errors met, in the order they appear. The value is
+    /// `null` when not even the start of one was found.
+    pub(crate) fn parse(mut self) -> (JsonValue, Vec<JsonError>) {
+        let value = self.parse_value(&[EXPECT_VALUE]).unwrap_or(JsonValue::Null);
+        if !matches!(self.current_token, Token::Eof) {
+            self.report(&[EXPECT_EOF]);
+        }
+        (value, self.errors)
+    }
+
+    fn advance(&mut self) {
+        if self.halted {
+            return;
+        }
+        let next = self.lexer.next_token();
+        for error in self.lexer.take_recovered() {
+            self.record(error);
+        }
+        self.current_token = next.unwrap_or_else(|e| {
+            self.record(e);
+            Token::Eof
+        });
+    }
+
+    fn record(&mut self, error: JsonError) {
+        if self.halted || self.errors.last().is_some_and(|last| last.offset() == error.offset()) {
+            return;
+        }
+        // An unterminated string or comment ends the input as surely as EOF does.
+        self.halted = !matches!(
+            error,
+            JsonError::InvalidToken { .. }
+                | JsonError::InvalidNumber { .. }
+                | JsonError::InvalidEscapeSequence { .. }
+                | JsonError::DuplicateKey { .. }
+        );
+        if self.halted {
+            self.current_token = Token::Eof;
+        }
+        self.errors.push(error.with_path(self.path.normalized()));
+    }
+
+    // Reports the current token as not being one of `expected`.
+    fn report(&mut self, expected: &[&'static str]) {
+        let at = self.lexer.token_start();
+        let error = match &self.current_token {
+            Token::Eof => JsonError::unexpected_eof(at, expected),
+            token => JsonError::invalid_token(at, token.describe(), expected),
+        };
+        self.record(error);
+    }
+
+    // Counts the value at the current token against the limits, as
+    // `Parser::start_value` does; `false` once one is exceeded.
+    fn start_value(&mut self, nested: bool) -> bool {
+        let limits = self.lexer.options().limits;
+        self.nodes += 1;
+        self.depth += usize::from(nested);
+        let at = || self.lexer.token_start();
+        let checked = limits.check(Limit::Nodes, self.nodes, at).and_then(|()| limits.check(Limit::Depth, self.depth, at));
+        checked.map_err(|e| self.record(e)).is_ok()
+    }
+
+    fn check_elements(&mut self, len: usize) -> bool {
+        let checked = self.lexer.options().limits.check(Limit::Elements, len, || self.lexer.token_start());
+        checked.map_err(|e| self.record(e)).is_ok()
+    }
+
+    // Skips to the next `,`, `]` or `}` outside any container opened on the way.
+    fn skip_to_sync(&mut self) {
+        let mut depth = 0usize;
+        loop {
+            match self.current_token {
+                Token::Eof => return,
+                Token::Comma | Token::SquareRight | Token::CurlyRight if depth == 0 => return,
+                Token::SquareLeft | Token::CurlyLeft => depth += 1,
+                Token::SquareRight | Token::CurlyRight => depth -= 1,
+                _ => {}
+            }
+            self.advance();
+        }
+    }
+
+    // `None` when the current token ends the surrounding container instead,
+    // in which case nothing is consumed.
+    fn parse_value(&mut self, expected: &[&'static str]) -> Option<JsonValue> {
+        let value = match &self.current_token {
+            Token::Null => JsonValue::Null,
+            Token::True => JsonValue::Bool(true),
+            Token::False => JsonValue::Bool(false),
+            Token::Number(n) => JsonValue::Number(n.clone()),
+            Token::String(s) => JsonValue::String(s.clone()),
+            Token::Wtf8(s) => JsonValue::Wtf8(s.clone()),
+            Token::Identifier(name) => match identifier_number(name, self.lexer.options()) {
+                Some(value) => JsonValue::Number(value),
+                None => return Some(self.skip_value(expected)),
+            },
+            Token::SquareLeft | Token::CurlyLeft => {
+                let value = if !self.start_value(true) {
+                    JsonValue::Null
+                } else if matches!(self.current_token, Token::SquareLeft) {
+                    self.parse_array()
+                } else {
+                    self.parse_object()
+                };
+                self.depth -= 1;
+                return Some(value);
+            }
+            Token::Comma | Token::SquareRight | Token::CurlyRight | Token::Eof => {
+                self.report(expected);
+                return None;
+            }
+            _ => return Some(self.skip_value(expected)),
+        };
+        if !self.start_value(false) {
+            return None;
+        }
+        self.path.leave();
+        self.advance();
+        Some(value)
+    }
+
+    // Passes over something that should have been a value, standing `null` in for it.
+    fn skip_value(&mut self, expected: &[&'static str]) -> JsonValue {
+        self.report(expected);
+        self.skip_to_sync();
+        JsonValue::Null
+    }
+
+    fn parse_array(&mut self) -> JsonValue {
+        self.path.enter(PathStep::Index(0));
+        self.advance(); // consume '['
+        let mut elements = Vec::new();
+        let mut closable = true;
+        let mut expected: &[&'static str] = &[EXPECT_VALUE, EXPECT_END_ARRAY];
+        loop {
+            if closable && matches!(self.current_token, Token::SquareRight) {
+                self.path.exit();
+                self.path.leave();
+                self.advance();
+                return JsonValue::Array(elements);
+            }
+            if !self.check_elements(elements.len() + 1) {
+                self.path.exit();
+                return JsonValue::Array(elements);
+            }
+            if let Some(value) = self.parse_value(expected) {
+                elements.push(value);
+            }
+            self.path.exit();
+
+            loop {
+                match self.current_token {
+                    Token::Comma => {
+                        self.path.enter(PathStep::Index(elements.len()));
+                        self.advance();
+                        break;
+                    }
+                    Token::SquareRight => {
+                        self.path.leave();
+                        self.advance();
+                        return JsonValue::Array(elements);
+                    }
+                    Token::CurlyRight | Token::Eof => {
+                        self.report(&[EXPECT_COMMA, EXPECT_END_ARRAY]);
+                        self.path.leave();
+                        self.advance();
+                        return JsonValue::Array(elements);
+                    }
+                    ref token if starts_value(token) => {
+                        self.report(&[EXPECT_COMMA, EXPECT_END_ARRAY]);
+                        self.path.enter(PathStep::Index(elements.len()));
+                        break;
+                    }
+                    _ => {
+                        self.report(&[EXPECT_COMMA, EXPECT_END_ARRAY]);
+                        self.skip_to_sync();
+                    }
+                }
+            }
+            closable = self.lexer.options().trailing_commas;
+            expected = if closable { &[EXPECT_VALUE, EXPECT_END_ARRAY] } else { &[EXPECT_VALUE] };
+        }
+    }
+
+    fn parse_object(&mut self) -> JsonValue {
+        self.advance(); // consume '{'
+        let mut pairs = Vec::new();
+        let first = [member_expectation(self.lexer.options()), EXPECT_END_OBJECT];
+        let mut seen = SeenKeys::new(self.lexer.options().duplicate_keys);
+        let mut closable = true;
+        loop {
+            if closable && matches!(self.current_token, Token::CurlyRight) {
+                self.path.leave();
+                self.advance();
+                return JsonValue::Object(pairs);
+            }
+            match member_name(&self.current_token, self.lexer.options()).map(str::to_string) {
+                Some(_) if !self.check_elements(pairs.len() + 1) => return JsonValue::Object(pairs),
+                Some(key) => {
+                    let slot = seen.insert(&key, &self.current_token, || self.lexer.token_start(), pairs.len());
+                    let slot = slot.unwrap_or_else(|e| {
+                        self.record(e);
+                        Slot::Drop
+                    });
+                    self.path.enter(PathStep::Key(key));
+                    self.advance();
+                    if matches!(self.current_token, Token::Colon) {
+                        self.advance();
+                    } else {
+                        self.report(&[EXPECT_COLON]);
+                    }
+                    let value = self.parse_value(&[EXPECT_VALUE]).unwrap_or(JsonValue::Null);
+                    let Some(PathStep::Key(key)) = self.path.exit() else {
+                        unreachable!("object members are entered by key");
+                    };
+                    match slot {
+                        Slot::Push => pairs.push((key, value)),
+                        Slot::Replace(index) => pairs[index].1 = value,
+                        Slot::Drop => {}
+                    }
+                }
+                None => {
+                    match lone_surrogate_in_name(self.lexer.token_start(), &self.current_token) {
+                        Some(error) => self.record(error),
+                        None => self.report(if closable { &first[..] } else { &first[..1] }),
+                    }
+                    self.skip_to_sync();
+                }
+            }
+
+            loop {
+                match self.current_token {
+                    Token::Comma => {
+                        self.advance();
+                        break;
+                    }
+                    Token::CurlyRight => {
+                        self.path.leave();
+                        self.advance();
+                        return JsonValue::Object(pairs);
+                    }
+                    Token::SquareRight | Token::Eof => {
+                        self.report(&[EXPECT_COMMA, EXPECT_END_OBJECT]);
+                        self.path.leave();
+                        self.advance();
+                        return JsonValue::Object(pairs);
+                    }
+                    ref token if member_name(token, self.lexer.options()).is_some() => {
+                        self.report(&[EXPECT_COMMA, EXPECT_END_OBJECT]);
+                        break;
+                    }
+                    _ => {
+                        self.report(&[EXPECT_COMMA, EXPECT_END_OBJECT]);
+                        self.skip_to_sync();
+                    }
+                }
+            }
+            closable = self.lexer.options().trailing_commas;
+        }
+    }
+}
+
+// Tokens read as the start of a value when a comma seems to be missing.
+fn starts_value(token: &Token) -> bool {
+    matches!(
+        token,
+        Token::Null | Token::True | Token::False | Token::Number(_) | Token::String(_) | Token::Wtf8(_)
+            | Token::Identifier(_) | Token::SquareLeft | Token::CurlyLeft
+    )
+}
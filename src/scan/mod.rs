@@ -0,0 +1,103 @@
+use crate::core::Position;
+
+// The lexer's fast paths test eight bytes at a time as one little-endian
+// `u64` (SWAR), then finish the last few bytes one by one.
+
+const ONES: u64 = 0x0101_0101_0101_0101;
+const HIGHS: u64 = 0x8080_8080_8080_8080;
+
+fn words(bytes: &[u8]) -> impl Iterator<Item = u64> + '_ {
+    bytes.chunks_exact(8).map(|chunk| u64::from_le_bytes(chunk.try_into().unwrap_or_default()))
+}
+
+// The high bit of each byte of `word` below `n`, for `n <= 0x80`. Only the
+// lowest flagged byte is exact: a borrow may flag the bytes above it too.
+fn below(word: u64, n: u8) -> u64 {
+    word.wrapping_sub(ONES * u64::from(n)) & !word & HIGHS
+}
+
+// The high bit of each byte of `word` equal to `byte`, lowest exact as above.
+fn equal(word: u64, byte: u8) -> u64 {
+    below(word ^ (ONES * u64::from(byte)), 1)
+}
+
+/// How many bytes from the start of `bytes` a string quoted with `quote`
+/// can take as they are: up to the closing quote, a backslash or a control
+/// character.
+pub(crate) fn string_run(bytes: &[u8], quote: u8) -> usize {
+    let mut run = 0;
+    for word in words(bytes) {
+        let stops = equal(word, quote) | equal(word, b'\\') | below(word, 0x20);
+        if stops != 0 {
+            return run + stops.trailing_zeros() as usize / 8;
+        }
+        run += 8;
+    }
+    run + bytes[run..].iter().take_while(|&&b| b != quote && b != b'\\' && b >= 0x20).count()
+}
+
+/// The nearest offset at or before `end` that does not fall inside a
+/// character, where a character cut off at the end of `bytes` counts as
+/// not having started yet.
+pub(crate) fn char_boundary(bytes: &[u8], mut end: usize) -> usize {
+    while end > 0 && bytes.get(end).is_some_and(|b| b & 0xC0 == 0x80) {
+        end -= 1;
+    }
+    if end == bytes.len() {
+        let lead = (end.saturating_sub(4)..end).rev().find(|&i| bytes[i] & 0xC0 != 0x80);
+        if let Some(lead) = lead {
+            let width = match bytes[lead] {
+                0xC0..=0xDF => 2,
+                0xE0..=0xEF => 3,
+                0xF0..=0xF7 => 4,
+                _ => 1,
+            };
+            if lead + width > end {
+                end = lead;
+            }
+        }
+    }
+    end
+}
+
+/// How many of the leading bytes are RFC 8259 whitespace.
+pub(crate) fn whitespace_run(bytes: &[u8]) -> usize {
+    // Indentation comes in long runs of spaces.
+    let mut run = 0;
+    for word in words(bytes) {
+        if word != ONES * u64::from(b' ') {
+            break;
+        }
+        run += 8;
+    }
+    run + bytes[run..].iter().take_while(|b| matches!(b, b' ' | b'\t' | b'\n' | b'\r')).count()
+}
+
+/// How many of the leading bytes are ASCII digits.
+pub(crate) fn digit_run(bytes: &[u8]) -> usize {
+    let mut run = 0;
+    for word in words(bytes) {
+        // Digits are 0x30 to 0x39: a high nibble of 3, and still 3 once 6
+        // is added to the low one.
+        let nibbles = ONES * 0xF0;
+        if word & nibbles != ONES * 0x30 || word.wrapping_add(ONES * 0x06) & nibbles != ONES * 0x30 {
+            break;
+        }
+        run += 8;
+    }
+    run + bytes[run..].iter().take_while(|b| b.is_ascii_digit()).count()
+}
+
+/// The position reached from `from` by reading `bytes`; columns count
+/// characters, so UTF-8 continuation bytes do not move them.
+pub(crate) fn advance(from: Position, bytes: &[u8]) -> Position {
+    let chars = |bytes: &[u8]| bytes.iter().filter(|&&b| b & 0xC0 != 0x80).count();
+    let offset = from.offset + bytes.len();
+    match bytes.iter().rposition(|&b| b == b'\n') {
+        Some(last) => {
+            let lines = bytes[..last].iter().filter(|&&b| b == b'\n').count() + 1;
+            Position { offset, line: from.line + lines, col: 1 + chars(&bytes[last + 1..]) }
+        }
+        None => Position { offset, line: from.line, col: from.col + chars(bytes) },
+    }
+}
@@ -0,0 +1,605 @@
+//! Writing Rust types with serde straight to JSON text through
+//! [`JsonWriter`], without building a [`JsonValue`] first. Enabled by the
+//! `serde` feature.
+
+use std::fmt;
+use std::io::{self, Write};
+use serde::ser::{self, Impossible, Serialize};
+use crate::core::{JsonError, JsonNumber, JsonValue, RawNumber};
+use crate::writer::{JsonWriter, PrettyConfig, WriteOptions};
+
+/// Struct name and only field under which [`JsonNumber::Raw`] travels
+/// through serde, so its text is written back unchanged.
+pub(crate) const RAW_NUMBER: &str = "$basic_json_parser::RawNumber";
+
+/// Serializes `value` into compact JSON text.
+pub fn to_string<T: ?Sized + Serialize>(value: &T) -> Result<String, JsonError> {
+    to_string_with_options(value, None, &WriteOptions::default())
+}
+
+/// Serializes `value` into indented, human-readable JSON text.
+pub fn to_string_pretty<T: ?Sized + Serialize>(value: &T) -> Result<String, JsonError> {
+    to_string_with_options(value, Some(&PrettyConfig::default()), &WriteOptions::default())
+}
+
+/// Pretty text when `config` is given, compact otherwise, written with `options`.
+pub fn to_string_with_options<T: ?Sized + Serialize>(
+    value: &T,
+    config: Option<&PrettyConfig>,
+    options: &WriteOptions,
+) -> Result<String, JsonError> {
+    let mut writer = match config {
+        Some(config) => JsonWriter::with_config(Vec::new(), config.clone()),
+        None => JsonWriter::new(Vec::new()),
+    };
+    writer.set_options(*options);
+    let mut serializer = Serializer::with_writer(writer);
+    value.serialize(&mut serializer)?;
+    Ok(String::from_utf8(serializer.into_inner()).expect("JsonWriter emits UTF-8"))
+}
+
+/// Streams `value` as compact JSON into `writer`.
+pub fn to_writer<W: Write, T: ?Sized + Serialize>(writer: W, value: &T) -> Result<(), JsonError> {
+    let mut serializer = Serializer::new(writer);
+    value.serialize(&mut serializer)?;
+    serializer.flush()
+}
+
+/// Streams `value` as indented JSON into `writer`.
+pub fn to_writer_pretty<W: Write, T: ?Sized + Serialize>(writer: W, value: &T) -> Result<(), JsonError> {
+    let mut serializer = Serializer::pretty(writer);
+    value.serialize(&mut serializer)?;
+    serializer.flush()
+}
+
+/// A serde `Serializer` writing through a [`JsonWriter`], so layout and
+/// [`WriteOptions`] apply as they do to [`JsonValue`]s.
+///
+/// Enums are externally tagged: a unit variant is written as its name, any
+/// other variant as an object with its name as the only member. Map keys
+/// must be strings, integers, booleans, chars or unit variants. `i128` and
+/// `u128` values outside the 64-bit range are written as exact integers.
+/// Failures of the underlying writer come back as [`JsonError::Custom`].
+pub struct Serializer<W: Write> {
+    writer: JsonWriter<W>,
+}
+
+impl<W: Write> Serializer<W> {
+    pub fn new(writer: W) -> Self {
+        Self::with_writer(JsonWriter::new(writer))
+    }
+
+    pub fn pretty(writer: W) -> Self {
+        Self::with_writer(JsonWriter::pretty(writer))
+    }
+
+    /// Writes through `writer` as it is configured.
+    pub fn with_writer(writer: JsonWriter<W>) -> Self {
+        Self { writer }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer.into_inner()
+    }
+
+    pub fn flush(&mut self) -> Result<(), JsonError> {
+        self.writer.flush().map_err(write_error)
+    }
+
+    fn number(&mut self, n: JsonNumber) -> Result<(), JsonError> {
+        self.writer.number(&n).map_err(write_error)
+    }
+}
+
+// Unwraps the `JsonError` a rejected number fails with; anything else went
+// wrong in the destination.
+fn write_error(error: io::Error) -> JsonError {
+    let kind = error.kind();
+    match error.into_inner().map(|inner| inner.downcast::<JsonError>()) {
+        Some(Ok(error)) => *error,
+        Some(Err(inner)) => JsonError::custom(format!("I/O error ({}): {}", kind, inner)),
+        None => JsonError::custom(format!("I/O error ({})", kind)),
+    }
+}
+
+impl<'a, W: Write> ser::Serializer for &'a mut Serializer<W> {
+    type Ok = ();
+    type Error = JsonError;
+    type SerializeSeq = Compound<'a, W>;
+    type SerializeTuple = Compound<'a, W>;
+    type SerializeTupleStruct = Compound<'a, W>;
+    type SerializeTupleVariant = Compound<'a, W>;
+    type SerializeMap = Compound<'a, W>;
+    type SerializeStruct = Compound<'a, W>;
+    type SerializeStructVariant = Compound<'a, W>;
+
+    fn serialize_bool(self, v: bool) -> Result<(), JsonError> {
+        self.writer.bool(v).map_err(write_error)
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<(), JsonError> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<(), JsonError> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<(), JsonError> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<(), JsonError> {
+        self.number(JsonNumber::Integer(v))
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<(), JsonError> {
+        match i64::try_from(v) {
+            Ok(v) => self.serialize_i64(v),
+            Err(_) if v > 0 => self.serialize_u128(v as u128),
+            Err(_) => self.number(JsonNumber::Raw(RawNumber::new(v.to_string()))),
+        }
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<(), JsonError> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<(), JsonError> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<(), JsonError> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<(), JsonError> {
+        self.number(i64::try_from(v).map_or(JsonNumber::UnsignedInteger(v), JsonNumber::Integer))
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<(), JsonError> {
+        match u64::try_from(v) {
+            Ok(v) => self.serialize_u64(v),
+            Err(_) => self.number(JsonNumber::Raw(RawNumber::new(v.to_string()))),
+        }
+    }
+
+    /// Written with the fewest digits that read back as the same `f32`, so
+    /// `0.1f32` becomes `0.1` rather than its exact `f64` value.
+    fn serialize_f32(self, v: f32) -> Result<(), JsonError> {
+        self.serialize_f64(v.to_string().parse().expect("f32 text parses as f64"))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<(), JsonError> {
+        self.number(JsonNumber::Float(v))
+    }
+
+    fn serialize_char(self, v: char) -> Result<(), JsonError> {
+        self.serialize_str(v.encode_utf8(&mut [0; 4]))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<(), JsonError> {
+        self.writer.string(v).map_err(write_error)
+    }
+
+    /// Bytes are written as an array of numbers.
+    fn serialize_bytes(self, v: &[u8]) -> Result<(), JsonError> {
+        ser::Serializer::collect_seq(self, v)
+    }
+
+    fn serialize_none(self) -> Result<(), JsonError> {
+        self.serialize_unit()
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<(), JsonError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), JsonError> {
+        self.writer.null().map_err(write_error)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), JsonError> {
+        self.serialize_unit()
+    }
+
+    fn serialize_unit_variant(self, _name: &'static str, _index: u32, variant: &'static str) -> Result<(), JsonError> {
+        self.serialize_str(variant)
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(self, _name: &'static str, value: &T) -> Result<(), JsonError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<(), JsonError> {
+        self.writer.begin_object().map_err(write_error)?;
+        self.writer.key(variant).map_err(write_error)?;
+        value.serialize(&mut *self)?;
+        self.writer.end_object().map_err(write_error)
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Compound<'a, W>, JsonError> {
+        self.writer.begin_array().map_err(write_error)?;
+        Ok(Compound { ser: self, variant: false, raw_number: false })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Compound<'a, W>, JsonError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, len: usize) -> Result<Compound<'a, W>, JsonError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Compound<'a, W>, JsonError> {
+        self.writer.begin_object().map_err(write_error)?;
+        self.writer.key(variant).map_err(write_error)?;
+        self.writer.begin_array().map_err(write_error)?;
+        Ok(Compound { ser: self, variant: true, raw_number: false })
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Compound<'a, W>, JsonError> {
+        self.writer.begin_object().map_err(write_error)?;
+        Ok(Compound { ser: self, variant: false, raw_number: false })
+    }
+
+    fn serialize_struct(self, name: &'static str, len: usize) -> Result<Compound<'a, W>, JsonError> {
+        if name == RAW_NUMBER {
+            return Ok(Compound { ser: self, variant: false, raw_number: true });
+        }
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Compound<'a, W>, JsonError> {
+        self.writer.begin_object().map_err(write_error)?;
+        self.writer.key(variant).map_err(write_error)?;
+        self.writer.begin_object().map_err(write_error)?;
+        Ok(Compound { ser: self, variant: true, raw_number: false })
+    }
+}
+
+/// The state of an array or object being written by [`Serializer`].
+pub struct Compound<'a, W: Write> {
+    ser: &'a mut Serializer<W>,
+    // Wrapped in a `{"variant": ...}` object that has to be closed too.
+    variant: bool,
+    // A `JsonNumber::Raw` whose text is the only field.
+    raw_number: bool,
+}
+
+impl<W: Write> Compound<'_, W> {
+    fn end_array(self) -> Result<(), JsonError> {
+        self.ser.writer.end_array().map_err(write_error)?;
+        self.end_variant()
+    }
+
+    fn end_object(self) -> Result<(), JsonError> {
+        if !self.raw_number {
+            self.ser.writer.end_object().map_err(write_error)?;
+        }
+        self.end_variant()
+    }
+
+    fn end_variant(self) -> Result<(), JsonError> {
+        if self.variant {
+            self.ser.writer.end_object().map_err(write_error)?;
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write> ser::SerializeSeq for Compound<'_, W> {
+    type Ok = ();
+    type Error = JsonError;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), JsonError> {
+        value.serialize(&mut *self.ser)
+    }
+
+    fn end(self) -> Result<(), JsonError> {
+        self.end_array()
+    }
+}
+
+impl<W: Write> ser::SerializeTuple for Compound<'_, W> {
+    type Ok = ();
+    type Error = JsonError;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), JsonError> {
+        value.serialize(&mut *self.ser)
+    }
+
+    fn end(self) -> Result<(), JsonError> {
+        self.end_array()
+    }
+}
+
+impl<W: Write> ser::SerializeTupleStruct for Compound<'_, W> {
+    type Ok = ();
+    type Error = JsonError;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), JsonError> {
+        value.serialize(&mut *self.ser)
+    }
+
+    fn end(self) -> Result<(), JsonError> {
+        self.end_array()
+    }
+}
+
+impl<W: Write> ser::SerializeTupleVariant for Compound<'_, W> {
+    type Ok = ();
+    type Error = JsonError;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), JsonError> {
+        value.serialize(&mut *self.ser)
+    }
+
+    fn end(self) -> Result<(), JsonError> {
+        self.end_array()
+    }
+}
+
+impl<W: Write> ser::SerializeMap for Compound<'_, W> {
+    type Ok = ();
+    type Error = JsonError;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<(), JsonError> {
+        let key = key.serialize(KeySerializer)?;
+        self.ser.writer.key(&key).map_err(write_error)
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), JsonError> {
+        value.serialize(&mut *self.ser)
+    }
+
+    fn end(self) -> Result<(), JsonError> {
+        self.end_object()
+    }
+}
+
+impl<W: Write> ser::SerializeStruct for Compound<'_, W> {
+    type Ok = ();
+    type Error = JsonError;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, key: &'static str, value: &T) -> Result<(), JsonError> {
+        if self.raw_number {
+            // Text from elsewhere is checked before it is written as a number.
+            return self.ser.number(JsonNumber::from_lexeme(&value.serialize(KeySerializer)?)?);
+        }
+        self.ser.writer.key(key).map_err(write_error)?;
+        value.serialize(&mut *self.ser)
+    }
+
+    fn end(self) -> Result<(), JsonError> {
+        self.end_object()
+    }
+}
+
+impl<W: Write> ser::SerializeStructVariant for Compound<'_, W> {
+    type Ok = ();
+    type Error = JsonError;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, key: &'static str, value: &T) -> Result<(), JsonError> {
+        self.ser.writer.key(key).map_err(write_error)?;
+        value.serialize(&mut *self.ser)
+    }
+
+    fn end(self) -> Result<(), JsonError> {
+        self.end_object()
+    }
+}
+
+// Turns a map key into the string it is written as.
+struct KeySerializer;
+
+fn key_must_be_a_string() -> JsonError {
+    JsonError::custom("map key must be a string, integer, boolean or char")
+}
+
+impl ser::Serializer for KeySerializer {
+    type Ok = String;
+    type Error = JsonError;
+    type SerializeSeq = Impossible<String, JsonError>;
+    type SerializeTuple = Impossible<String, JsonError>;
+    type SerializeTupleStruct = Impossible<String, JsonError>;
+    type SerializeTupleVariant = Impossible<String, JsonError>;
+    type SerializeMap = Impossible<String, JsonError>;
+    type SerializeStruct = Impossible<String, JsonError>;
+    type SerializeStructVariant = Impossible<String, JsonError>;
+
+    fn serialize_bool(self, v: bool) -> Result<String, JsonError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<String, JsonError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<String, JsonError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<String, JsonError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<String, JsonError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<String, JsonError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<String, JsonError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<String, JsonError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<String, JsonError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<String, JsonError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<String, JsonError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_f32(self, _v: f32) -> Result<String, JsonError> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_f64(self, _v: f64) -> Result<String, JsonError> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_char(self, v: char) -> Result<String, JsonError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_str(self, v: &str) -> Result<String, JsonError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<String, JsonError> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_none(self) -> Result<String, JsonError> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, _value: &T) -> Result<String, JsonError> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_unit(self) -> Result<String, JsonError> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<String, JsonError> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_unit_variant(self, _name: &'static str, _index: u32, variant: &'static str) -> Result<String, JsonError> {
+        Ok(variant.to_string())
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(self, _name: &'static str, value: &T) -> Result<String, JsonError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<String, JsonError> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, JsonError> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, JsonError> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeTupleStruct, JsonError> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, JsonError> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, JsonError> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct, JsonError> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, JsonError> {
+        Err(key_must_be_a_string())
+    }
+}
+
+impl ser::Error for JsonError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        JsonError::custom(msg)
+    }
+}
+
+impl Serialize for JsonValue {
+    fn serialize<S: ser::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            JsonValue::Null => serializer.serialize_unit(),
+            JsonValue::Bool(b) => serializer.serialize_bool(*b),
+            JsonValue::Number(n) => n.serialize(serializer),
+            JsonValue::String(s) => serializer.serialize_str(s),
+            JsonValue::Wtf8(_) => Err(ser::Error::custom("a string with a lone surrogate has no serde form")),
+            JsonValue::Array(elements) => serializer.collect_seq(elements),
+            JsonValue::Object(pairs) => serializer.collect_map(pairs.iter().map(|(key, value)| (key, value))),
+        }
+    }
+}
+
+/// [`JsonNumber::Raw`] is written as a struct with a single private field
+/// holding its text, which [`Serializer`] writes back as the number itself
+/// and which other formats keep so that it deserializes back as `Raw`.
+impl Serialize for JsonNumber {
+    fn serialize<S: ser::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            JsonNumber::Integer(i) => serializer.serialize_i64(*i),
+            JsonNumber::UnsignedInteger(u) => serializer.serialize_u64(*u),
+            JsonNumber::Float(f) => serializer.serialize_f64(*f),
+            JsonNumber::Raw(text) => {
+                use ser::SerializeStruct;
+                let mut raw = serializer.serialize_struct(RAW_NUMBER, 1)?;
+                raw.serialize_field(RAW_NUMBER, text.as_str())?;
+                raw.end()
+            }
+        }
+    }
+}
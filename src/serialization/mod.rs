@@ -1,77 +1,73 @@
-use crate::core::JsonValue;
-use crate::parser::StringEscaper;
+use std::io::{self, Write};
+use crate::core::{JsonError, JsonValue};
+use crate::writer::{JsonWriter, PrettyConfig, WriteOptions};
 
 impl JsonValue{
 
-    pub(crate) fn to_json_string(&self) -> String {
-        self.format(0, false)
+    pub fn to_json_string(&self) -> String {
+        Self::into_string(JsonWriter::new(Vec::new()), self)
     }
-    pub(crate)  fn to_json_string_pretty(&self) -> String{
-        self.format(0, true)
+    pub fn to_json_string_pretty(&self) -> String{
+        Self::into_string(JsonWriter::pretty(Vec::new()), self)
     }
-    fn format(&self, indent: usize, pretty: bool) -> String{
-        use JsonValue::*;
-        match self {
-            Null => "null".to_string(),
-            Bool(b) => b.to_string(),
-            Number(n) => n.to_string(),
-            String(s) =>  format!("\"{}\"", StringEscaper::escape(s)),
-            Array(arr) => self.format_array(arr, indent, pretty),
-            Object(obj) => self.format_object(obj, indent, pretty),
-        }
+    pub fn to_json_string_with(&self, config: &PrettyConfig) -> String {
+        Self::into_string(JsonWriter::with_config(Vec::new(), config.clone()), self)
     }
-    fn format_array(&self, arr: &[JsonValue], indent: usize, pretty: bool) -> String {
-        if arr.is_empty() {
-            return "[]".to_string();
-        }
 
-        let items: Vec<String> = arr
-            .iter()
-            .map(|v| {
-                if pretty {
-                    format!("{}{}", indent_str(indent + 1), v.format(indent + 1, true))
-                } else {
-                    v.format(indent + 1, false)
-                }
-            })
-            .collect();
+    /// Pretty text when `config` is given, compact otherwise, written with
+    /// `options`. Fails only when `options` reject a NaN or infinite number.
+    pub fn to_json_string_with_options(&self, config: Option<&PrettyConfig>, options: &WriteOptions) -> Result<String, JsonError> {
+        let mut writer = Self::writer_for(Vec::new(), config, options);
+        writer.value(self).map_err(|e| match e.into_inner().map(|inner| inner.downcast::<JsonError>()) {
+            Some(Ok(err)) => *err,
+            _ => unreachable!("writing to a Vec only fails on rejected numbers"),
+        })?;
+        Ok(String::from_utf8(writer.into_inner()).expect("JsonWriter emits UTF-8"))
+    }
 
-        if pretty {
-            format!("[\n{}\n{}]", items.join(",\n"), indent_str(indent))
-        } else {
-            format!("[{}]", items.join(","))
-        }
+    /// Streams compact JSON into `writer` without building the text in memory.
+    pub fn write_json<W: Write>(&self, writer: W) -> io::Result<()> {
+        let mut writer = JsonWriter::new(writer);
+        writer.value(self)?;
+        writer.flush()
     }
 
-    fn format_object(&self, obj: &[(String, JsonValue)], indent: usize, pretty: bool) -> String {
-        if obj.is_empty() {
-            return "{}".to_string();
-        }
+    /// Streams indented JSON into `writer` without building the text in memory.
+    pub fn write_json_pretty<W: Write>(&self, writer: W) -> io::Result<()> {
+        let mut writer = JsonWriter::pretty(writer);
+        writer.value(self)?;
+        writer.flush()
+    }
 
-        let items: Vec<String> = obj
-            .iter()
-            .map(|(k, v)| {
-                if pretty {
-                    format!(
-                        "{}\"{}\": {}",
-                        indent_str(indent + 1),
-                        StringEscaper::escape(k),
-                        v.format(indent + 1, true)
-                    )
-                } else {
-                    format!("\"{}\":{}", StringEscaper::escape(k), v.format(indent + 1, false))
-                }
-            })
-            .collect();
+    /// Streams pretty JSON laid out according to `config` into `writer`.
+    pub fn write_json_with<W: Write>(&self, writer: W, config: &PrettyConfig) -> io::Result<()> {
+        let mut writer = JsonWriter::with_config(writer, config.clone());
+        writer.value(self)?;
+        writer.flush()
+    }
 
-        if pretty {
-            format!("{{\n{}\n{}}}", items.join(",\n"), indent_str(indent))
-        } else {
-            format!("{{{}}}", items.join(","))
-        }
+    /// Streams JSON into `writer`: pretty when `config` is given, compact
+    /// otherwise, written with `options`. A rejected NaN or infinite number
+    /// fails with `ErrorKind::InvalidData` wrapping a
+    /// [`JsonError::NonFiniteNumber`].
+    pub fn write_json_with_options<W: Write>(&self, writer: W, config: Option<&PrettyConfig>, options: &WriteOptions) -> io::Result<()> {
+        let mut writer = Self::writer_for(writer, config, options);
+        writer.value(self)?;
+        writer.flush()
     }
-}
 
-fn indent_str(level: usize) -> String {
-    "    ".repeat(level)
-}
\ No newline at end of file
+    fn writer_for<W: Write>(writer: W, config: Option<&PrettyConfig>, options: &WriteOptions) -> JsonWriter<W> {
+        let mut writer = match config {
+            Some(config) => JsonWriter::with_config(writer, config.clone()),
+            None => JsonWriter::new(writer),
+        };
+        writer.set_options(*options);
+        writer
+    }
+
+    fn into_string(mut writer: JsonWriter<Vec<u8>>, value: &JsonValue) -> String {
+        // Writing a well-formed tree into a Vec cannot fail and only ever emits UTF-8.
+        writer.value(value).expect("writing to a Vec cannot fail");
+        String::from_utf8(writer.into_inner()).expect("JsonWriter emits UTF-8")
+    }
+}
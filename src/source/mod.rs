@@ -0,0 +1,118 @@
+use std::io::{self, ErrorKind, Read};
+use crate::core::Position;
+use crate::scan;
+
+/// The input bytes the [`Lexer`](crate::engine::Lexer) scans, held in a
+/// window that slides forward as tokens are consumed.
+///
+/// Offsets are counted from the start of the whole input.
+pub trait Source {
+    /// The bytes read and not yet discarded.
+    fn buffer(&self) -> &[u8];
+
+    /// Where the first byte of [`buffer`](Self::buffer) is in the input.
+    fn start(&self) -> Position;
+
+    /// Reads more input into the buffer, first discarding what comes before
+    /// offset `keep`; `false` at the end of input.
+    fn refill(&mut self, keep: usize) -> io::Result<bool>;
+
+    /// The text from offset `start` up to `end`, which must be buffered,
+    /// leaving out a character cut off at `end`. Fails with the offset of
+    /// the first byte that is not valid UTF-8.
+    fn text(&self, start: usize, end: usize) -> Result<&str, usize>;
+}
+
+/// The bytes of an in-memory `&str`, all buffered from the start.
+pub struct StrSource<'a> {
+    input: &'a str,
+}
+
+impl<'a> StrSource<'a> {
+    pub(crate) fn new(input: &'a str) -> Self {
+        Self { input }
+    }
+
+    pub(crate) fn input(&self) -> &'a str {
+        self.input
+    }
+}
+
+impl Source for StrSource<'_> {
+    fn buffer(&self) -> &[u8] {
+        self.input.as_bytes()
+    }
+
+    fn start(&self) -> Position {
+        Position { offset: 0, line: 1, col: 1 }
+    }
+
+    fn refill(&mut self, _keep: usize) -> io::Result<bool> {
+        Ok(false)
+    }
+
+    fn text(&self, start: usize, mut end: usize) -> Result<&str, usize> {
+        while !self.input.is_char_boundary(end) {
+            end -= 1;
+        }
+        self.input.get(start..end).ok_or(start)
+    }
+}
+
+/// Bytes pulled from a `Read` in chunks; only the token being read and the
+/// one before it stay buffered.
+pub struct ReadSource<R> {
+    reader: R,
+    buffer: Vec<u8>,
+    start: Position,
+}
+
+// Bytes asked of the reader at a time.
+const CHUNK: usize = 8 * 1024;
+
+impl<R: Read> ReadSource<R> {
+    pub(crate) fn new(reader: R) -> Self {
+        Self { reader, buffer: Vec::with_capacity(CHUNK), start: Position { offset: 0, line: 1, col: 1 } }
+    }
+}
+
+impl<R: Read> Source for ReadSource<R> {
+    fn buffer(&self) -> &[u8] {
+        &self.buffer
+    }
+
+    fn start(&self) -> Position {
+        self.start
+    }
+
+    fn refill(&mut self, keep: usize) -> io::Result<bool> {
+        let done = keep.saturating_sub(self.start.offset).min(self.buffer.len());
+        self.start = scan::advance(self.start, &self.buffer[..done]);
+        self.buffer.drain(..done);
+
+        let len = self.buffer.len();
+        self.buffer.resize(len + CHUNK, 0);
+        loop {
+            match self.reader.read(&mut self.buffer[len..]) {
+                Ok(read) => {
+                    self.buffer.truncate(len + read);
+                    return Ok(read > 0);
+                }
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => {
+                    self.buffer.truncate(len);
+                    return Err(e);
+                }
+            }
+        }
+    }
+
+    fn text(&self, start: usize, end: usize) -> Result<&str, usize> {
+        let bytes = &self.buffer[start - self.start.offset..end - self.start.offset];
+        match std::str::from_utf8(bytes) {
+            Ok(text) => Ok(text),
+            Err(e) if e.error_len().is_none() => Ok(std::str::from_utf8(&bytes[..e.valid_up_to()]).unwrap_or_default()),
+            Err(e) => Err(start + e.valid_up_to()),
+        }
+    }
+}
@@ -0,0 +1,359 @@
+use std::io::Read;
+use crate::core::{quote, JsonError, JsonNumber, JsonValue, Limit, Position, Token};
+use crate::engine::{
+    identifier_number, lone_surrogate_in_name, member_expectation, member_name, Lexer, SeenKeys, EXPECT_COLON,
+    EXPECT_COMMA, EXPECT_END_ARRAY, EXPECT_END_OBJECT, EXPECT_EOF, EXPECT_VALUE,
+};
+use crate::jsonpath::{normalized_path, PathElement};
+use crate::options::{DuplicateKeyPolicy, ParseOptions};
+use crate::source::{ReadSource, Source, StrSource};
+use crate::wtf8::Wtf8String;
+
+/// A leaf value reported by [`JsonReader`].
+#[derive(Debug, PartialEq, Clone)]
+pub enum Scalar {
+    Null,
+    Bool(bool),
+    Number(JsonNumber),
+    String(String),
+    /// See [`JsonValue::Wtf8`].
+    Wtf8(Wtf8String),
+}
+
+impl From<Scalar> for JsonValue {
+    fn from(scalar: Scalar) -> Self {
+        match scalar {
+            Scalar::Null => JsonValue::Null,
+            Scalar::Bool(b) => JsonValue::Bool(b),
+            Scalar::Number(n) => JsonValue::Number(n),
+            Scalar::String(s) => JsonValue::String(s),
+            Scalar::Wtf8(s) => JsonValue::Wtf8(s),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum JsonEvent {
+    StartObject,
+    Key(String),
+    EndObject,
+    StartArray,
+    EndArray,
+    Value(Scalar),
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Container {
+    Object,
+    Array,
+}
+
+// An open container, with what is needed to name its children in errors.
+struct Frame {
+    container: Container,
+    // Children completed so far.
+    len: usize,
+    // Name of the current member of an object.
+    key: String,
+    seen: SeenKeys,
+}
+
+impl Frame {
+    fn new(container: Container, options: &ParseOptions) -> Self {
+        // Events cannot be taken back, so only an error policy applies here.
+        let policy = match options.duplicate_keys {
+            DuplicateKeyPolicy::Error if container == Container::Object => DuplicateKeyPolicy::Error,
+            _ => DuplicateKeyPolicy::All,
+        };
+        Self { container, len: 0, key: String::new(), seen: SeenKeys::new(policy) }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Expect {
+    Value,
+    FirstKeyOrEnd,
+    Key,
+    FirstValueOrEnd,
+    Element,
+    CommaOrEnd,
+    Eof,
+    Done,
+}
+
+/// A pull parser that yields one [`JsonEvent`] at a time.
+///
+/// Memory use is bounded by the nesting depth of the document, not its
+/// size: only the stack of open containers is kept.
+pub struct JsonReader<S> {
+    lexer: Lexer<S>,
+    stack: Vec<Frame>,
+    expect: Expect,
+    // Values started so far, against `Limits::max_nodes`.
+    nodes: usize,
+}
+
+impl<'a> JsonReader<StrSource<'a>> {
+    pub fn new(input: &'a str) -> Self {
+        Self::with_options(input, &ParseOptions::default())
+    }
+
+    /// Reads events from `input`, accepting the extensions enabled in `options`.
+    pub fn with_options(input: &'a str, options: &ParseOptions) -> Self {
+        Self::with_lexer(Lexer::new(input, *options))
+    }
+}
+
+impl<R: Read> JsonReader<ReadSource<R>> {
+    /// Reads events from `reader` without loading the whole document.
+    pub fn from_reader(reader: R) -> Self {
+        Self::from_reader_with_options(reader, &ParseOptions::default())
+    }
+
+    pub fn from_reader_with_options(reader: R, options: &ParseOptions) -> Self {
+        Self::with_lexer(Lexer::from_reader(reader, *options))
+    }
+}
+
+impl<S: Source> JsonReader<S> {
+    fn with_lexer(lexer: Lexer<S>) -> Self {
+        Self { lexer, stack: Vec::new(), expect: Expect::Value, nodes: 0 }
+    }
+
+    /// Current nesting depth; 0 outside any container.
+    pub fn depth(&self) -> usize {
+        self.stack.len()
+    }
+
+    /// Returns the next event and where it starts, or `None` once the
+    /// document has been fully read.
+    pub fn next_event(&mut self) -> Result<Option<(JsonEvent, Position)>, JsonError> {
+        let result = self.read_event().map_err(|e| e.with_path(self.path()));
+        if result.is_err() {
+            self.expect = Expect::Done;
+        }
+        result
+    }
+
+    // Normalized path of the value the next token belongs to.
+    fn path(&self) -> String {
+        let reading_value = matches!(self.expect, Expect::Value | Expect::FirstValueOrEnd | Expect::Element);
+        let open = self.stack.len().saturating_sub(usize::from(!reading_value));
+        normalized_path(self.stack[..open].iter().map(|frame| match frame.container {
+            Container::Object => PathElement::Name(&frame.key),
+            Container::Array => PathElement::Index(frame.len),
+        }))
+    }
+
+    // What the reader would have accepted in its current state.
+    fn expected(&self) -> Vec<&'static str> {
+        let trailing_commas = self.lexer.options().trailing_commas;
+        match self.expect {
+            Expect::Value => vec![EXPECT_VALUE],
+            Expect::FirstValueOrEnd => vec![EXPECT_VALUE, EXPECT_END_ARRAY],
+            Expect::Element if trailing_commas => vec![EXPECT_VALUE, EXPECT_END_ARRAY],
+            Expect::Element => vec![EXPECT_VALUE],
+            Expect::FirstKeyOrEnd => vec![member_expectation(self.lexer.options()), EXPECT_END_OBJECT],
+            Expect::Key if trailing_commas => vec![member_expectation(self.lexer.options()), EXPECT_END_OBJECT],
+            Expect::Key => vec![member_expectation(self.lexer.options())],
+            Expect::CommaOrEnd => match self.stack.last().map(|frame| frame.container) {
+                Some(Container::Object) => vec![EXPECT_COMMA, EXPECT_END_OBJECT],
+                _ => vec![EXPECT_COMMA, EXPECT_END_ARRAY],
+            },
+            Expect::Eof => vec![EXPECT_EOF],
+            Expect::Done => Vec::new(),
+        }
+    }
+
+    fn unexpected(&self, token: &Token, position: Position) -> JsonError {
+        match token {
+            Token::Eof => JsonError::unexpected_eof(position, &self.expected()),
+            token => JsonError::invalid_token(position, token.describe(), &self.expected()),
+        }
+    }
+
+    /// Skips the value that would be returned next, including all of its
+    /// children when it is an object or array.
+    pub fn skip_value(&mut self) -> Result<(), JsonError> {
+        self.skip_nested(0)
+    }
+
+    /// Skips the rest of the container whose `StartObject`/`StartArray`
+    /// event was just returned, including its closing event.
+    pub fn skip_container(&mut self) -> Result<(), JsonError> {
+        self.skip_nested(1)
+    }
+
+    fn skip_nested(&mut self, mut depth: usize) -> Result<(), JsonError> {
+        loop {
+            let position = self.lexer.position();
+            match self.next_event()? {
+                Some((JsonEvent::StartObject | JsonEvent::StartArray, _)) => depth += 1,
+                Some((JsonEvent::EndObject | JsonEvent::EndArray, _)) if depth > 0 => depth -= 1,
+                Some((JsonEvent::Key(_), _)) if depth > 0 => {}
+                Some((JsonEvent::Value(_), _)) => {}
+                Some((event, position)) => {
+                    let found = match event {
+                        JsonEvent::Key(key) => format!("\"{}\"", key),
+                        JsonEvent::EndObject => "}".to_string(),
+                        _ => "]".to_string(),
+                    };
+                    let error = JsonError::invalid_token(position, Some(quote(&found)), &[EXPECT_VALUE]);
+                    return Err(error.with_path(self.path()));
+                }
+                None => return Err(JsonError::unexpected_eof(position, &[EXPECT_VALUE])),
+            }
+            if depth == 0 {
+                return Ok(());
+            }
+        }
+    }
+
+    fn read_event(&mut self) -> Result<Option<(JsonEvent, Position)>, JsonError> {
+        loop {
+            if self.expect == Expect::Done {
+                return Ok(None);
+            }
+            let token = self.lexer.next_token()?;
+            let position = self.lexer.token_start();
+            let event = match (self.expect, token) {
+                (Expect::Eof, Token::Eof) => {
+                    self.expect = Expect::Done;
+                    return Ok(None);
+                }
+                (Expect::FirstKeyOrEnd, Token::CurlyRight) | (Expect::FirstValueOrEnd, Token::SquareRight) => {
+                    self.close_container()
+                }
+                (Expect::Key, Token::CurlyRight) | (Expect::Element, Token::SquareRight)
+                    if self.lexer.options().trailing_commas =>
+                {
+                    self.close_container()
+                }
+                (Expect::FirstKeyOrEnd | Expect::Key, token) => self.start_member(token, position)?,
+                (Expect::Value | Expect::FirstValueOrEnd | Expect::Element, token) => self.start_value(token, position)?,
+                (Expect::CommaOrEnd, Token::Comma) => {
+                    self.expect = match self.top() {
+                        Some(Container::Object) => Expect::Key,
+                        _ => Expect::Element,
+                    };
+                    continue;
+                }
+                (Expect::CommaOrEnd, Token::CurlyRight) if self.top() == Some(Container::Object) => {
+                    self.close_container()
+                }
+                (Expect::CommaOrEnd, Token::SquareRight) if self.top() == Some(Container::Array) => {
+                    self.close_container()
+                }
+                (_, token) => return Err(self.unexpected(&token, position)),
+            };
+            return Ok(Some((event, position)));
+        }
+    }
+
+    fn start_member(&mut self, token: Token, position: Position) -> Result<JsonEvent, JsonError> {
+        let Some(key) = member_name(&token, self.lexer.options()).map(str::to_string) else {
+            return Err(lone_surrogate_in_name(position, &token).unwrap_or_else(|| self.unexpected(&token, position)));
+        };
+        self.check_elements(position)?;
+        if let Some(frame) = self.stack.last_mut() {
+            frame.seen.insert(&key, &token, || position, frame.len)?;
+            frame.key.clone_from(&key);
+        }
+        // The colon and the value that follows belong to this member.
+        self.expect = Expect::Value;
+        match self.lexer.next_token()? {
+            Token::Colon => {}
+            token => {
+                let at = self.lexer.token_start();
+                return Err(match token {
+                    Token::Eof => JsonError::unexpected_eof(at, &[EXPECT_COLON]),
+                    token => JsonError::invalid_token(at, token.describe(), &[EXPECT_COLON]),
+                });
+            }
+        }
+        Ok(JsonEvent::Key(key))
+    }
+
+    fn start_value(&mut self, token: Token, position: Position) -> Result<JsonEvent, JsonError> {
+        if matches!(self.expect, Expect::FirstValueOrEnd | Expect::Element) {
+            self.check_elements(position)?;
+        }
+        let scalar = match token {
+            Token::CurlyLeft => {
+                self.count_value(position, true)?;
+                self.stack.push(Frame::new(Container::Object, self.lexer.options()));
+                self.expect = Expect::FirstKeyOrEnd;
+                return Ok(JsonEvent::StartObject);
+            }
+            Token::SquareLeft => {
+                self.count_value(position, true)?;
+                self.stack.push(Frame::new(Container::Array, self.lexer.options()));
+                self.expect = Expect::FirstValueOrEnd;
+                return Ok(JsonEvent::StartArray);
+            }
+            Token::Null => Scalar::Null,
+            Token::True => Scalar::Bool(true),
+            Token::False => Scalar::Bool(false),
+            Token::Number(n) => Scalar::Number(n),
+            Token::String(s) => Scalar::String(s),
+            Token::Wtf8(s) => Scalar::Wtf8(s),
+            Token::Identifier(ref name) => match identifier_number(name, self.lexer.options()) {
+                Some(n) => Scalar::Number(n),
+                None => return Err(self.unexpected(&token, position)),
+            },
+            token => return Err(self.unexpected(&token, position)),
+        };
+        self.count_value(position, false)?;
+        self.value_finished();
+        Ok(JsonEvent::Value(scalar))
+    }
+
+    // Counts a value starting at `position` against the limits; `nested`
+    // when it opens a container.
+    fn count_value(&mut self, position: Position, nested: bool) -> Result<(), JsonError> {
+        let limits = self.lexer.options().limits;
+        self.nodes += 1;
+        limits.check(Limit::Nodes, self.nodes, || position)?;
+        if nested {
+            limits.check(Limit::Depth, self.stack.len() + 1, || position)?;
+        }
+        Ok(())
+    }
+
+    // The member or element starting at `position` must fit its container.
+    fn check_elements(&self, position: Position) -> Result<(), JsonError> {
+        let len = self.stack.last().map_or(0, |frame| frame.len);
+        self.lexer.options().limits.check(Limit::Elements, len + 1, || position)
+    }
+
+    fn close_container(&mut self) -> JsonEvent {
+        let event = match self.stack.pop().map(|frame| frame.container) {
+            Some(Container::Object) => JsonEvent::EndObject,
+            _ => JsonEvent::EndArray,
+        };
+        self.value_finished();
+        event
+    }
+
+    fn value_finished(&mut self) {
+        match self.stack.last_mut() {
+            Some(frame) => {
+                frame.len += 1;
+                self.expect = Expect::CommaOrEnd;
+            }
+            None => self.expect = Expect::Eof,
+        }
+    }
+
+    fn top(&self) -> Option<Container> {
+        self.stack.last().map(|frame| frame.container)
+    }
+}
+
+impl<S: Source> Iterator for JsonReader<S> {
+    type Item = Result<(JsonEvent, Position), JsonError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_event().transpose()
+    }
+}
@@ -0,0 +1,153 @@
+use crate::cli::parse_flags;
+use crate::core::{JsonError, JsonNumber, JsonValue};
+use crate::driver::{deserialize, deserialize_reader_with, deserialize_with};
+use crate::options::ParseOptions;
+use crate::stream::{JsonEvent, JsonReader, Scalar};
+
+fn json5(input: &str) -> Result<JsonValue, JsonError> {
+    deserialize_with(input, &ParseOptions::json5())
+}
+
+#[test]
+fn test_json5_document() {
+    let input = r#"
+        // Service configuration
+        {
+            name: 'api',           /* single quotes */
+            port: 0x1F90,
+            ratio: .5,
+            scale: +2.,
+            limits: [Infinity, -Infinity,],
+            "quoted": "it\'s",
+            $ref: null,
+        }
+    "#;
+    let value = json5(input).unwrap();
+    assert_eq!(value["name"], JsonValue::String("api".to_string()));
+    assert_eq!(value["port"].as_i64(), Some(8080));
+    assert_eq!(value["ratio"].as_f64(), Some(0.5));
+    assert_eq!(value["scale"].as_f64(), Some(2.0));
+    assert_eq!(value["limits"][0].as_f64(), Some(f64::INFINITY));
+    assert_eq!(value["limits"][1].as_f64(), Some(f64::NEG_INFINITY));
+    assert_eq!(value["limits"].len(), 2);
+    assert_eq!(value["quoted"], JsonValue::String("it's".to_string()));
+    assert!(value["$ref"].is_null());
+    assert!(json5("NaN").unwrap().as_f64().unwrap().is_nan());
+}
+
+#[test]
+fn test_defaults_stay_strict() {
+    for input in [
+        "[1,]",
+        "{\"a\": 1,}",
+        "{a: 1}",
+        "'x'",
+        "// c\n1",
+        "/* c */ 1",
+        "Infinity",
+        "-Infinity",
+        "NaN",
+        "0x10",
+        "+1",
+        ".5",
+        r#""\v""#,
+    ] {
+        assert!(deserialize(input).is_err(), "{} should be rejected", input);
+        assert!(json5(input).is_ok(), "{} should be accepted as JSON5", input);
+    }
+}
+
+#[test]
+fn test_individual_extensions() {
+    let only_comments = ParseOptions { line_comments: true, ..ParseOptions::default() };
+    assert_eq!(deserialize_with("[1, // one\n 2]", &only_comments).unwrap().len(), 2);
+    assert!(deserialize_with("[1, /* one */ 2]", &only_comments).is_err());
+    assert!(deserialize_with("[1,]", &only_comments).is_err());
+
+    let only_keys = ParseOptions { unquoted_keys: true, ..ParseOptions::default() };
+    let value = deserialize_with("{a_1: 1, true: 2, null: 3}", &only_keys).unwrap();
+    assert_eq!(value["a_1"].as_i64(), Some(1));
+    assert_eq!(value["true"].as_i64(), Some(2));
+    assert_eq!(value["null"].as_i64(), Some(3));
+    assert!(deserialize_with("{a: b}", &only_keys).is_err());
+    assert!(deserialize_with("Infinity", &only_keys).is_err());
+
+    let only_floats = ParseOptions { special_floats: true, ..ParseOptions::default() };
+    assert!(deserialize_with("{Infinity: 1}", &only_floats).is_err());
+    assert_eq!(deserialize_with("-Infinity", &only_floats).unwrap().as_f64(), Some(f64::NEG_INFINITY));
+}
+
+#[test]
+fn test_json5_numbers() {
+    assert_eq!(json5("-0x10").unwrap().as_i64(), Some(-16));
+    assert_eq!(json5("0xFFFFFFFFFFFFFFFF").unwrap().as_u64(), Some(u64::MAX));
+    assert_eq!(json5("-0x8000000000000000").unwrap().as_i64(), Some(i64::MIN));
+    assert_eq!(json5("0").unwrap().as_i64(), Some(0));
+    assert_eq!(json5("0.25").unwrap().as_f64(), Some(0.25));
+    assert_eq!(json5("5.").unwrap(), JsonValue::Number(JsonNumber::Float(5.0)));
+    assert!(json5("0x").is_err());
+    assert!(json5("+-1").is_err());
+    assert!(json5(".").is_err());
+}
+
+#[test]
+fn test_json5_strings() {
+    assert_eq!(json5(r#"'a "quoted" word'"#).unwrap().as_str(), Some("a \"quoted\" word"));
+    assert_eq!(json5(r#""\x41\v\0""#).unwrap().as_str(), Some("A\u{000B}\0"));
+    assert_eq!(json5("'line \\\ncontinued'").unwrap().as_str(), Some("line continued"));
+    assert_eq!(json5("'crlf \\\r\ncontinued'").unwrap().as_str(), Some("crlf continued"));
+    assert_eq!(json5(r#""\q""#).unwrap().as_str(), Some("q"));
+    assert!(json5(r#""\1""#).is_err());
+    assert!(json5(r#""\01""#).is_err());
+    assert!(json5(r#""\xZZ""#).is_err());
+}
+
+#[test]
+fn test_comment_errors() {
+    assert!(matches!(json5("/* open"), Err(JsonError::UnexpectedEof { line: 1, col: 1, .. })));
+    assert!(matches!(json5("1 / 2"), Err(JsonError::InvalidToken { line: 1, col: 3, .. })));
+    assert_eq!(json5("1 // trailing").unwrap().as_i64(), Some(1));
+}
+
+#[test]
+fn test_reader_and_stream_accept_json5() {
+    let input = "{a: [1, 2,], /* x */ b: 'y',}";
+    let value = deserialize_reader_with(input.as_bytes(), &ParseOptions::json5()).unwrap();
+    assert_eq!(value, json5(input).unwrap());
+
+    let events: Vec<JsonEvent> = JsonReader::with_options(input, &ParseOptions::json5())
+        .map(|r| r.map(|(event, _)| event))
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(events.len(), 9);
+    assert_eq!(events[1], JsonEvent::Key("a".to_string()));
+    assert_eq!(events[7], JsonEvent::Value(Scalar::String("y".to_string())));
+    assert!(JsonReader::new(input).next_event().is_ok());
+    assert!(JsonReader::new(input).nth(1).unwrap().is_err());
+    assert!(JsonReader::new("[1,]").nth(2).unwrap().is_err());
+}
+
+#[test]
+fn test_json5_cli_flag() {
+    let (options, positional) = parse_flags(["--json5".to_string(), "in.json5".to_string()].into_iter()).unwrap();
+    assert_eq!(options.parse, ParseOptions::json5());
+    assert_eq!(positional, ["in.json5"]);
+
+    let args = ["--arbitrary-precision", "--json5"].map(String::from);
+    let (options, _) = parse_flags(args.into_iter()).unwrap();
+    assert_eq!(options.parse, ParseOptions { arbitrary_precision: true, ..ParseOptions::json5() });
+}
+
+#[test]
+fn test_strict_scanner() {
+    assert!(matches!(deserialize("[01]"), Err(JsonError::InvalidNumber { line: 1, col: 2, .. })));
+    assert!(matches!(deserialize("1e999"), Err(JsonError::InvalidNumber { .. })));
+    assert!(matches!(deserialize("\"a\tb\""), Err(JsonError::InvalidToken { line: 1, col: 3, .. })));
+    assert!(deserialize("\u{00A0}1").is_err());
+    assert!(deserialize("\u{FEFF}1").is_err());
+
+    assert_eq!(json5("\u{FEFF}\u{00A0}1\u{2028}").unwrap().as_i64(), Some(1));
+    assert_eq!(json5("'a\tb'").unwrap().as_str(), Some("a\tb"));
+    assert!(json5("'a\nb'").is_err());
+    assert!(json5("01").is_err());
+}
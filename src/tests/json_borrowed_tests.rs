@@ -0,0 +1,63 @@
+use std::borrow::Cow;
+use crate::borrowed::BorrowedJsonValue;
+use crate::core::{JsonError, JsonValue};
+use crate::driver::{deserialize, deserialize_borrowed, deserialize_borrowed_with, deserialize_with};
+use crate::options::{DuplicateKeyPolicy, Limits, ParseOptions};
+
+#[test]
+fn test_plain_strings_borrow_from_input() {
+    let input = r#"{"name": "Alice", "say": "a\"b", "tags": ["x", "yé"], "n\tb": 1}"#;
+    let value = deserialize_borrowed(input).unwrap();
+    let BorrowedJsonValue::Object(pairs) = &value else { panic!("{:?}", value) };
+    let borrowed: Vec<_> = pairs.iter().map(|(k, _)| matches!(k, Cow::Borrowed(_))).collect();
+    assert_eq!(borrowed, [true, true, true, false]);
+    assert_eq!(pairs[3].0, "n\tb");
+
+    let BorrowedJsonValue::String(name) = &pairs[0].1 else { panic!() };
+    assert!(matches!(name, Cow::Borrowed(_)));
+    assert!(std::ptr::eq(name.as_ptr(), input[10..].as_ptr()));
+    let BorrowedJsonValue::String(say) = &pairs[1].1 else { panic!() };
+    assert_eq!((matches!(say, Cow::Borrowed(_)), say.as_ref()), (false, "a\"b"));
+    assert_eq!(value.get("tags").and_then(|tags| tags.get_index(1)).and_then(BorrowedJsonValue::as_str), Some("yé"));
+
+    assert_eq!(value.into_owned(), deserialize(input).unwrap());
+}
+
+#[test]
+fn test_into_owned_matches_the_tree_parser() {
+    let options = ParseOptions::json5();
+    let input = "{a: 'it\\'s', 'b': [1, -2.5, 0x10, true, null, \"\", \"line\\\n two\"], c: {d: {}}, // x\n}";
+    let value = deserialize_borrowed_with(input, &options).unwrap();
+    assert!(matches!(value.get("b").and_then(|b| b.get_index(5)), Some(BorrowedJsonValue::String(Cow::Borrowed("")))));
+    assert_eq!(JsonValue::from(value), deserialize_with(input, &options).unwrap());
+
+    for policy in [DuplicateKeyPolicy::First, DuplicateKeyPolicy::Last, DuplicateKeyPolicy::All] {
+        let options = ParseOptions { duplicate_keys: policy, ..ParseOptions::default() };
+        let input = r#"{"a": 1, "b": {"a": 2, "a": 3}, "a\u0000": 4, "a": 5}"#;
+        let value = deserialize_borrowed_with(input, &options).unwrap();
+        assert_eq!(value.into_owned(), deserialize_with(input, &options).unwrap(), "{:?}", policy);
+    }
+}
+
+#[test]
+fn test_errors_match_the_tree_parser() {
+    let inputs = ["[\"a\" \"b\"]", r#"{"a" "b"}"#, r#"{"a": 1 "key"}"#, r#""a" "b""#, r#"{"a": [1, "x\q"]}"#, r#"["abc"#];
+    for input in inputs {
+        assert_eq!(deserialize_borrowed(input).unwrap_err(), deserialize(input).unwrap_err(), "{}", input);
+    }
+    let err = deserialize_borrowed(r#"{"a": 1 "key"}"#).unwrap_err();
+    assert_eq!(err.found(), Some("`\"key\"`"));
+
+    let options = ParseOptions { duplicate_keys: DuplicateKeyPolicy::Error, ..ParseOptions::default() };
+    let input = r#"{"k": 1, "k": 2}"#;
+    let err = deserialize_borrowed_with(input, &options).unwrap_err();
+    assert_eq!(err, deserialize_with(input, &options).unwrap_err());
+    assert!(err.to_string().contains("\"k\""), "{}", err);
+
+    let options = ParseOptions { limits: Limits { max_string_bytes: 3, ..Limits::default() }, ..ParseOptions::default() };
+    for input in [r#"["abcd"]"#, r#"["ab\ncd"]"#, r#"{"abcd": 1}"#, r#"["abc"]"#] {
+        let expected = deserialize_with(input, &options).map(|_| ());
+        assert_eq!(deserialize_borrowed_with(input, &options).map(|_| ()), expected, "{}", input);
+    }
+    assert!(matches!(deserialize_borrowed_with(r#"["abcd"]"#, &options), Err(JsonError::LimitExceeded { .. })));
+}
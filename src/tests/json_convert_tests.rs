@@ -0,0 +1,70 @@
+use std::collections::{BTreeMap, HashMap};
+use crate::convert::{FromJson, FromJsonError, FromJsonErrorKind, ToJson};
+use crate::core::{JsonNumber, JsonValue};
+use crate::driver::deserialize;
+
+fn json(input: &str) -> JsonValue {
+    deserialize(input).unwrap()
+}
+
+#[test]
+fn test_primitives_round_trip() {
+    assert_eq!(true.to_json(), JsonValue::Bool(true));
+    assert_eq!((-3i8).to_json(), JsonValue::Number(JsonNumber::Integer(-3)));
+    assert_eq!(u64::MAX.to_json(), JsonValue::Number(JsonNumber::UnsignedInteger(u64::MAX)));
+    assert_eq!(u128::MAX.to_json(), JsonValue::Number(JsonNumber::from_lexeme(&u128::MAX.to_string()).unwrap()));
+    assert_eq!(u128::from_json(&u128::MAX.to_json()), Ok(u128::MAX));
+    assert_eq!(0.1f32.to_json(), JsonValue::Number(JsonNumber::Float(0.1)));
+    assert_eq!(f32::from_json(&json("0.1")), Ok(0.1));
+    assert_eq!("a\"b".to_json().to_json_string(), r#""a\"b""#);
+    assert_eq!(char::from_json(&json(r#""é""#)), Ok('é'));
+    assert_eq!(<()>::from_json(&JsonValue::Null), Ok(()));
+    assert_eq!(i64::from_json(&json("-0")), Ok(0));
+    assert_eq!(f64::from_json(&json("7")), Ok(7.0));
+}
+
+#[test]
+fn test_containers_round_trip() {
+    let value = json(r#"[[1, "a", null], [2, "b", true]]"#);
+    let rows = <Vec<(u8, String, Option<bool>)>>::from_json(&value).unwrap();
+    assert_eq!(rows, vec![(1, "a".to_string(), None), (2, "b".to_string(), Some(true))]);
+    assert_eq!(rows.to_json(), value);
+    assert_eq!([1, 2].to_json(), Box::new(vec![1, 2]).to_json());
+
+    let value = json(r#"{"2": [1.5], "10": []}"#);
+    let map = <BTreeMap<u32, Vec<f64>>>::from_json(&value).unwrap();
+    assert_eq!(map, BTreeMap::from([(2, vec![1.5]), (10, vec![])]));
+    assert_eq!(map.to_json().to_json_string(), r#"{"2":[1.5],"10":[]}"#);
+
+    let map = <HashMap<String, i32>>::from_json(&json(r#"{"a": 1, "a": 2}"#)).unwrap();
+    assert_eq!(map["a"], 2);
+}
+
+#[test]
+fn test_errors_carry_the_path() {
+    let value = json(r#"{"servers": [{"port": 80}, {"port": "80"}]}"#);
+    let err = <HashMap<String, Vec<HashMap<String, u16>>>>::from_json(&value).unwrap_err();
+    assert_eq!(err.kind(), &FromJsonErrorKind::InvalidType { expected: "u16", found: "a string" });
+    assert_eq!(err.path(), "$['servers'][1]['port']");
+    assert_eq!(err.to_string(), "Invalid type: found a string, expected u16 in $['servers'][1]['port']");
+
+    let err = <Vec<u8>>::from_json(&json("[1, 256]")).unwrap_err();
+    assert_eq!(err.to_string(), "Invalid number: found `256`, expected u8 in $[1]");
+    assert_eq!(i32::from_json(&json("1.5")).unwrap_err().to_string(), "Invalid number: found `1.5`, expected i32");
+    assert_eq!(<(u8, u8)>::from_json(&json("[1]")).unwrap_err().to_string(), "Invalid length: found 1 elements, expected 2");
+
+    let err = <BTreeMap<u8, bool>>::from_json(&json(r#"{"x": true}"#)).unwrap_err();
+    assert_eq!(err.to_string(), "Invalid key: found `x`, expected u8 in $['x']");
+    assert_eq!(String::from_json(&json("{}")).unwrap_err().path(), "$");
+}
+
+#[test]
+fn test_error_constructors() {
+    static VARIANTS: &[&str] = &["a", "b", "c"];
+    let err = FromJsonError::unknown_variant("d", VARIANTS).at_key("kind").at_index(0);
+    assert_eq!(err.to_string(), r#"Unknown variant `d`, expected `a`, `b` or `c` in $[0]['kind']"#);
+    assert_eq!(FromJsonError::missing_field("id").to_string(), "Missing field `id`");
+    assert_eq!(FromJsonError::custom("bad").at_key("x").to_string(), "bad in $['x']");
+    assert_eq!(<Option<u8>>::from_missing(), Some(None));
+    assert_eq!(u8::from_missing(), None);
+}
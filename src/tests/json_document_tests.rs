@@ -0,0 +1,68 @@
+use crate::core::{JsonNumber, JsonValue};
+use crate::driver::{deserialize, deserialize_document, deserialize_document_with, deserialize_with};
+use crate::options::{DuplicateKeyPolicy, Limits, ParseOptions};
+use crate::pointer::PointerError;
+
+#[test]
+fn test_to_value_matches_the_tree_parser() {
+    let input = include_str!("../../input.json");
+    assert_eq!(deserialize_document(input).unwrap().to_value(), deserialize(input).unwrap());
+
+    let options = ParseOptions { arbitrary_precision: true, ..ParseOptions::json5() };
+    let input = "{a: 'it\\'s', 'b': [1, -2.5, 0x10, 1e400, true, null, \"\", [[]], {}], c: {d: {e: -Infinity}}, // x\n}";
+    let document = deserialize_document_with(input, &options).unwrap();
+    assert_eq!(JsonValue::from(&document), deserialize_with(input, &options).unwrap());
+
+    for policy in [DuplicateKeyPolicy::First, DuplicateKeyPolicy::Last, DuplicateKeyPolicy::All] {
+        let options = ParseOptions { duplicate_keys: policy, ..ParseOptions::default() };
+        let input = r#"{"a": [1, {"x": 2}], "b": {"a": 2, "a": [3, 4], "c": 5}, "a\u0000": 4, "a": {"y": [6]}, "z": 7}"#;
+        let document = deserialize_document_with(input, &options).unwrap();
+        assert_eq!(document.to_value(), deserialize_with(input, &options).unwrap(), "{:?}", policy);
+    }
+}
+
+#[test]
+fn test_navigating_a_document() {
+    let input = r#"{"name": "Alice", "say": "a\"b", "tags": ["x", {"k": [1, 2]}, "yé"], "n": 18446744073709551615, "ok": true}"#;
+    let document = deserialize_document(input).unwrap();
+    let root = document.root();
+    assert!(root.is_object());
+    assert_eq!(root.len(), 5);
+    assert_eq!(root.get("name").and_then(|name| name.as_str()), Some("Alice"));
+    assert!(std::ptr::eq(root.get("name").unwrap().as_str().unwrap().as_ptr(), input[10..].as_ptr()));
+    assert_eq!(root.get("say").and_then(|say| say.as_str()), Some("a\"b"));
+    assert_eq!(root.get("n").and_then(|n| n.as_number()), Some(JsonNumber::UnsignedInteger(u64::MAX)));
+    assert_eq!(root.get("ok").and_then(|ok| ok.as_bool()), Some(true));
+    assert!(root.get("missing").is_none() && root.get_index(0).is_none());
+
+    // Elements after a container are reached by stepping over it.
+    let tags = root.get("tags").unwrap();
+    let names: Vec<_> = tags.elements().map(|tag| tag.as_str()).collect();
+    assert_eq!(names, [Some("x"), None, Some("yé")]);
+    assert_eq!(tags.get_index(2).and_then(|tag| tag.as_str()), Some("yé"));
+    assert_eq!(tags.get_index(1).and_then(|tag| tag.get("k")).map(|k| k.to_value()), Some(deserialize("[1, 2]").unwrap()));
+
+    let keys: Vec<_> = root.members().map(|(name, _)| name).collect();
+    assert_eq!(keys, ["name", "say", "tags", "n", "ok"]);
+    assert_eq!(root.pointer("/tags/1/k/1").ok().and_then(|n| n.as_i64()), Some(2));
+    assert!(matches!(root.pointer("/tags/01"), Err(PointerError::InvalidIndex { .. })));
+    assert!(matches!(root.pointer("/tags/3"), Err(PointerError::NotFound { .. })));
+    assert_eq!(format!("{:?}", tags), r#"["x", {"k": [Integer(1), Integer(2)]}, "yé"]"#);
+}
+
+#[test]
+fn test_errors_match_the_tree_parser() {
+    let inputs = ["[\"a\" \"b\"]", r#"{"a" "b"}"#, r#"{"a": 1 "key"}"#, r#""a" "b""#, r#"{"a": [1, "x\q"]}"#, r#"["abc"#, "[1,]"];
+    for input in inputs {
+        assert_eq!(deserialize_document(input).unwrap_err(), deserialize(input).unwrap_err(), "{}", input);
+    }
+
+    let options = ParseOptions { duplicate_keys: DuplicateKeyPolicy::Error, ..ParseOptions::default() };
+    let input = r#"{"k": 1, "k": 2}"#;
+    assert_eq!(deserialize_document_with(input, &options).unwrap_err(), deserialize_with(input, &options).unwrap_err());
+
+    let options = ParseOptions { limits: Limits { max_depth: 2, max_elements: 3, ..Limits::default() }, ..ParseOptions::default() };
+    for input in ["[[[]]]", "[1, 2, 3, 4]", r#"{"a": {"b": [1]}}"#] {
+        assert_eq!(deserialize_document_with(input, &options).unwrap_err(), deserialize_with(input, &options).unwrap_err());
+    }
+}
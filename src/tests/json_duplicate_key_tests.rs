@@ -0,0 +1,72 @@
+use crate::cli::parse_flags;
+use crate::core::{JsonError, JsonValue, Position};
+use crate::driver::{deserialize_reader_with, deserialize_recovering, deserialize_with};
+use crate::options::{DuplicateKeyPolicy, ParseOptions};
+use crate::stream::JsonReader;
+
+const INPUT: &str = r#"{"a": 1, "b": {"a": true}, "a": 2, "c": 3, "a": 4}"#;
+
+fn parse(input: &str, policy: DuplicateKeyPolicy) -> Result<JsonValue, JsonError> {
+    let options = ParseOptions { duplicate_keys: policy, ..ParseOptions::default() };
+    let value = deserialize_with(input, &options);
+    assert_eq!(deserialize_reader_with(input.as_bytes(), &options), value, "{}", input);
+    value
+}
+
+#[test]
+fn test_keep_all_by_default() {
+    let value = parse(INPUT, DuplicateKeyPolicy::All).unwrap();
+    assert_eq!(value.to_json_string(), r#"{"a":1,"b":{"a":true},"a":2,"c":3,"a":4}"#);
+    assert_eq!(ParseOptions::default().duplicate_keys, DuplicateKeyPolicy::All);
+}
+
+#[test]
+fn test_keep_first_or_last() {
+    let first = parse(INPUT, DuplicateKeyPolicy::First).unwrap();
+    assert_eq!(first.to_json_string(), r#"{"a":1,"b":{"a":true},"c":3}"#);
+
+    // The last value takes the place of the first member.
+    let last = parse(INPUT, DuplicateKeyPolicy::Last).unwrap();
+    assert_eq!(last.to_json_string(), r#"{"a":4,"b":{"a":true},"c":3}"#);
+}
+
+#[test]
+fn test_error_knows_both_locations() {
+    let err = parse("{\n  \"a\": 1,\n  \"b\": {\"a\": 2, \"a\": 3}\n}", DuplicateKeyPolicy::Error).unwrap_err();
+    let JsonError::DuplicateKey { line, col, first, .. } = err else {
+        panic!("expected a duplicate key error, got {:?}", err);
+    };
+    assert_eq!((line, col), (3, 17));
+    assert_eq!(first, Position { offset: 20, line: 3, col: 9 });
+    assert_eq!((err.found(), err.path()), (Some("`\"a\"`"), Some("$['b']")));
+    assert_eq!(err.to_string(), "Duplicate key at 3:17: found `\"a\"`, first defined at 3:9 in $['b']");
+
+    let unquoted = ParseOptions { duplicate_keys: DuplicateKeyPolicy::Error, ..ParseOptions::json5() };
+    assert!(matches!(deserialize_with("{a: 1, 'a': 2}", &unquoted), Err(JsonError::DuplicateKey { col: 8, .. })));
+}
+
+#[test]
+fn test_reader_reports_duplicates() {
+    let options = ParseOptions { duplicate_keys: DuplicateKeyPolicy::Error, ..ParseOptions::default() };
+    let err = JsonReader::with_options(INPUT, &options).find_map(Result::err).unwrap();
+    assert_eq!(Err(err), deserialize_with(INPUT, &options));
+
+    let options = ParseOptions { duplicate_keys: DuplicateKeyPolicy::Last, ..ParseOptions::default() };
+    assert!(JsonReader::with_options(INPUT, &options).all(|event| event.is_ok()));
+}
+
+#[test]
+fn test_recovering_lists_every_duplicate() {
+    let options = ParseOptions { duplicate_keys: DuplicateKeyPolicy::Error, ..ParseOptions::default() };
+    let (value, errors) = deserialize_recovering(INPUT, &options);
+    let columns: Vec<usize> = errors.iter().map(JsonError::col).collect();
+    assert_eq!(columns, [28, 44]);
+    assert_eq!(value.to_json_string(), r#"{"a":1,"b":{"a":true},"c":3}"#);
+}
+
+#[test]
+fn test_duplicate_keys_cli_flag() {
+    let (options, _) = parse_flags(["--duplicate-keys=last".to_string()].into_iter()).unwrap();
+    assert_eq!(options.parse.duplicate_keys, DuplicateKeyPolicy::Last);
+    assert!(parse_flags(["--duplicate-keys=maybe".to_string()].into_iter()).is_err());
+}
@@ -0,0 +1,118 @@
+use crate::core::JsonError;
+use crate::driver::{deserialize, deserialize_reader, deserialize_with};
+use crate::options::ParseOptions;
+use crate::stream::JsonReader;
+
+// Error from the tree parser, checked against the streaming reader, which
+// must fail at the same place and in the same value.
+fn error(input: &str) -> JsonError {
+    let err = deserialize(input).unwrap_err();
+    assert_eq!(deserialize_reader(input.as_bytes()).unwrap_err(), err, "{}", input);
+    let streamed = JsonReader::new(input).find_map(Result::err).unwrap();
+    assert_eq!((streamed.offset(), streamed.path()), (err.offset(), err.path()), "{}", input);
+    err
+}
+
+#[test]
+fn test_found_and_expected() {
+    let err = error("[1 2]");
+    assert!(matches!(err, JsonError::InvalidToken { line: 1, col: 4, .. }));
+    assert_eq!(err.offset(), 3);
+    assert_eq!(err.found(), Some("`2`"));
+    assert_eq!(err.expected(), ["`,`", "`]`"]);
+
+    let err = error(r#"{"a" 1}"#);
+    assert_eq!((err.found(), err.expected()), (Some("`1`"), &["`:`"][..]));
+
+    let err = error(r#"{"a": 1,}"#);
+    assert_eq!((err.found(), err.expected()), (Some("`}`"), &["a string"][..]));
+
+    let err = error("[1, 2");
+    assert!(matches!(err, JsonError::UnexpectedEof { .. }));
+    assert_eq!((err.found(), err.expected()), (None, &["`,`", "`]`"][..]));
+
+    assert_eq!(error("{} x").expected(), ["end of input"]);
+    assert_eq!(error("@").found(), Some("`@`"));
+    assert_eq!(error("[\u{7}]").found(), Some("U+0007"));
+}
+
+#[test]
+fn test_lexer_errors_carry_details() {
+    let err = error("[trueish]");
+    assert_eq!((err.found(), err.expected()), (Some("`trueish`"), &["`true`"][..]));
+
+    let err = error(r#"["a\qb"]"#);
+    assert!(matches!(err, JsonError::InvalidEscapeSequence { line: 1, col: 4, .. }));
+    assert_eq!(err.found(), Some(r"`\q`"));
+
+    assert_eq!(error(r#""\u12x4""#).found(), Some(r"`\u12x`"));
+    assert_eq!(error(r#""\ud800""#).expected(), ["a surrogate pair"]);
+    assert_eq!(error("\"abc").expected(), ["`\"`"]);
+
+    let err = error("[01]");
+    assert!(matches!(err, JsonError::InvalidNumber { line: 1, col: 2, .. }));
+    assert_eq!(err.found(), Some("`01`"));
+    assert_eq!(error("1.").expected(), ["a digit"]);
+    assert_eq!(error("1e999").found(), Some("`1e999`"));
+}
+
+#[test]
+fn test_path_names_the_value_being_read() {
+    assert_eq!(error("tru").path(), Some("$"));
+    assert_eq!(error(r#"{"a": [1, 2, tru]}"#).path(), Some("$['a'][2]"));
+    assert_eq!(error(r#"{"a": {"b": [[], [x]]}}"#).path(), Some("$['a']['b'][1][0]"));
+    assert_eq!(error(r#"{"it's": @}"#).path(), Some(r"$['it\'s']"));
+    // Between members or elements the error belongs to the container.
+    assert_eq!(error("[1 2]").path(), Some("$"));
+    assert_eq!(error(r#"{"a": [1] "b": 2}"#).path(), Some("$"));
+    assert_eq!(error(r#"[{"a": 1} x]"#).path(), Some("$"));
+    // Lexer errors are attributed to the value the bad token starts.
+    assert_eq!(error(r#"["ok", "\q"]"#).path(), Some("$[1]"));
+    assert_eq!(error(r#"{"a" 1}"#).path(), Some("$['a']"));
+    assert_eq!(error(r#"{"a": [1,"#).path(), Some("$['a'][1]"));
+}
+
+#[test]
+fn test_display() {
+    assert_eq!(
+        error(r#"{"a": [1, 2, tru]}"#).to_string(),
+        "Invalid token at 1:14: found `tru`, expected `true` in $['a'][2]"
+    );
+    assert_eq!(error("[").to_string(), "Unexpected end of input at 1:2: expected a value or `]` in $[0]");
+}
+
+#[test]
+fn test_render() {
+    let source = "{\n  \"a\": [1, 2, tru],\n  \"b\": 1\n}\n";
+    let rendered = error(source).render(source, "config.json");
+    assert_eq!(
+        rendered,
+        "error: invalid token: found `tru`, expected `true`\n \
+         --> config.json:2:15\n  \
+         |\n\
+         2 |   \"a\": [1, 2, tru],\n  \
+         |               ^^^\n  \
+         = note: while reading $['a'][2]\n"
+    );
+
+    let source = "\t[1 2]";
+    assert!(error(source).render(source, "in").ends_with("1 | \t[1 2]\n  | \t   ^\n"));
+}
+
+#[test]
+fn test_render_long_line() {
+    let source = format!("[{}, x, {}]", "1, ".repeat(100) + "1", "2, ".repeat(100) + "2");
+    let rendered = error(&source).render(&source, "long.json");
+    let lines: Vec<&str> = rendered.lines().collect();
+    assert!(lines[3].starts_with("1 | ...") && lines[3].ends_with("..."), "{}", rendered);
+    assert_eq!(lines[4].find('^'), lines[3].find(" x,").map(|i| i + 1));
+}
+
+#[test]
+fn test_render_without_excerpt() {
+    let err = deserialize_with("[1, NaN]", &ParseOptions::default()).unwrap_err();
+    assert_eq!(
+        err.render("", "gone.json"),
+        "error: invalid token: found `NaN`, expected a value\n --> gone.json:1:5\n  = note: while reading $[1]\n"
+    );
+}
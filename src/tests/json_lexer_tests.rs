@@ -0,0 +1,64 @@
+use crate::core::{JsonError, Limit, Position};
+use crate::driver::{deserialize, deserialize_reader, deserialize_reader_with, deserialize_with};
+use crate::options::{Limits, ParseOptions};
+use crate::scan;
+use crate::stream::JsonReader;
+
+#[test]
+fn test_scanners_match_byte_loops() {
+    // Every length and stop position either side of the eight-byte words.
+    for len in 0..20 {
+        for stop in 0..=len {
+            let mut bytes = vec![b'a'; len];
+            bytes.iter_mut().take(stop).for_each(|b| *b = 0xC3);
+            for end in [b'"', b'\\', b'\n', 0x1F, b'\''] {
+                let mut bytes = bytes.clone();
+                if stop < len {
+                    bytes[stop] = end;
+                }
+                let expected = bytes.iter().take_while(|&&b| b != b'"' && b != b'\\' && b >= 0x20).count();
+                assert_eq!(scan::string_run(&bytes, b'"'), expected, "{:?}", bytes);
+            }
+
+            let mut spaces = vec![b' '; len];
+            let mut digits = vec![b'7'; len];
+            if stop < len {
+                (spaces[stop], digits[stop]) = (b'x', b':');
+            }
+            assert_eq!(scan::whitespace_run(&spaces), stop, "{:?}", spaces);
+            assert_eq!(scan::digit_run(&digits), stop, "{:?}", digits);
+        }
+    }
+    assert_eq!(scan::whitespace_run(b"  \r\n\t   \t  x"), 11);
+    assert_eq!(scan::digit_run(b"0123456789/"), 10);
+}
+
+#[test]
+fn test_positions_are_counted_on_demand() {
+    let input = "{\n  \"é☺\": [1,\n  tru]\n}";
+    let err = deserialize(input).unwrap_err();
+    assert_eq!((err.offset(), err.line(), err.col()), (19, 3, 3));
+    assert_eq!(deserialize_reader(input.as_bytes()).unwrap_err(), err);
+
+    // Far enough in that the reader has discarded the lines before.
+    let input = format!("[{}\"é\", x]", "1,\n".repeat(20_000));
+    let err = deserialize(&input).unwrap_err();
+    assert_eq!((err.line(), err.col()), (20_001, 6));
+    assert_eq!(deserialize_reader(input.as_bytes()).unwrap_err(), err);
+
+    let positions: Vec<_> = JsonReader::new("[1,\n \"ü\",\n  {}]").map(|event| event.unwrap().1).collect();
+    let expected = [(0, 1, 1), (1, 1, 2), (5, 2, 2), (13, 3, 3), (14, 3, 4), (15, 3, 5)];
+    let expected: Vec<_> = expected.iter().map(|&(offset, line, col)| Position { offset, line, col }).collect();
+    assert_eq!(positions, expected);
+}
+
+#[test]
+fn test_input_limit_stops_at_the_character_crossing_it() {
+    let options = ParseOptions { limits: Limits { max_input_bytes: 3, ..Limits::default() }, ..ParseOptions::default() };
+    for input in ["[\"é\"]", "[[é]]", "\"aé\""] {
+        let err = deserialize_with(input, &options).unwrap_err();
+        assert!(matches!(err, JsonError::LimitExceeded { limit: Limit::InputBytes, .. }), "{:?}", err);
+        assert_eq!(err.offset(), input.find('é').unwrap(), "{}", input);
+        assert_eq!(deserialize_reader_with(input.as_bytes(), &options).unwrap_err(), err);
+    }
+}
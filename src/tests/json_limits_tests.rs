@@ -0,0 +1,92 @@
+use crate::core::{JsonError, Limit};
+use crate::driver::{deserialize_reader_with, deserialize_recovering, deserialize_with};
+use crate::options::{Limits, ParseOptions};
+use crate::stream::JsonReader;
+
+fn options(limits: Limits) -> ParseOptions {
+    ParseOptions { limits, ..ParseOptions::default() }
+}
+
+// The error every front end must report for `input` under `limits`.
+fn exceeded(input: &str, limits: Limits) -> JsonError {
+    let options = options(limits);
+    let err = deserialize_with(input, &options).unwrap_err();
+    assert_eq!(deserialize_reader_with(input.as_bytes(), &options).unwrap_err(), err, "{}", input);
+    let streamed = JsonReader::with_options(input, &options).find_map(Result::err).unwrap();
+    assert_eq!(streamed, err, "{}", input);
+    assert_eq!(deserialize_recovering(input, &options).1, std::slice::from_ref(&err), "{}", input);
+    err
+}
+
+fn limit_of(err: &JsonError) -> (Limit, usize) {
+    match *err {
+        JsonError::LimitExceeded { limit, max, .. } => (limit, max),
+        ref other => panic!("expected a limit error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_default_depth_limit() {
+    let nested = |depth: usize| format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+    assert!(deserialize_with(&nested(Limits::DEFAULT_MAX_DEPTH), &ParseOptions::default()).is_ok());
+
+    let err = exceeded(&nested(Limits::DEFAULT_MAX_DEPTH + 1), Limits::default());
+    assert_eq!(limit_of(&err), (Limit::Depth, 512));
+    assert_eq!(err.offset(), 512);
+
+    // Far deeper than the stack could take without the limit.
+    let err = exceeded(&"[{\"a\":".repeat(100_000), Limits::default());
+    assert_eq!(limit_of(&err), (Limit::Depth, 512));
+}
+
+#[test]
+fn test_depth_counts_objects_and_arrays() {
+    let limits = Limits { max_depth: 2, ..Limits::default() };
+    assert!(deserialize_with(r#"{"a": [1, 2], "b": {}}"#, &options(limits)).is_ok());
+    let err = exceeded(r#"{"a": [{}]}"#, limits);
+    assert_eq!((limit_of(&err), err.offset(), err.path()), ((Limit::Depth, 2), 7, Some("$['a'][0]")));
+}
+
+#[test]
+fn test_size_limits() {
+    let limits = Limits { max_input_bytes: 8, ..Limits::default() };
+    assert!(deserialize_with("[1, 2.5]", &options(limits)).is_ok());
+    assert_eq!(limit_of(&exceeded("[1, 2.50]", limits)), (Limit::InputBytes, 8));
+
+    // Strings are measured once unescaped; member names count too.
+    let limits = Limits { max_string_bytes: 3, ..Limits::default() };
+    assert!(deserialize_with(r#"{"abc": "ABC"}"#, &options(limits)).is_ok());
+    let err = exceeded(r#"["abc", "abcd"]"#, limits);
+    assert_eq!((limit_of(&err), err.offset()), ((Limit::StringBytes, 3), 8));
+    assert_eq!(exceeded(r#"{"keys": 1}"#, limits).offset(), 1);
+
+    let limits = Limits { max_number_length: 4, ..Limits::default() };
+    assert!(deserialize_with("[-1.5, 1e10]", &options(limits)).is_ok());
+    let err = exceeded("[1, 12345]", limits);
+    assert_eq!((limit_of(&err), err.offset()), ((Limit::NumberLength, 4), 4));
+    let json5 = ParseOptions { limits, ..ParseOptions::json5() };
+    assert!(deserialize_with("0xFF", &json5).is_ok());
+    assert!(matches!(deserialize_with("0xFFF", &json5), Err(JsonError::LimitExceeded { .. })));
+}
+
+#[test]
+fn test_count_limits() {
+    let limits = Limits { max_elements: 2, ..Limits::default() };
+    assert!(deserialize_with(r#"[[1, 2], {"a": 1, "b": 2}]"#, &options(limits)).is_ok());
+    let err = exceeded("[1, 2, 3]", limits);
+    assert_eq!((limit_of(&err), err.offset(), err.path()), ((Limit::Elements, 2), 7, Some("$[2]")));
+    let err = exceeded(r#"{"a": 1, "b": 2, "c": 3}"#, limits);
+    assert_eq!((err.offset(), err.path()), (17, Some("$")));
+
+    let limits = Limits { max_nodes: 4, ..Limits::default() };
+    assert!(deserialize_with(r#"{"a": [1, 2]}"#, &options(limits)).is_ok());
+    let err = exceeded(r#"{"a": [1, 2], "b": null}"#, limits);
+    assert_eq!((limit_of(&err), err.offset(), err.path()), ((Limit::Nodes, 4), 19, Some("$['b']")));
+}
+
+#[test]
+fn test_limit_error_message() {
+    let err = exceeded("[1, 2, 3]", Limits { max_elements: 2, ..Limits::default() });
+    assert_eq!(err.to_string(), "Array/object length limit of 2 exceeded at 1:8 in $[2]");
+    assert_eq!(err.render("[1, 2, 3]", "<input>").lines().next(), Some("error: array/object length limit of 2 exceeded"));
+}
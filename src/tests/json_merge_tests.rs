@@ -0,0 +1,75 @@
+use crate::driver::deserialize;
+use crate::merge::create_merge_patch;
+use crate::value::values_equal;
+
+fn merge(target: &str, patch: &str) -> String {
+    let mut value = deserialize(target).unwrap();
+    value.merge_patch(&deserialize(patch).unwrap());
+    value.to_json_string()
+}
+
+#[test]
+fn test_rfc_appendix_examples() {
+    let cases = [
+        (r#"{"a":"b"}"#, r#"{"a":"c"}"#, r#"{"a":"c"}"#),
+        (r#"{"a":"b"}"#, r#"{"b":"c"}"#, r#"{"a":"b","b":"c"}"#),
+        (r#"{"a":"b"}"#, r#"{"a":null}"#, r#"{}"#),
+        (r#"{"a":"b","b":"c"}"#, r#"{"a":null}"#, r#"{"b":"c"}"#),
+        (r#"{"a":["b"]}"#, r#"{"a":"c"}"#, r#"{"a":"c"}"#),
+        (r#"{"a":"c"}"#, r#"{"a":["b"]}"#, r#"{"a":["b"]}"#),
+        (r#"{"a":{"b":"c"}}"#, r#"{"a":{"b":"d","c":null}}"#, r#"{"a":{"b":"d"}}"#),
+        (r#"{"a":[{"b":"c"}]}"#, r#"{"a":[1]}"#, r#"{"a":[1]}"#),
+        (r#"["a","b"]"#, r#"["c","d"]"#, r#"["c","d"]"#),
+        (r#"{"a":"b"}"#, r#"["c"]"#, r#"["c"]"#),
+        (r#"{"a":"foo"}"#, r#"null"#, r#"null"#),
+        (r#"{"a":"foo"}"#, r#""bar""#, r#""bar""#),
+        (r#"{"e":null}"#, r#"{"a":1}"#, r#"{"e":null,"a":1}"#),
+        (r#"[1,2]"#, r#"{"a":"b","c":null}"#, r#"{"a":"b"}"#),
+        (r#"{}"#, r#"{"a":{"bb":{"ccc":null}}}"#, r#"{"a":{"bb":{}}}"#),
+    ];
+    for (target, patch, expected) in cases {
+        assert_eq!(merge(target, patch), expected, "{} + {}", target, patch);
+    }
+}
+
+#[test]
+fn test_merge_preserves_key_order() {
+    assert_eq!(
+        merge(r#"{"x":1,"y":2,"z":3}"#, r#"{"new":0,"y":{"k":true},"x":null}"#),
+        r#"{"y":{"k":true},"z":3,"new":0}"#
+    );
+}
+
+#[test]
+fn test_create_merge_patch_round_trip() {
+    let from = deserialize(include_str!("../../input.json")).unwrap();
+    let mut to = from.clone();
+    to.pointer_set("/config/logging/level", deserialize(r#""info""#).unwrap()).unwrap();
+    to.pointer_remove("/flags").unwrap();
+    to.pointer_set("/extra", deserialize("[1,2]").unwrap()).unwrap();
+
+    let patch = create_merge_patch(&from, &to);
+    assert_eq!(patch.to_json_string(), r#"{"config":{"logging":{"level":"info"}},"extra":[1,2],"flags":null}"#);
+
+    let mut patched = from.clone();
+    patched.merge_patch(&patch);
+    assert_eq!(patched.to_json_string(), to.to_json_string());
+}
+
+#[test]
+fn test_create_merge_patch_with_repeated_keys() {
+    let cases = [
+        (r#"{"a":2}"#, r#"{"a":1,"a":2}"#, "{}"),
+        (r#"{"a":1,"a":2}"#, r#"{"a":2}"#, "{}"),
+        (r#"{"a":1,"a":2,"b":0}"#, r#"{"b":0}"#, r#"{"a":null}"#),
+        (r#"{"a":{"c":1},"a":{"c":2}}"#, r#"{"a":{"c":1},"a":{"c":3}}"#, r#"{"a":{"c":3}}"#),
+    ];
+    for (from, to, expected) in cases {
+        let (from, to) = (deserialize(from).unwrap(), deserialize(to).unwrap());
+        let patch = create_merge_patch(&from, &to);
+        assert_eq!(patch.to_json_string(), expected);
+        let mut patched = from.clone();
+        patched.merge_patch(&patch);
+        assert!(values_equal(&patched, &to), "{}", patched.to_json_string());
+    }
+}
@@ -1,5 +1,9 @@
-use crate::core::JsonNumber;
+use std::cmp::Ordering;
+use crate::core::{JsonError, JsonNumber, JsonValue};
+use crate::driver::{deserialize, deserialize_with, serialize};
+use crate::options::ParseOptions;
 use crate::parser::NumberParser;
+use crate::value::{compare_numbers, values_equal};
 
 #[cfg(test)]
 #[test]
@@ -54,10 +58,128 @@ fn test_from_str_simple() {
     assert_eq!(u_val, JsonNumber::UnsignedInteger(18446744073709551615));
 
     // Float parsing
-    let float_val = NumberParser::parse("3.14").unwrap();
-    assert_eq!(float_val, JsonNumber::Float(3.14));
+    let float_val = NumberParser::parse("2.5").unwrap();
+    assert_eq!(float_val, JsonNumber::Float(2.5));
 
     // Scientific notation
     let sci_val = NumberParser::parse("1e10").unwrap();
     assert_eq!(sci_val, JsonNumber::Float(1e10));
 }
+
+fn lossless(input: &str) -> JsonValue {
+    deserialize_with(input, &ParseOptions { arbitrary_precision: true, ..ParseOptions::default() }).unwrap()
+}
+
+#[test]
+fn test_arbitrary_precision_round_trip() {
+    let input = r#"{"big":12345678901234567890123,"fine":0.1000000000000000000001,"exp":1E+400,"small":42,"neg":-7}"#;
+    let value = lossless(input);
+    assert_eq!(value["big"], JsonValue::Number(JsonNumber::from_lexeme("12345678901234567890123").unwrap()));
+    assert_eq!(value["small"], JsonValue::Number(JsonNumber::Integer(42)));
+    assert_eq!(serialize(&value), input);
+    // Without the option the same document is rounded.
+    assert_ne!(serialize(&deserialize(r#"[0.1000000000000000000001]"#).unwrap()), "[0.1000000000000000000001]");
+}
+
+#[test]
+fn test_raw_json5_numbers_are_normalized() {
+    let options = ParseOptions { arbitrary_precision: true, ..ParseOptions::json5() };
+    let value = deserialize_with("[+1.5, .5, -.5, 5., 5.e3]", &options).unwrap();
+    assert_eq!(serialize(&value), "[1.5,0.5,-0.5,5.0,5.0e3]");
+}
+
+#[test]
+fn test_raw_conversions() {
+    let raw = |s: &str| JsonNumber::from_lexeme(s).unwrap();
+    assert_eq!(raw("170141183460469231731687303715884105727").to_i128(), Some(i128::MAX));
+    assert_eq!(raw("170141183460469231731687303715884105728").to_i128(), None);
+    assert_eq!(raw("1.5e1").to_i128(), Some(15));
+    assert_eq!(raw("1.5").to_i128(), None);
+    assert_eq!(raw("-12e2").as_i64(), Some(-1200));
+    assert_eq!(raw("18446744073709551616").as_u64(), None);
+    assert_eq!(JsonNumber::Float(1e20).to_i128(), Some(100_000_000_000_000_000_000));
+    assert_eq!(JsonNumber::Float(0.5).to_i128(), None);
+
+    assert!(raw("1.5").is_f64() && raw("1e400").is_f64() && raw("2E3").is_f64());
+    assert!(raw("1.5e1").is_f64() && raw("1.5e1").is_i64());
+    assert!(!raw("18446744073709551616").is_f64());
+    assert!(lossless("[0.1000000000000000000001]")[0].is_f64());
+    assert!(!lossless("[123456789012345678901234567890]")[0].is_f64());
+
+    assert_eq!(raw("1.5e-3").to_decimal_string().as_deref(), Some("0.0015"));
+    assert_eq!(raw("-12.3400").to_decimal_string().as_deref(), Some("-12.34"));
+    assert_eq!(raw("-0.0").to_decimal_string().as_deref(), Some("0"));
+    assert_eq!(raw("25E+3").to_decimal_string().as_deref(), Some("25000"));
+    assert_eq!(raw("1e100000").to_decimal_string(), None);
+    assert_eq!(JsonNumber::Float(f64::NAN).to_decimal_string(), None);
+    assert_eq!(JsonNumber::Float(1e-7).to_decimal_string().as_deref(), Some("0.0000001"));
+
+    assert_eq!(raw("0.1000000000000000000001").to_f64_lossy(), 0.1);
+    assert_eq!(raw("1e400").to_f64_lossy(), f64::INFINITY);
+}
+
+#[test]
+fn test_from_lexeme_checks_the_grammar() {
+    for text in ["abc", "01", "1.", "-", "1e", ".5", "+1", "1 ", ""] {
+        assert!(
+            matches!(JsonNumber::from_lexeme(text), Err(JsonError::InvalidNumber { .. })),
+            "{:?} should be rejected",
+            text
+        );
+    }
+    let number = JsonNumber::from_lexeme("-12.50e300").unwrap();
+    assert_eq!(number.to_string(), "-12.50e300");
+    assert_eq!(serialize(&JsonValue::Number(number)), "-12.50e300");
+}
+
+#[test]
+fn test_raw_numbers_compare_exactly() {
+    let a = lossless("0.1000000000000000000001");
+    let b = lossless("0.1000000000000000000002");
+    assert!(!values_equal(&a, &b));
+    assert_eq!(compare_numbers(a.as_number().unwrap(), b.as_number().unwrap()), Some(Ordering::Less));
+    assert!(values_equal(&lossless("1.0e2"), &JsonValue::Number(JsonNumber::Integer(100))));
+    assert!(values_equal(&lossless("1.50"), &JsonValue::Number(JsonNumber::Float(1.5))));
+    assert_eq!(
+        compare_numbers(&JsonNumber::from_lexeme("-1e400").unwrap(), &JsonNumber::Integer(i64::MIN)),
+        Some(Ordering::Less)
+    );
+}
+
+#[test]
+fn test_display_float_exponent_notation() {
+    assert_eq!(JsonNumber::Float(1e300).to_string(), "1e300");
+    assert_eq!(JsonNumber::Float(-2.5e-300).to_string(), "-2.5e-300");
+    assert_eq!(JsonNumber::Float(1e21).to_string(), "1e21");
+    assert_eq!(JsonNumber::Float(1e20).to_string(), "100000000000000000000");
+    assert_eq!(JsonNumber::Float(1.5e-8).to_string(), "1.5e-8");
+    assert_eq!(JsonNumber::Float(1e-6).to_string(), "0.000001");
+    assert_eq!(JsonNumber::Float(0.1).to_string(), "0.1");
+    assert_eq!(JsonNumber::Float(-0.0).to_string(), "-0.0");
+}
+
+#[test]
+fn test_display_float_round_trips() {
+    for f in [0.1, 1.0 / 3.0, 5e-324, f64::MAX, f64::MIN_POSITIVE, 123456.789e-20, 2f64.powi(60)] {
+        let text = JsonNumber::Float(f).to_string();
+        assert_eq!(text.parse::<f64>().unwrap(), f, "{}", text);
+    }
+}
+
+#[test]
+fn test_negative_zero_round_trips() {
+    let value = JsonValue::Array(vec![JsonValue::Number(JsonNumber::Float(-0.0))]);
+    assert_eq!(serialize(&value), "[-0.0]");
+    let back = deserialize(&serialize(&value)).unwrap();
+    let JsonValue::Number(JsonNumber::Float(zero)) = back[0] else { panic!("{:?}", back) };
+    assert!(zero == 0.0 && zero.is_sign_negative());
+}
+
+#[test]
+fn test_display_float_alternate_keeps_point() {
+    assert_eq!(format!("{:#}", JsonNumber::Float(42.0)), "42.0");
+    assert_eq!(format!("{:#}", JsonNumber::Float(-0.0)), "-0.0");
+    assert_eq!(format!("{:#}", JsonNumber::Float(1.5)), "1.5");
+    assert_eq!(format!("{:#}", JsonNumber::Float(1e300)), "1e300");
+    assert_eq!(format!("{:#}", JsonNumber::Integer(42)), "42");
+}
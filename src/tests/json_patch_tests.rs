@@ -0,0 +1,167 @@
+use crate::core::{JsonNumber, JsonValue};
+use crate::driver::deserialize;
+use crate::patch::{JsonPatch, PatchError, PatchOperation};
+use crate::pointer::{JsonPointer, PointerError};
+use crate::value::values_equal;
+
+fn apply(document: &str, patch: &str) -> Result<String, PatchError> {
+    let mut value = deserialize(document).unwrap();
+    JsonPatch::parse(patch)?.apply(&mut value)?;
+    Ok(value.to_json_string())
+}
+
+fn pointer(text: &str) -> JsonPointer {
+    JsonPointer::parse(text).unwrap()
+}
+
+// Diffs `from` against `to`, checks the exact operations and applies them back.
+fn assert_diff(from: &str, to: &str, expected: Vec<PatchOperation>) {
+    let (from, to) = (deserialize(from).unwrap(), deserialize(to).unwrap());
+    let patch = JsonPatch::diff(&from, &to);
+    assert_eq!(patch.operations, expected);
+    let mut patched = from.clone();
+    patch.apply(&mut patched).unwrap();
+    assert!(values_equal(&patched, &to), "{}", patched.to_json_string());
+}
+
+#[test]
+fn test_rfc_examples() {
+    assert_eq!(apply(r#"{"foo":"bar"}"#, r#"[{"op":"add","path":"/baz","value":"qux"}]"#).unwrap(), r#"{"foo":"bar","baz":"qux"}"#);
+    assert_eq!(apply(r#"{"foo":["bar","baz"]}"#, r#"[{"op":"add","path":"/foo/1","value":"qux"}]"#).unwrap(), r#"{"foo":["bar","qux","baz"]}"#);
+    assert_eq!(apply(r#"{"baz":"qux","foo":"bar"}"#, r#"[{"op":"remove","path":"/baz"}]"#).unwrap(), r#"{"foo":"bar"}"#);
+    assert_eq!(apply(r#"{"foo":["bar","qux","baz"]}"#, r#"[{"op":"remove","path":"/foo/1"}]"#).unwrap(), r#"{"foo":["bar","baz"]}"#);
+    assert_eq!(apply(r#"{"baz":"qux","foo":"bar"}"#, r#"[{"op":"replace","path":"/baz","value":"boo"}]"#).unwrap(), r#"{"baz":"boo","foo":"bar"}"#);
+    assert_eq!(
+        apply(r#"{"foo":{"bar":"baz","waldo":"fred"},"qux":{"corge":"grault"}}"#, r#"[{"op":"move","from":"/foo/waldo","path":"/qux/thud"}]"#).unwrap(),
+        r#"{"foo":{"bar":"baz"},"qux":{"corge":"grault","thud":"fred"}}"#
+    );
+    assert_eq!(apply(r#"{"foo":["all","grass","cows","eat"]}"#, r#"[{"op":"move","from":"/foo/1","path":"/foo/3"}]"#).unwrap(), r#"{"foo":["all","cows","eat","grass"]}"#);
+    assert_eq!(apply(r#"{"foo":["bar"]}"#, r#"[{"op":"add","path":"/foo/-","value":["abc","def"]}]"#).unwrap(), r#"{"foo":["bar",["abc","def"]]}"#);
+    assert_eq!(apply(r#"{"a":{"b":1}}"#, r#"[{"op":"copy","from":"/a","path":"/c"}]"#).unwrap(), r#"{"a":{"b":1},"c":{"b":1}}"#);
+    assert_eq!(apply(r#"{"q":{"bar":2}}"#, r#"[{"op":"add","path":"/","value":1}]"#).unwrap(), r#"{"q":{"bar":2},"":1}"#);
+}
+
+#[test]
+fn test_test_operation_compares_semantically() {
+    let doc = r#"{"baz":"qux","foo":["a",2,"c"],"n":{"x":1,"y":2.0}}"#;
+    let patch = r#"[
+        {"op":"test","path":"/baz","value":"qux"},
+        {"op":"test","path":"/foo/1","value":2.0},
+        {"op":"test","path":"/n","value":{"y":2,"x":1}}
+    ]"#;
+    assert_eq!(apply(doc, patch).unwrap(), deserialize(doc).unwrap().to_json_string());
+    assert_eq!(
+        apply(doc, r#"[{"op":"test","path":"/baz","value":"bar"}]"#),
+        Err(PatchError::TestFailed { index: 0, path: "/baz".to_string() })
+    );
+}
+
+#[test]
+fn test_failed_patch_leaves_document_untouched() {
+    let mut value = deserialize(r#"{"a":1}"#).unwrap();
+    let patch = JsonPatch::parse(r#"[
+        {"op":"add","path":"/b","value":2},
+        {"op":"remove","path":"/a"},
+        {"op":"test","path":"/b","value":3}
+    ]"#).unwrap();
+    assert!(patch.apply(&mut value).is_err());
+    assert_eq!(value.to_json_string(), r#"{"a":1}"#);
+}
+
+#[test]
+fn test_errors() {
+    assert!(matches!(JsonPatch::parse("[{"), Err(PatchError::Parse(_))));
+    assert!(matches!(JsonPatch::parse(r#"{"op":"add"}"#), Err(PatchError::InvalidOperation { index: 0, .. })));
+    assert!(matches!(JsonPatch::parse(r#"[{"op":"remove","path":"/a"},{"op":"add","path":"/a"}]"#), Err(PatchError::InvalidOperation { index: 1, .. })));
+    assert!(matches!(JsonPatch::parse(r#"[{"op":"frobnicate","path":"/a"}]"#), Err(PatchError::InvalidOperation { .. })));
+    assert!(matches!(JsonPatch::parse(r#"[{"op":"move","path":"/a"}]"#), Err(PatchError::InvalidOperation { .. })));
+    assert!(matches!(JsonPatch::parse(r#"[{"op":"remove","path":"a"}]"#), Err(PatchError::Pointer { error: PointerError::Malformed { .. }, .. })));
+
+    assert!(matches!(apply(r#"{"foo":"bar"}"#, r#"[{"op":"add","path":"/baz/bat","value":"qux"}]"#), Err(PatchError::Pointer { .. })));
+    assert!(matches!(apply(r#"[1]"#, r#"[{"op":"add","path":"/2","value":0}]"#), Err(PatchError::Pointer { .. })));
+    assert!(matches!(apply(r#"{"a":{"b":1}}"#, r#"[{"op":"move","from":"/a","path":"/a/c"}]"#), Err(PatchError::InvalidOperation { .. })));
+}
+
+#[test]
+fn test_diff_round_trip() {
+    let cases = [
+        (r#"{"a":1,"b":[1,2,3],"c":{"d":true}}"#, r#"{"a":2,"b":[1,9,2,3],"e":null,"c":{}}"#),
+        (r#"[1,2,3,4,5]"#, r#"[1,5]"#),
+        (r#"[{"x":1},{"x":2}]"#, r#"[{"x":1},{"x":3},{"y":4}]"#),
+        (r#"{"a":[1]}"#, r#""scalar""#),
+    ];
+    for (from, to) in cases {
+        let from = deserialize(from).unwrap();
+        let to = deserialize(to).unwrap();
+        let patch = JsonPatch::diff(&from, &to);
+        let mut patched = from.clone();
+        JsonPatch::from_value(&patch.to_value()).unwrap().apply(&mut patched).unwrap();
+        assert!(values_equal(&patched, &to), "{}", patch.to_value().to_json_string());
+    }
+}
+
+#[test]
+fn test_diff_is_minimal_for_simple_edits() {
+    let from = deserialize(include_str!("../../input.json")).unwrap();
+    let mut to = from.clone();
+    to.pointer_set("/config/network/ports/1", JsonValue::Null).unwrap();
+    to.pointer_remove("/users/0").unwrap();
+    assert_eq!(
+        JsonPatch::diff(&from, &to).to_value().to_json_string(),
+        r#"[{"op":"replace","path":"/config/network/ports/1","value":null},{"op":"remove","path":"/users/0"}]"#
+    );
+    assert!(JsonPatch::diff(&from, &from).operations.is_empty());
+}
+
+#[test]
+fn test_objects_with_duplicate_keys_compare_by_last_value() {
+    let doubled = deserialize(r#"{"x":{"a":2,"a":2}}"#).unwrap();
+    let other = deserialize(r#"{"x":{"a":2,"b":3}}"#).unwrap();
+    assert!(!values_equal(&doubled, &other));
+    assert!(!values_equal(&other, &doubled));
+    assert!(values_equal(&deserialize(r#"{"a":1,"a":2}"#).unwrap(), &deserialize(r#"{"a":2}"#).unwrap()));
+
+    assert_eq!(JsonPatch::diff(&doubled, &other).to_value().to_json_string(), r#"[{"op":"add","path":"/x/b","value":3}]"#);
+    let test = |document, value| apply(document, &format!(r#"[{{"op":"test","path":"","value":{}}}]"#, value));
+    assert!(matches!(test(r#"{"a":2,"a":2}"#, r#"{"a":2,"b":3}"#), Err(PatchError::TestFailed { .. })));
+    assert!(matches!(test(r#"{"a":2,"b":3}"#, r#"{"a":2,"a":2}"#), Err(PatchError::TestFailed { .. })));
+    assert!(test(r#"{"a":1,"a":2}"#, r#"{"a":2}"#).is_ok());
+
+    let number = |n| JsonValue::Number(JsonNumber::Integer(n));
+    let cases = [
+        (r#"{"a":1,"a":2,"b":0}"#, r#"{"b":0}"#, vec![PatchOperation::Remove { path: pointer("/a") }]),
+        (r#"{"a":1,"a":2}"#, r#"{"a":3,"a":3}"#, vec![PatchOperation::Replace { path: pointer("/a"), value: number(3) }]),
+        (r#"{"a":{"c":1},"a":{"c":2}}"#, r#"{"a":{"c":2,"d":4}}"#, vec![PatchOperation::Add { path: pointer("/a/d"), value: number(4) }]),
+    ];
+    for (from, to, expected) in cases {
+        assert_diff(from, to, expected);
+    }
+}
+
+#[test]
+fn test_array_diff_is_minimal() {
+    let number = |n| JsonValue::Number(JsonNumber::Integer(n));
+    assert_diff("[1,2,3,4]", "[2,3,4,5]", vec![
+        PatchOperation::Remove { path: pointer("/0") },
+        PatchOperation::Add { path: pointer("/3"), value: number(5) },
+    ]);
+    assert_diff("[1,2,3]", "[0,1,2,3,4]", vec![
+        PatchOperation::Add { path: pointer("/0"), value: number(0) },
+        PatchOperation::Add { path: pointer("/4"), value: number(4) },
+    ]);
+    assert_diff("[1,2,3,4,5]", "[1,5]", vec![
+        PatchOperation::Remove { path: pointer("/1") },
+        PatchOperation::Remove { path: pointer("/1") },
+        PatchOperation::Remove { path: pointer("/1") },
+    ]);
+    // Aligned changes are diffed in place rather than removed and added.
+    assert_diff(r#"[1,{"x":1},3]"#, r#"[1,{"x":2},3,4]"#, vec![
+        PatchOperation::Replace { path: pointer("/1/x"), value: number(2) },
+        PatchOperation::Add { path: pointer("/3"), value: number(4) },
+    ]);
+    assert_diff("[1,2]", "[3,4,5]", vec![
+        PatchOperation::Replace { path: pointer("/0"), value: number(3) },
+        PatchOperation::Replace { path: pointer("/1"), value: number(4) },
+        PatchOperation::Add { path: pointer("/2"), value: number(5) },
+    ]);
+}
@@ -0,0 +1,121 @@
+use crate::core::JsonValue;
+use crate::driver::deserialize;
+use crate::jsonpath::{JsonPath, PathError};
+
+// The example document from RFC 9535 section 1.5.
+fn bookstore() -> JsonValue {
+    deserialize(r#"{ "store": {
+        "book": [
+          { "category": "reference", "author": "Nigel Rees", "title": "Sayings of the Century", "price": 8.95 },
+          { "category": "fiction", "author": "Evelyn Waugh", "title": "Sword of Honour", "price": 12.99 },
+          { "category": "fiction", "author": "Herman Melville", "title": "Moby Dick", "isbn": "0-553-21311-3", "price": 8.99 },
+          { "category": "fiction", "author": "J. R. R. Tolkien", "title": "The Lord of the Rings", "isbn": "0-395-19395-8", "price": 22.99 }
+        ],
+        "bicycle": { "color": "red", "price": 399 }
+    } }"#).unwrap()
+}
+
+fn paths(value: &JsonValue, query: &str) -> Vec<String> {
+    value.query(query).unwrap().iter().map(|n| n.normalized_path()).collect()
+}
+
+fn values(value: &JsonValue, query: &str) -> Vec<String> {
+    value.query(query).unwrap().iter().map(|n| n.value().to_json_string()).collect()
+}
+
+#[test]
+fn test_rfc_bookstore_examples() {
+    let doc = bookstore();
+    assert_eq!(values(&doc, "$.store.book[*].author").len(), 4);
+    assert_eq!(values(&doc, "$..author").len(), 4);
+    assert_eq!(values(&doc, "$.store.*").len(), 2);
+    assert_eq!(values(&doc, "$.store..price"), ["8.95", "12.99", "8.99", "22.99", "399"]);
+    assert_eq!(paths(&doc, "$..book[2]"), ["$['store']['book'][2]"]);
+    assert_eq!(paths(&doc, "$..book[-1]"), ["$['store']['book'][3]"]);
+    assert_eq!(paths(&doc, "$..book[0,1]"), ["$['store']['book'][0]", "$['store']['book'][1]"]);
+    assert_eq!(paths(&doc, "$..book[:2]"), ["$['store']['book'][0]", "$['store']['book'][1]"]);
+    assert_eq!(values(&doc, "$..book[?@.isbn].title"), ["\"Moby Dick\"", "\"The Lord of the Rings\""]);
+    assert_eq!(values(&doc, "$..book[?@.price<10].title"), ["\"Sayings of the Century\"", "\"Moby Dick\""]);
+    assert_eq!(values(&doc, "$..*").len(), 27);
+}
+
+#[test]
+fn test_input_json_queries() {
+    let doc = deserialize(include_str!("../../input.json")).unwrap();
+    assert_eq!(values(&doc, "$.users[*].roles[*]").len(), 7);
+    assert_eq!(values(&doc, "$.users[?(@.id > 2)].name"), ["\"Carol\"", "\"Dave\""]);
+    assert_eq!(values(&doc, "$.users[?@.roles[?@ == 'tester']].id"), ["2", "4"]);
+    assert_eq!(values(&doc, "$.config.network.ports[?@ >= 8081 && @ != 8082]"), ["8081"]);
+    assert_eq!(values(&doc, "$.users[?count(@.roles[*]) == 1].name"), ["\"Bob\""]);
+    assert_eq!(values(&doc, "$.users[?length(@.name) == 3].name"), ["\"Bob\""]);
+    assert_eq!(values(&doc, "$.users[?!(@.id < 4)].name"), ["\"Dave\""]);
+}
+
+#[test]
+fn test_slices() {
+    let doc = deserialize(r#"["a","b","c","d","e","f","g"]"#).unwrap();
+    assert_eq!(values(&doc, "$[1:3]"), ["\"b\"", "\"c\""]);
+    assert_eq!(values(&doc, "$[5:]"), ["\"f\"", "\"g\""]);
+    assert_eq!(values(&doc, "$[1:5:2]"), ["\"b\"", "\"d\""]);
+    assert_eq!(values(&doc, "$[5:1:-2]"), ["\"f\"", "\"d\""]);
+    assert_eq!(values(&doc, "$[::-1]").len(), 7);
+    assert_eq!(values(&doc, "$[::0]").len(), 0);
+    assert_eq!(values(&doc, "$[-100:100]").len(), 7);
+}
+
+#[test]
+fn test_comparison_semantics() {
+    let doc = deserialize(r#"[{"a":1},{"a":1.0},{"a":"1"},{"a":[1,{"b":2}]},{"b":null},{}]"#).unwrap();
+    assert_eq!(paths(&doc, "$[?@.a == 1]"), ["$[0]", "$[1]"]);
+    assert_eq!(paths(&doc, "$[?@.a == @.missing]"), ["$[4]", "$[5]"]);
+    assert_eq!(paths(&doc, "$[?@.b == null]"), ["$[4]"]);
+    assert_eq!(paths(&doc, "$[?@.a <= '1']"), ["$[2]"]);
+    assert_eq!(paths(&doc, "$[?@.a == $[3].a]"), ["$[3]"]);
+    assert_eq!(paths(&doc, "$[?@.a != 1]").len(), 4);
+}
+
+#[test]
+fn test_name_escapes_and_normalized_paths() {
+    let doc = deserialize(r#"{"it's":{"a\nb":true},"☺":1}"#).unwrap();
+    assert_eq!(paths(&doc, r#"$["it's"]['a\nb']"#), [r"$['it\'s']['a\nb']"]);
+    assert_eq!(paths(&doc, "$.☺"), ["$['☺']"]);
+    assert_eq!(doc.query("$['it\\'s'].*").unwrap()[0].pointer().to_string(), "/it's/a\nb");
+}
+
+#[test]
+fn test_match_and_search() {
+    let doc = bookstore();
+    assert_eq!(values(&doc, "$..book[?match(@.author, '[A-Z][a-z]+ [A-Z][a-z]+')].price"), ["8.95", "12.99", "8.99"]);
+    assert_eq!(values(&doc, "$..book[?search(@.author, 'R')].author"), ["\"Nigel Rees\"", "\"J. R. R. Tolkien\""]);
+    assert_eq!(values(&doc, "$..book[?!search(@.title, '[Tt]he')].title").len(), 2);
+    assert_eq!(values(&doc, "$..book[?match(@.isbn, '[0-9]-[0-9]{3}-[0-9]{5}-[0-9]')].price"), ["8.99", "22.99"]);
+    assert_eq!(values(&doc, "$..book[?match(@.category, 'fic|ref') || match(@.price, '8.*')]").len(), 0);
+
+    let doc = deserialize(r#"[{"s":"ab","p":"a."},{"s":"a\nb","p":"a."},{"s":"aab","p":"(a|b)+"},{"s":"x","p":"["}]"#).unwrap();
+    assert_eq!(paths(&doc, "$[?match(@.s, @.p)]"), ["$[0]", "$[2]"]);
+    assert_eq!(paths(&doc, "$[?search(@.s, @.p)]"), ["$[0]", "$[2]"]);
+    assert_eq!(paths(&doc, "$[?match(@.s, 'a{2}b')]"), ["$[2]"]);
+    assert_eq!(paths(&doc, "$[?search(@.s, '^')]").len(), 0);
+    assert_eq!(paths(&doc, r"$[?search(@.s, '\\n|x')]"), ["$[1]", "$[3]"]);
+    // A pattern that is not an I-Regexp matches nothing.
+    assert_eq!(paths(&doc, "$[?search(@.s, 'a**')]").len(), 0);
+    assert_eq!(paths(&doc, "$[?match(@.s, '[b-a]')]").len(), 0);
+}
+
+#[test]
+fn test_invalid_queries() {
+    for query in ["", "users", "$.", "$[", "$[01]", "$[-0]", "$.a ", " $", "$['a'", "$[?@.a == ]", "$[9007199254740992]"] {
+        assert!(matches!(JsonPath::parse(query), Err(PathError::Syntax { .. })), "{:?}", query);
+    }
+    assert!(matches!(JsonPath::parse("$[?@.* == 1]"), Err(PathError::TypeError { .. })));
+    assert!(matches!(JsonPath::parse("$[?1]"), Err(PathError::TypeError { .. })));
+    assert!(matches!(JsonPath::parse("$[?foo(@)]"), Err(PathError::UnknownFunction { .. })));
+    assert!(matches!(JsonPath::parse("$[?match(@, 'a') == true]"), Err(PathError::TypeError { .. })));
+    assert!(matches!(JsonPath::parse("$[?length(search(@, 'a')) > 0]"), Err(PathError::TypeError { .. })));
+    assert!(matches!(JsonPath::parse("$[?match(@.*, 'a')]"), Err(PathError::TypeError { .. })));
+    assert!(matches!(JsonPath::parse("$[?match(@)]"), Err(PathError::Syntax { .. })));
+    assert_eq!(
+        JsonPath::parse(r"$[?match(@, '\\p{Lu}')]"),
+        Err(PathError::Unsupported { offset: 12, reason: "Unicode category escapes are not supported" })
+    );
+}
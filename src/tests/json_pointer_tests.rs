@@ -0,0 +1,98 @@
+use crate::core::{JsonNumber, JsonValue};
+use crate::driver::deserialize;
+use crate::pointer::{JsonPointer, PointerError};
+
+fn int(i: i64) -> JsonValue {
+    JsonValue::Number(JsonNumber::Integer(i))
+}
+
+// The example document from RFC 6901 section 5.
+fn rfc_document() -> JsonValue {
+    deserialize(r#"{
+        "foo": ["bar", "baz"],
+        "": 0,
+        "a/b": 1,
+        "c%d": 2,
+        "e^f": 3,
+        "g|h": 4,
+        "i\\j": 5,
+        "k\"l": 6,
+        " ": 7,
+        "m~n": 8
+    }"#).unwrap()
+}
+
+#[test]
+fn test_rfc_examples() {
+    let doc = rfc_document();
+    assert_eq!(doc.pointer("").unwrap(), &doc);
+    assert_eq!(doc.pointer("/foo").unwrap(), &doc["foo"]);
+    assert_eq!(doc.pointer("/foo/0").unwrap().as_str(), Some("bar"));
+    assert_eq!(doc.pointer("/").unwrap(), &int(0));
+    assert_eq!(doc.pointer("/a~1b").unwrap(), &int(1));
+    assert_eq!(doc.pointer("/c%d").unwrap(), &int(2));
+    assert_eq!(doc.pointer("/e^f").unwrap(), &int(3));
+    assert_eq!(doc.pointer("/g|h").unwrap(), &int(4));
+    assert_eq!(doc.pointer("/i\\j").unwrap(), &int(5));
+    assert_eq!(doc.pointer("/k\"l").unwrap(), &int(6));
+    assert_eq!(doc.pointer("/ ").unwrap(), &int(7));
+    assert_eq!(doc.pointer("/m~0n").unwrap(), &int(8));
+}
+
+#[test]
+fn test_malformed_pointers() {
+    let doc = rfc_document();
+    assert!(matches!(doc.pointer("foo"), Err(PointerError::Malformed { .. })));
+    assert!(matches!(doc.pointer("/m~2n"), Err(PointerError::Malformed { .. })));
+    assert!(matches!(doc.pointer("/m~"), Err(PointerError::Malformed { .. })));
+}
+
+#[test]
+fn test_array_index_rules() {
+    let doc = rfc_document();
+    assert!(matches!(doc.pointer("/foo/01"), Err(PointerError::InvalidIndex { .. })));
+    assert!(matches!(doc.pointer("/foo/-"), Err(PointerError::InvalidIndex { .. })));
+    assert!(matches!(doc.pointer("/foo/+1"), Err(PointerError::InvalidIndex { .. })));
+    assert!(matches!(doc.pointer("/foo/2"), Err(PointerError::NotFound { .. })));
+    assert!(matches!(doc.pointer("/foo/0/x"), Err(PointerError::NotFound { .. })));
+}
+
+#[test]
+fn test_pointer_mut() {
+    let mut doc = rfc_document();
+    *doc.pointer_mut("/foo/1").unwrap() = JsonValue::Null;
+    assert!(doc["foo"][1].is_null());
+}
+
+#[test]
+fn test_pointer_set() {
+    let mut doc = deserialize(r#"{"a":{"b":[1,2]}}"#).unwrap();
+    assert_eq!(doc.pointer_set("/a/b/0", int(9)).unwrap(), Some(int(1)));
+    assert_eq!(doc.pointer_set("/a/b/-", int(3)).unwrap(), None);
+    assert_eq!(doc.pointer_set("/a/b/3", int(4)).unwrap(), None);
+    assert_eq!(doc.pointer_set("/a/c", JsonValue::Bool(true)).unwrap(), None);
+    assert_eq!(doc.to_json_string(), r#"{"a":{"b":[9,2,3,4],"c":true}}"#);
+
+    assert!(matches!(doc.pointer_set("/a/b/9", int(0)), Err(PointerError::NotFound { .. })));
+    assert!(matches!(doc.pointer_set("/x/y", int(0)), Err(PointerError::NotFound { .. })));
+
+    assert_eq!(doc.pointer_set("", JsonValue::Null).unwrap().map(|v| v.is_object()), Some(true));
+    assert!(doc.is_null());
+}
+
+#[test]
+fn test_pointer_remove() {
+    let mut doc = deserialize(r#"{"a":[1,2,3],"b":true}"#).unwrap();
+    assert_eq!(doc.pointer_remove("/a/0").unwrap(), int(1));
+    assert_eq!(doc.pointer_remove("/b").unwrap(), JsonValue::Bool(true));
+    assert_eq!(doc.to_json_string(), r#"{"a":[2,3]}"#);
+    assert!(matches!(doc.pointer_remove("/b"), Err(PointerError::NotFound { .. })));
+    assert_eq!(doc.pointer_remove(""), Err(PointerError::RootNotRemovable));
+}
+
+#[test]
+fn test_pointer_display_round_trip() {
+    let pointer = JsonPointer::parse("/a~1b/m~0n/0").unwrap();
+    assert_eq!(pointer.tokens(), ["a/b", "m~n", "0"]);
+    assert_eq!(pointer.to_string(), "/a~1b/m~0n/0");
+}
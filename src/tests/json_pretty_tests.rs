@@ -0,0 +1,64 @@
+use crate::cli::parse_flags;
+use crate::driver::deserialize;
+use crate::writer::{NewlineStyle, PrettyConfig};
+
+#[test]
+fn test_default_config_matches_pretty() {
+    let value = deserialize(include_str!("../../input.json")).unwrap();
+    assert_eq!(value.to_json_string_with(&PrettyConfig::default()), value.to_json_string_pretty());
+}
+
+#[test]
+fn test_indent_newline_and_colon() {
+    let value = deserialize(r#"{"a":[1],"b":{}}"#).unwrap();
+    let config = PrettyConfig {
+        indent: "\t".to_string(),
+        newline: NewlineStyle::CrLf,
+        space_after_colon: false,
+        trailing_newline: true,
+        ..PrettyConfig::default()
+    };
+    assert_eq!(value.to_json_string_with(&config), "{\r\n\t\"a\":[\r\n\t\t1\r\n\t],\r\n\t\"b\":{}\r\n}\r\n");
+}
+
+#[test]
+fn test_inline_width() {
+    let value = deserialize(r#"{"ports":[8080,8081,8082],"point":{"x":10,"y":20},"long":["abcdefgh","ijklmnop","qrstuvwx"]}"#).unwrap();
+    let config = PrettyConfig { indent: "  ".to_string(), max_inline_width: 20, ..PrettyConfig::default() };
+    assert_eq!(
+        value.to_json_string_with(&config),
+        "{\n  \"ports\": [8080, 8081, 8082],\n  \"point\": {\"x\": 10, \"y\": 20},\n  \"long\": [\n    \"abcdefgh\",\n    \"ijklmnop\",\n    \"qrstuvwx\"\n  ]\n}"
+    );
+
+    // The whole document fits, so it is written on one line.
+    let small = deserialize("[1,[2,3],{}]").unwrap();
+    assert_eq!(small.to_json_string_with(&config), "[1, [2, 3], {}]");
+}
+
+#[test]
+fn test_sort_keys() {
+    let value = deserialize(r#"{"b":1,"a":{"d":2,"c":3}}"#).unwrap();
+    let config = PrettyConfig { sort_keys: true, max_inline_width: 80, ..PrettyConfig::default() };
+    assert_eq!(value.to_json_string_with(&config), r#"{"a": {"c": 3, "d": 2}, "b": 1}"#);
+}
+
+#[test]
+fn test_cli_flags() {
+    let args = ["pointer", "--indent=2", "in.json", "--crlf", "--inline-width=60", "/a", "--sort-keys", "--trailing-newline", "--no-space-after-colon"];
+    let (options, positional) = parse_flags(args.iter().map(|s| s.to_string())).unwrap();
+    assert_eq!(positional, ["pointer", "in.json", "/a"]);
+    assert_eq!(
+        options.pretty,
+        PrettyConfig {
+            indent: "  ".to_string(),
+            newline: NewlineStyle::CrLf,
+            space_after_colon: false,
+            max_inline_width: 60,
+            trailing_newline: true,
+            sort_keys: true,
+        }
+    );
+    assert!(parse_flags(["--indent=x".to_string()].into_iter()).is_err());
+    assert!(parse_flags(["--nope".to_string()].into_iter()).is_err());
+    assert_eq!(parse_flags(["--indent=tab".to_string()].into_iter()).unwrap().0.pretty.indent, "\t");
+}
@@ -0,0 +1,118 @@
+use crate::cli::run_check;
+use crate::core::{JsonError, JsonNumber, JsonValue};
+use crate::driver::{deserialize_reader_recovering, deserialize_recovering, deserialize_with};
+use crate::options::ParseOptions;
+
+// Recovers `input` from a string and from a reader, which must agree.
+fn recover(input: &str) -> (JsonValue, Vec<JsonError>) {
+    let recovered = deserialize_recovering(input, &ParseOptions::default());
+    assert_eq!(deserialize_reader_recovering(input.as_bytes(), &ParseOptions::default()), recovered, "{}", input);
+    recovered
+}
+
+fn positions(errors: &[JsonError]) -> Vec<(usize, usize)> {
+    errors.iter().map(|e| (e.line(), e.col())).collect()
+}
+
+#[test]
+fn test_valid_documents_have_no_errors() {
+    for input in [r#"{"a": [1, 2.5, "x"], "b": {"c": null}}"#, "[]", "{}", "  true ", include_str!("../../input.json")] {
+        let (value, errors) = recover(input);
+        assert!(errors.is_empty(), "{}: {:?}", input, errors);
+        assert_eq!(value, deserialize_with(input, &ParseOptions::default()).unwrap());
+    }
+}
+
+#[test]
+fn test_first_error_matches_strict_parser() {
+    for input in ["", "[1,]", "{\"a\"}", "{\"a\":1,}", "[1 2]", "{1:2}", "[}", "1 2", "[1", "{\"a\":", "[tru]", "[\"\\q\"]", "[01]", "\"abc"] {
+        let (_, errors) = recover(input);
+        assert_eq!(errors.first(), deserialize_with(input, &ParseOptions::default()).err().as_ref(), "{}", input);
+    }
+}
+
+#[test]
+fn test_reports_every_error() {
+    let input = "{\n  \"a\": 1.,\n  \"b\": [\"x\" \"y\", tru],\n  \"c\": {\"d\": },\n  \"e\": \"\\q\",\n  \"f\": [1, 2}\n  \"g\": [1, 2,]\n}";
+    let (value, errors) = recover(input);
+    assert_eq!(positions(&errors), [(2, 8), (3, 13), (3, 18), (4, 14), (5, 9), (6, 13), (7, 3), (7, 14)]);
+    let paths: Vec<&str> = errors.iter().filter_map(JsonError::path).collect();
+    assert_eq!(paths, ["$['a']", "$['b']", "$['b'][2]", "$['c']['d']", "$['e']", "$['f']", "$", "$['g'][2]"]);
+    assert_eq!(
+        value,
+        JsonValue::Object(vec![
+            ("a".to_string(), JsonValue::Null),
+            ("b".to_string(), JsonValue::Array(vec![
+                JsonValue::String("x".to_string()),
+                JsonValue::String("y".to_string()),
+                JsonValue::Null,
+            ])),
+            ("c".to_string(), JsonValue::Object(vec![("d".to_string(), JsonValue::Null)])),
+            ("e".to_string(), JsonValue::String(String::new())),
+            ("f".to_string(), JsonValue::Array(vec![JsonValue::Number(JsonNumber::Integer(1)), JsonValue::Number(JsonNumber::Integer(2))])),
+            ("g".to_string(), JsonValue::Array(vec![JsonValue::Number(JsonNumber::Integer(1)), JsonValue::Number(JsonNumber::Integer(2))])),
+        ])
+    );
+}
+
+#[test]
+fn test_resynchronises_after_garbage() {
+    let (value, errors) = recover("[1, @ # [2, 3], 4, : 5]");
+    assert_eq!(positions(&errors), [(1, 5), (1, 20)]);
+    assert_eq!(value.to_json_string(), "[1,null,4,null]");
+
+    let (value, errors) = recover(r#"{"a" 1, "b": 2 "c": 3, 4: 5, "d": 6}"#);
+    assert_eq!(errors.len(), 3);
+    assert_eq!(value.to_json_string(), r#"{"a":1,"b":2,"c":3,"d":6}"#);
+
+    let (value, errors) = recover("[[1, 2}, {\"a\": 1]]");
+    assert_eq!(errors.len(), 2);
+    assert_eq!(value.to_json_string(), r#"[[1,2],{"a":1}]"#);
+}
+
+#[test]
+fn test_end_of_input_is_reported_once() {
+    let (value, errors) = recover(r#"{"a": [1, {"b": "#);
+    assert_eq!(errors.len(), 1);
+    assert!(matches!(errors[0], JsonError::UnexpectedEof { .. }));
+    assert_eq!(value.to_json_string(), r#"{"a":[1,{"b":null}]}"#);
+
+    let (value, errors) = recover(r#"["a", "unterminated]"#);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].offset(), 6);
+    assert_eq!(value.to_json_string(), r#"["a"]"#);
+
+    let (value, errors) = recover("");
+    assert_eq!((value, errors.len()), (JsonValue::Null, 1));
+}
+
+#[test]
+fn test_invalid_utf8_stops_the_reader() {
+    let (value, errors) = deserialize_reader_recovering(&b"[1, \"\xff\", 2, x]"[..], &ParseOptions::default());
+    assert!(matches!(errors[..], [JsonError::InvalidUtf8 { .. }]));
+    assert_eq!(value.to_json_string(), "[1]");
+}
+
+#[test]
+fn test_recovering_honours_options() {
+    let input = "{unquoted: [1, 2,], /* comment */ 'single': 0x10, bad: 1 2}";
+    let (value, errors) = deserialize_recovering(input, &ParseOptions::json5());
+    assert_eq!(positions(&errors), [(1, 58)]);
+    assert_eq!(value.to_json_string(), r#"{"unquoted":[1,2],"single":16,"bad":1}"#);
+    assert_eq!(recover(input).1.len(), 3);
+}
+
+#[test]
+fn test_check_fails_when_it_finds_problems() {
+    let path = std::env::temp_dir().join(format!("basic_json_parser_check_{}.json", std::process::id()));
+    let path = path.to_str().unwrap();
+    let options = ParseOptions::default();
+    std::fs::write(path, r#"{"a": 1, "b": [2, 3]}"#).unwrap();
+    assert!(run_check(path, &options).is_ok());
+    std::fs::write(path, r#"{"a": 1, "a": 2}"#).unwrap();
+    let err = run_check(path, &options).unwrap_err();
+    assert!(err.to_string().ends_with(": 1 problem found\n"), "{}", err);
+    std::fs::write(path, r#"{"a": 1, "a": [2,, 3]}"#).unwrap();
+    assert!(run_check(path, &options).unwrap_err().to_string().ends_with(": 2 problems found\n"));
+    std::fs::remove_file(path).unwrap();
+}
@@ -0,0 +1,149 @@
+use std::collections::BTreeMap;
+use serde::{Deserialize, Serialize};
+use crate::core::{JsonError, JsonNumber, JsonValue};
+use crate::de::{from_reader, from_str, from_str_with};
+use crate::driver::{deserialize, deserialize_with};
+use crate::options::{DuplicateKeyPolicy, Limits, LoneSurrogatePolicy, ParseOptions};
+use crate::ser::{to_string, to_string_pretty, to_string_with_options};
+use crate::writer::{NonFinitePolicy, WriteOptions};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Config {
+    name: String,
+    port: u16,
+    ratio: f32,
+    tags: Vec<String>,
+    owner: Option<String>,
+    limits: BTreeMap<u32, i64>,
+    shapes: Vec<Shape>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+enum Shape {
+    Dot,
+    Circle(f64),
+    Line(i32, i32),
+    Rect { w: u8, h: u8 },
+}
+
+fn config() -> Config {
+    Config {
+        name: "svc \"a\"".to_string(),
+        port: 8080,
+        ratio: 0.1,
+        tags: vec!["x".to_string(), "y".to_string()],
+        owner: None,
+        limits: BTreeMap::from([(1, -5), (20, i64::MAX)]),
+        shapes: vec![Shape::Dot, Shape::Circle(1.5), Shape::Line(-1, 2), Shape::Rect { w: 3, h: 4 }],
+    }
+}
+
+const CONFIG_JSON: &str = concat!(
+    r#"{"name":"svc \"a\"","port":8080,"ratio":0.1,"tags":["x","y"],"owner":null,"#,
+    r#""limits":{"1":-5,"20":9223372036854775807},"#,
+    r#""shapes":["Dot",{"Circle":1.5},{"Line":[-1,2]},{"Rect":{"w":3,"h":4}}]}"#,
+);
+
+#[test]
+fn test_struct_round_trip() {
+    assert_eq!(to_string(&config()).unwrap(), CONFIG_JSON);
+    assert_eq!(from_str::<Config>(CONFIG_JSON).unwrap(), config());
+    assert_eq!(from_reader::<_, Config>(CONFIG_JSON.as_bytes()).unwrap(), config());
+
+    let pretty = to_string_pretty(&config()).unwrap();
+    assert_eq!(pretty, deserialize(CONFIG_JSON).unwrap().to_json_string_pretty());
+    assert_eq!(from_str::<Config>(&pretty).unwrap(), config());
+}
+
+#[test]
+fn test_value_and_number_round_trip() {
+    let input = r#"{"a": [1, -2, 18446744073709551615, 2.5, "s", null, true], "a": {}}"#;
+    let value: JsonValue = from_str(input).unwrap();
+    assert_eq!(value, deserialize(input).unwrap());
+    assert_eq!(to_string(&value).unwrap(), value.to_json_string());
+
+    // Raw numbers pass through untouched, in both directions.
+    let options = ParseOptions { arbitrary_precision: true, ..ParseOptions::default() };
+    let input = "[123456789012345678901234567890, 1.000000000000000000001]";
+    let value: JsonValue = from_str_with(input, &options).unwrap();
+    assert_eq!(value, deserialize_with(input, &options).unwrap());
+    assert_eq!(to_string(&value).unwrap(), "[123456789012345678901234567890,1.000000000000000000001]");
+    let numbers: Vec<JsonNumber> = from_str_with(input, &options).unwrap();
+    assert_eq!(numbers[0], JsonNumber::from_lexeme("123456789012345678901234567890").unwrap());
+    // Text standing in for a raw number must still be a number.
+    let forged = format!(r#"{{"{}": "1, 2"}}"#, crate::ser::RAW_NUMBER);
+    assert!(from_str::<JsonValue>(&forged).is_err());
+
+    // Typed fields read raw numbers as usual.
+    let big: (u128, f64) = from_str_with(input, &options).unwrap();
+    assert_eq!(big, (123456789012345678901234567890, 1.0));
+    assert_eq!(to_string(&big.0).unwrap(), "123456789012345678901234567890");
+}
+
+#[test]
+fn test_lone_surrogates_cannot_be_preserved_through_serde() {
+    let options = ParseOptions { lone_surrogates: LoneSurrogatePolicy::Preserve, ..ParseOptions::default() };
+    let input = r#"["\ud800"]"#;
+    assert!(matches!(from_str_with::<JsonValue>(input, &options), Err(JsonError::InvalidEscapeSequence { .. })));
+    let value = deserialize_with(input, &options).unwrap();
+    assert!(to_string(&value).is_err());
+}
+
+#[test]
+fn test_errors_point_at_the_value() {
+    let err = from_str::<Config>("{\n  \"name\": \"a\",\n  \"port\": \"80\"\n}").unwrap_err();
+    assert!(matches!(err, JsonError::Custom { line: 3, col: 11, .. }), "{:?}", err);
+    assert_eq!(err.path(), Some("$['port']"));
+    assert_eq!(
+        err.to_string(),
+        "Invalid type: string \"80\", expected u16 at 3:11 in $['port']"
+    );
+
+    let err = from_str::<Vec<Shape>>(r#"[{"Rect": {"w": 1}}]"#).unwrap_err();
+    assert_eq!(err.to_string(), "Missing field `h` at 1:18 in $[0]['Rect']");
+
+    // A container the visitor rejects outright is blamed as a whole.
+    let err = from_str::<Vec<String>>(r#"["a", {"b": 1}]"#).unwrap_err();
+    assert_eq!((err.offset(), err.path()), (6, Some("$[1]")));
+
+    assert_eq!(from_str::<Shape>(r#""Square""#).unwrap_err().col(), 1);
+    assert_eq!(from_str::<u8>("256").unwrap_err().summary(), "invalid value: integer `256`, expected u8");
+}
+
+#[test]
+fn test_syntax_errors_match_the_parser() {
+    for input in ["[1 2]", r#"{"a": [1, 2, tru]}"#, "[1,]", "{} x", "[", r#"{"a" 1}"#] {
+        assert_eq!(from_str::<JsonValue>(input).unwrap_err(), deserialize(input).unwrap_err(), "{}", input);
+    }
+    let err = from_str::<(u8, u8)>("[1, 2, 3]").unwrap_err();
+    assert_eq!((err.found(), err.expected()), (Some("`3`"), &["`]`"][..]));
+}
+
+#[test]
+fn test_options_apply() {
+    let input = "{port: 1, tags: ['a',], name: 'n', ratio: .5, owner: null, limits: {}, shapes: [],}";
+    let config: Config = from_str_with(input, &ParseOptions::json5()).unwrap();
+    assert_eq!((config.port, config.tags, config.ratio), (1, vec!["a".to_string()], 0.5));
+
+    let options = ParseOptions { limits: Limits { max_depth: 2, ..Limits::default() }, ..ParseOptions::default() };
+    let err = from_str_with::<Vec<Vec<Vec<u8>>>>("[[[]]]", &options).unwrap_err();
+    assert!(matches!(err, JsonError::LimitExceeded { .. }), "{:?}", err);
+
+    let options = ParseOptions { duplicate_keys: DuplicateKeyPolicy::Error, ..ParseOptions::default() };
+    let err = from_str_with::<BTreeMap<String, u8>>(r#"{"a": 1, "a": 2}"#, &options).unwrap_err();
+    assert!(matches!(err, JsonError::DuplicateKey { line: 1, col: 10, .. }), "{:?}", err);
+    let last: BTreeMap<String, u8> = from_str(r#"{"a": 1, "a": 2}"#).unwrap();
+    assert_eq!(last["a"], 2);
+}
+
+#[test]
+fn test_serializer_output() {
+    assert_eq!(to_string(&(1u64 << 63, -1i128 << 70, 'c', ())).unwrap(), r#"[9223372036854775808,-1180591620717411303424,"c",null]"#);
+    assert_eq!(to_string(&BTreeMap::from([(true, 1)])).unwrap(), r#"{"true":1}"#);
+    assert!(to_string(&BTreeMap::from([(vec![1], 1)])).unwrap_err().to_string().starts_with("Map key must be"));
+
+    let options = WriteOptions { non_finite: NonFinitePolicy::Error, ..WriteOptions::default() };
+    let err = to_string_with_options(&[1.0, f64::NAN], None, &options).unwrap_err();
+    assert_eq!(err, JsonError::NonFiniteNumber { pointer: "/1".to_string() });
+    assert_eq!(to_string(&[f64::INFINITY]).unwrap(), "[null]");
+}
@@ -0,0 +1,74 @@
+use std::io::{self, Read};
+use crate::core::JsonError;
+use crate::driver::{deserialize, deserialize_reader};
+use crate::stream::{JsonEvent, JsonReader};
+
+/// Hands out at most one byte per `read` call to exercise buffer boundaries.
+struct Trickle<'a>(&'a [u8]);
+
+impl Read for Trickle<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self.0.split_first() {
+            Some((&byte, rest)) if !buf.is_empty() => {
+                buf[0] = byte;
+                self.0 = rest;
+                Ok(1)
+            }
+            _ => Ok(0),
+        }
+    }
+}
+
+struct Failing;
+
+impl Read for Failing {
+    fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+        Err(io::Error::new(io::ErrorKind::ConnectionReset, "gone"))
+    }
+}
+
+#[test]
+fn test_reader_matches_str_parser() {
+    let input = include_str!("../../input.json");
+    assert_eq!(deserialize_reader(input.as_bytes()).unwrap(), deserialize(input).unwrap());
+    assert_eq!(deserialize_reader(Trickle(input.as_bytes())).unwrap(), deserialize(input).unwrap());
+}
+
+#[test]
+fn test_multibyte_characters_across_reads() {
+    let input = r#"{"grüße": "日本語 ☺", "e": "é"}"#;
+    let value = deserialize_reader(Trickle(input.as_bytes())).unwrap();
+    assert_eq!(value["grüße"].as_str(), Some("日本語 ☺"));
+    assert_eq!(value["e"].as_str(), Some("é"));
+}
+
+#[test]
+fn test_invalid_utf8() {
+    let cases: [&[u8]; 4] = [b"[\"\xff\"]", b"[\"\xc3\"]", b"[\"\xe2\x28\xa1\"]", b"[\"\xed\xa0\x80\"]"];
+    for bytes in cases {
+        let err = deserialize_reader(bytes).unwrap_err();
+        assert!(matches!(err, JsonError::InvalidUtf8 { line: 1, .. }), "{:?}", bytes);
+        assert_eq!(deserialize_reader(Trickle(bytes)).unwrap_err(), err);
+    }
+}
+
+#[test]
+fn test_io_errors_are_reported() {
+    assert!(matches!(
+        deserialize_reader(Failing),
+        Err(JsonError::Io { kind: io::ErrorKind::ConnectionReset, .. })
+    ));
+}
+
+#[test]
+fn test_event_reader_over_read() {
+    let mut reader = JsonReader::from_reader(Trickle(b"[1, {\"a\": [2, 3]}, 4]"));
+    let mut count = 0;
+    while let Some((event, _)) = reader.next_event().unwrap() {
+        if event == JsonEvent::StartObject {
+            reader.skip_container().unwrap();
+        }
+        count += 1;
+    }
+    assert_eq!(count, 5);
+}
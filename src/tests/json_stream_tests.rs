@@ -0,0 +1,78 @@
+use crate::core::{JsonError, JsonNumber, Position};
+use crate::stream::{JsonEvent, JsonReader, Scalar};
+
+fn events(input: &str) -> Result<Vec<JsonEvent>, JsonError> {
+    JsonReader::new(input).map(|r| r.map(|(event, _)| event)).collect()
+}
+
+#[test]
+fn test_event_sequence() {
+    use JsonEvent::*;
+    assert_eq!(
+        events(r#"{"a": [1, true, null], "b": {}, "c": "x"}"#).unwrap(),
+        vec![
+            StartObject,
+            Key("a".to_string()),
+            StartArray,
+            Value(Scalar::Number(JsonNumber::Integer(1))),
+            Value(Scalar::Bool(true)),
+            Value(Scalar::Null),
+            EndArray,
+            Key("b".to_string()),
+            StartObject,
+            EndObject,
+            Key("c".to_string()),
+            Value(Scalar::String("x".to_string())),
+            EndObject,
+        ]
+    );
+    assert_eq!(events("  42 ").unwrap(), vec![Value(Scalar::Number(JsonNumber::Integer(42)))]);
+    assert_eq!(events("[]").unwrap(), vec![StartArray, EndArray]);
+}
+
+#[test]
+fn test_event_positions() {
+    let mut reader = JsonReader::new("{\n  \"k\": [1,\n    2]\n}");
+    let positions: Vec<Position> = reader.by_ref().map(|r| r.unwrap().1).collect();
+    let lines_cols: Vec<(usize, usize)> = positions.iter().map(|p| (p.line, p.col)).collect();
+    assert_eq!(lines_cols, [(1, 1), (2, 3), (2, 8), (2, 9), (3, 5), (3, 6), (4, 1)]);
+    assert_eq!(positions[1].offset, 4);
+}
+
+#[test]
+fn test_invalid_documents() {
+    for input in ["", "[1,]", "{\"a\"}", "{\"a\":1,}", "[1 2]", "{1:2}", "[}", "1 2", "[1", "{\"a\":"] {
+        assert!(events(input).is_err(), "{:?}", input);
+    }
+    assert!(matches!(events("[1, 2"), Err(JsonError::UnexpectedEof { .. })));
+
+    let mut reader = JsonReader::new("[1,]");
+    assert!(reader.by_ref().any(|r| r.is_err()));
+    assert!(reader.next().is_none());
+}
+
+#[test]
+fn test_skip_value_and_container() {
+    let mut reader = JsonReader::new(include_str!("../../input.json"));
+    let mut keys = Vec::new();
+    assert_eq!(reader.next_event().unwrap().unwrap().0, JsonEvent::StartObject);
+    while let Some((event, _)) = reader.next_event().unwrap() {
+        match event {
+            JsonEvent::Key(key) => {
+                keys.push(key);
+                reader.skip_value().unwrap();
+            }
+            JsonEvent::EndObject => break,
+            other => panic!("unexpected event {:?}", other),
+        }
+    }
+    assert_eq!(keys[..4], ["project", "version", "active", "config"]);
+    assert!(reader.next_event().unwrap().is_none());
+
+    let mut reader = JsonReader::new(r#"[[1, [2, 3]], 4]"#);
+    reader.next_event().unwrap();
+    assert_eq!(reader.next_event().unwrap().unwrap().0, JsonEvent::StartArray);
+    reader.skip_container().unwrap();
+    assert_eq!(reader.depth(), 1);
+    assert_eq!(reader.next_event().unwrap().unwrap().0, JsonEvent::Value(Scalar::Number(JsonNumber::Integer(4))));
+}
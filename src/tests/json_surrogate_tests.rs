@@ -0,0 +1,120 @@
+use crate::core::{JsonError, JsonValue};
+use crate::driver::{deserialize, deserialize_borrowed_with, deserialize_document_with, deserialize_recovering, deserialize_with, serialize};
+use crate::options::{LoneSurrogatePolicy, ParseOptions};
+use crate::parser::StringEscaper;
+use crate::stream::{JsonEvent, JsonReader, Scalar};
+
+fn with_policy(input: &str, policy: LoneSurrogatePolicy) -> Result<JsonValue, JsonError> {
+    deserialize_with(input, &ParseOptions { lone_surrogates: policy, ..ParseOptions::default() })
+}
+
+#[test]
+fn test_surrogate_pairs_decode() {
+    assert_eq!(deserialize(r#""😀""#).unwrap().as_str(), Some("😀"));
+    assert_eq!(deserialize(r#""𝄞""#).unwrap().as_str(), Some("𝄞"));
+    assert_eq!(deserialize(r#""􏿿""#).unwrap().as_str(), Some("\u{10FFFF}"));
+    assert_eq!(deserialize(r#""a😹💍b""#).unwrap().as_str(), Some("a😹💍b"));
+}
+
+#[test]
+fn test_astral_characters_round_trip() {
+    for text in ["😀", "𝄞 clef", "mixed é 😀 \u{10FFFF}", "\u{10000}"] {
+        let escaped = StringEscaper::escape(text);
+        assert!(escaped.contains("\\u"), "{} should be escaped", text);
+        let value = JsonValue::String(text.to_string());
+        let json = serialize(&value);
+        assert_eq!(deserialize(&json).unwrap(), value);
+    }
+}
+
+#[test]
+fn test_lone_surrogates_are_errors_by_default() {
+    for input in [
+        r#""\ud800""#,
+        r#""\udc00""#,
+        r#""\ud800abc""#,
+        r#""\ud800\n""#,
+        r#""\ud800A""#,
+        r#""\udd1e\ud834""#,
+    ] {
+        assert!(
+            matches!(deserialize(input), Err(JsonError::InvalidEscapeSequence { .. })),
+            "{} should be rejected",
+            input
+        );
+    }
+    assert!(deserialize(r#""\ud800\u12""#).is_err());
+}
+
+#[test]
+fn test_replace_policy() {
+    let replace = |input| with_policy(input, LoneSurrogatePolicy::Replace).unwrap();
+    assert_eq!(replace(r#""\ud800""#).as_str(), Some("\u{FFFD}"));
+    assert_eq!(replace(r#""\ud800abc""#).as_str(), Some("\u{FFFD}abc"));
+    assert_eq!(replace(r#""\ud800\n""#).as_str(), Some("\u{FFFD}\n"));
+    assert_eq!(replace(r#""\ud800A""#).as_str(), Some("\u{FFFD}A"));
+    assert_eq!(replace(r#""\ud800😀""#).as_str(), Some("\u{FFFD}😀"));
+    assert_eq!(replace(r#""\udd1e\ud834""#).as_str(), Some("\u{FFFD}\u{FFFD}"));
+}
+
+#[test]
+fn test_lone_surrogates_stay_distinct_from_escaped_text() {
+    // The second string is a backslash followed by text, not a surrogate.
+    let input = r#"["\ud800", "\\ud800"]"#;
+    assert!(with_policy(input, LoneSurrogatePolicy::Error).is_err());
+    let value = with_policy(input, LoneSurrogatePolicy::Replace).unwrap();
+    assert_eq!(value[0].as_str(), Some("\u{FFFD}"));
+    assert_eq!(value[1].as_str(), Some("\\ud800"));
+    let json = serialize(&value);
+    assert_eq!(json, "[\"\u{FFFD}\",\"\\\\ud800\"]");
+    assert_eq!(deserialize(&json).unwrap(), value);
+    assert_eq!(deserialize(r#""\\ud800""#).unwrap(), value[1]);
+}
+
+#[test]
+fn test_preserve_policy_round_trips() {
+    let options = ParseOptions { lone_surrogates: LoneSurrogatePolicy::Preserve, ..ParseOptions::default() };
+    let input = r#"["\uD800x","\udfaa","a\ud800\ud800b😀","\\ud800","\ud83d\ude00"]"#;
+    let value = deserialize_with(input, &options).unwrap();
+    let lone = value[0].as_wtf8().unwrap();
+    assert_eq!(lone.as_bytes(), b"\xED\xA0\x80x");
+    assert_eq!(lone.code_points().collect::<Vec<_>>(), [0xD800, 'x' as u32]);
+    assert_eq!(lone.to_string_lossy(), "\u{FFFD}x");
+    assert_eq!(value[1].as_wtf8().unwrap().code_points().collect::<Vec<_>>(), [0xDFAA]);
+    assert!(value[0].is_string() && value[0].as_str().is_none());
+    // Escaped text and real pairs stay ordinary strings.
+    assert_eq!(value[3].as_str(), Some("\\ud800"));
+    assert_eq!(value[4].as_str(), Some("😀"));
+
+    let json = serialize(&value);
+    assert_eq!(json, r#"["\ud800x","\udfaa","a\ud800\ud800b\ud83d\ude00","\\ud800","\ud83d\ude00"]"#);
+    assert_eq!(deserialize_with(&json, &options).unwrap(), value);
+    assert_ne!(value[0], with_policy(input, LoneSurrogatePolicy::Replace).unwrap()[0]);
+}
+
+#[test]
+fn test_preserve_policy_in_every_reader() {
+    let options = ParseOptions { lone_surrogates: LoneSurrogatePolicy::Preserve, ..ParseOptions::default() };
+    let input = r#"{"a": "\udc00"}"#;
+    let expected = deserialize_with(input, &options).unwrap();
+    assert!(matches!(expected["a"], JsonValue::Wtf8(_)));
+    assert_eq!(deserialize_borrowed_with(input, &options).unwrap().into_owned(), expected);
+    let document = deserialize_document_with(input, &options).unwrap();
+    assert_eq!(document.root().get("a").unwrap().as_wtf8(), expected["a"].as_wtf8());
+    assert_eq!(document.to_value(), expected);
+    let events: Vec<JsonEvent> = JsonReader::with_options(input, &options).map(|e| e.unwrap().0).collect();
+    assert_eq!(events[2], JsonEvent::Value(Scalar::Wtf8(expected["a"].as_wtf8().unwrap().clone())));
+}
+
+#[test]
+fn test_preserve_policy_rejects_member_names() {
+    let options = ParseOptions { lone_surrogates: LoneSurrogatePolicy::Preserve, ..ParseOptions::default() };
+    let input = r#"{"k\ud800": 1}"#;
+    let err = deserialize_with(input, &options).unwrap_err();
+    assert!(matches!(err, JsonError::InvalidEscapeSequence { line: 1, col: 2, .. }), "{:?}", err);
+    assert!(deserialize_document_with(input, &options).is_err());
+    assert!(JsonReader::with_options(input, &options).any(|event| event.is_err()));
+    let (_, errors) = deserialize_recovering(input, &options);
+    assert!(matches!(errors[..], [JsonError::InvalidEscapeSequence { .. }]), "{:?}", errors);
+}
+
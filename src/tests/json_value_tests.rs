@@ -0,0 +1,64 @@
+use crate::core::{JsonNumber, JsonValue};
+use crate::driver::deserialize;
+
+fn sample() -> JsonValue {
+    deserialize(include_str!("../../input.json")).unwrap()
+}
+
+#[test]
+fn test_index_nested() {
+    let value = sample();
+    assert_eq!(value["config"]["network"]["ports"][1].as_i64(), Some(8081));
+    assert_eq!(value["users"][2]["name"].as_str(), Some("Carol"));
+    assert_eq!(value["config"]["security"]["policies"]["policy2"].as_bool(), Some(false));
+}
+
+#[test]
+fn test_index_missing_is_null() {
+    let value = sample();
+    assert!(value["missing"].is_null());
+    assert!(value["users"][99]["name"].is_null());
+    assert!(value["project"][0].is_null());
+}
+
+#[test]
+fn test_get_and_get_mut() {
+    let mut value = deserialize(r#"{"a":1,"b":[true]}"#).unwrap();
+    assert_eq!(value.get("a"), Some(&JsonValue::Number(JsonNumber::Integer(1))));
+    assert_eq!(value.get("z"), None);
+    assert_eq!(value["b"].get_index(0), Some(&JsonValue::Bool(true)));
+
+    *value.get_mut("a").unwrap() = JsonValue::String("x".to_string());
+    assert_eq!(value["a"].as_str(), Some("x"));
+}
+
+#[test]
+fn test_get_duplicate_key_last_wins() {
+    let value = deserialize(r#"{"a":1,"a":2}"#).unwrap();
+    assert_eq!(value["a"].as_i64(), Some(2));
+}
+
+#[test]
+fn test_number_accessors() {
+    let value = deserialize("[-1, 18446744073709551615, 2.5]").unwrap();
+    assert_eq!(value[0].as_i64(), Some(-1));
+    assert_eq!(value[0].as_u64(), None);
+    assert_eq!(value[1].as_u64(), Some(u64::MAX));
+    assert!(value[1].is_u64() && !value[1].is_i64());
+    assert_eq!(value[2].as_f64(), Some(2.5));
+    assert!(value[2].is_f64());
+    assert_eq!(value[0].as_f64(), Some(-1.0));
+}
+
+#[test]
+fn test_take_and_len() {
+    let mut value = sample();
+    assert_eq!(value["users"].len(), 4);
+    assert_eq!(value["config"].len(), 3);
+    assert_eq!(value["project"].len(), 0);
+
+    let users = value.get_mut("users").unwrap().take();
+    assert_eq!(users.as_array().map(Vec::len), Some(4));
+    assert!(value["users"].is_null());
+    assert!(value["users"].is_empty());
+}
@@ -0,0 +1,176 @@
+use std::io::ErrorKind;
+use crate::core::{JsonError, JsonNumber, JsonValue};
+use crate::driver::{deserialize, deserialize_with, serialize_with_options};
+use crate::options::ParseOptions;
+use crate::cli::{parse_flags, run, run_query};
+use crate::io::write_output;
+use crate::writer::{JsonWriter, NonFinitePolicy, PrettyConfig, WriteOptions};
+
+fn written(pretty: bool, build: impl FnOnce(&mut JsonWriter<Vec<u8>>)) -> String {
+    let mut writer = if pretty { JsonWriter::pretty(Vec::new()) } else { JsonWriter::new(Vec::new()) };
+    build(&mut writer);
+    String::from_utf8(writer.into_inner()).unwrap()
+}
+
+#[test]
+fn test_event_api_compact() {
+    let out = written(false, |w| {
+        w.begin_object().unwrap();
+        w.key("name").unwrap();
+        w.string("Al\"ice").unwrap();
+        w.key("ports").unwrap();
+        w.begin_array().unwrap();
+        w.number(&JsonNumber::Integer(8080)).unwrap();
+        w.null().unwrap();
+        w.bool(false).unwrap();
+        w.end_array().unwrap();
+        w.key("empty").unwrap();
+        w.begin_object().unwrap();
+        w.end_object().unwrap();
+        w.end_object().unwrap();
+    });
+    assert_eq!(out, r#"{"name":"Al\"ice","ports":[8080,null,false],"empty":{}}"#);
+}
+
+#[test]
+fn test_event_api_pretty() {
+    let out = written(true, |w| {
+        w.begin_array().unwrap();
+        w.begin_object().unwrap();
+        w.key("a").unwrap();
+        w.begin_array().unwrap();
+        w.end_array().unwrap();
+        w.end_object().unwrap();
+        w.value(&JsonValue::Bool(true)).unwrap();
+        w.end_array().unwrap();
+    });
+    assert_eq!(out, "[\n    {\n        \"a\": []\n    },\n    true\n]");
+}
+
+#[test]
+fn test_matches_string_serialization() {
+    let value = deserialize(include_str!("../../input.json")).unwrap();
+    let mut compact = Vec::new();
+    value.write_json(&mut compact).unwrap();
+    assert_eq!(String::from_utf8(compact).unwrap(), value.to_json_string());
+    let mut pretty = Vec::new();
+    value.write_json_pretty(&mut pretty).unwrap();
+    assert_eq!(String::from_utf8(pretty).unwrap(), value.to_json_string_pretty());
+}
+
+#[test]
+fn test_misuse_is_rejected() {
+    let mut w = JsonWriter::new(Vec::new());
+    w.begin_array().unwrap();
+    assert_eq!(w.key("a").unwrap_err().kind(), ErrorKind::InvalidInput);
+    assert_eq!(w.end_object().unwrap_err().kind(), ErrorKind::InvalidInput);
+    w.end_array().unwrap();
+    assert_eq!(w.null().unwrap_err().kind(), ErrorKind::InvalidInput);
+
+    let mut w = JsonWriter::new(Vec::new());
+    w.begin_object().unwrap();
+    assert_eq!(w.string("no key").unwrap_err().kind(), ErrorKind::InvalidInput);
+    w.key("k").unwrap();
+    assert_eq!(w.end_object().unwrap_err().kind(), ErrorKind::InvalidInput);
+}
+
+#[test]
+fn test_preserve_float_survives_round_trip() {
+    let value = deserialize(r#"{"f":42.0,"i":42,"big":1e300,"list":[1.0,2]}"#).unwrap();
+    let options = WriteOptions { preserve_float: true, ..WriteOptions::default() };
+    let compact = serialize_with_options(&value, None, &options).unwrap();
+    assert_eq!(compact, r#"{"f":42.0,"i":42,"big":1e300,"list":[1.0,2]}"#);
+    assert_eq!(deserialize(&compact).unwrap(), value);
+    assert!(matches!(deserialize(&compact).unwrap()["f"], JsonValue::Number(JsonNumber::Float(_))));
+
+    let config = PrettyConfig { max_inline_width: 40, ..PrettyConfig::default() };
+    let pretty = serialize_with_options(&value, Some(&config), &options).unwrap();
+    assert!(pretty.contains("[1.0, 2]"), "{}", pretty);
+    assert_eq!(deserialize(&pretty).unwrap(), value);
+
+    assert_eq!(serialize_with_options(&value, None, &WriteOptions::default()).unwrap(), r#"{"f":42,"i":42,"big":1e300,"list":[1,2]}"#);
+}
+
+#[test]
+fn test_non_finite_policies() {
+    let value = JsonValue::Array(vec![
+        JsonValue::Number(JsonNumber::Float(f64::NAN)),
+        JsonValue::Number(JsonNumber::Float(f64::INFINITY)),
+        JsonValue::Number(JsonNumber::Float(f64::NEG_INFINITY)),
+    ]);
+    let with = |non_finite| serialize_with_options(&value, None, &WriteOptions { non_finite, ..WriteOptions::default() });
+    assert_eq!(with(NonFinitePolicy::Null).unwrap(), "[null,null,null]");
+    assert_eq!(with(NonFinitePolicy::Literal).unwrap(), "[NaN,Infinity,-Infinity]");
+    assert_eq!(with(NonFinitePolicy::String).unwrap(), r#"["NaN","Infinity","-Infinity"]"#);
+    assert_eq!(with(NonFinitePolicy::Error).unwrap_err(), JsonError::NonFiniteNumber { pointer: "/0".to_string() });
+    assert_eq!(value.to_json_string(), "[null,null,null]");
+}
+
+#[test]
+fn test_non_finite_error_names_the_value() {
+    let mut value = deserialize(r#"{"a": [1, {"b/c": 2}], "d": 3}"#).unwrap();
+    *value.pointer_mut("/a/1/b~1c").unwrap() = JsonValue::Number(JsonNumber::Float(f64::NAN));
+    let options = WriteOptions { non_finite: NonFinitePolicy::Error, ..WriteOptions::default() };
+    let expected = JsonError::NonFiniteNumber { pointer: "/a/1/b~1c".to_string() };
+    assert_eq!(serialize_with_options(&value, None, &options).unwrap_err(), expected);
+
+    // Inlined containers fall back to the full writer so the path is kept.
+    let config = PrettyConfig { max_inline_width: 80, ..PrettyConfig::default() };
+    assert_eq!(serialize_with_options(&value, Some(&config), &options).unwrap_err(), expected);
+
+    let mut out = Vec::new();
+    let err = value.write_json_with_options(&mut out, None, &options).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+    assert_eq!(err.to_string(), "Cannot serialize NaN or infinite number at '/a/1/b~1c'");
+
+    let root = JsonValue::Number(JsonNumber::Float(f64::INFINITY));
+    assert_eq!(serialize_with_options(&root, None, &options).unwrap_err(), JsonError::NonFiniteNumber { pointer: String::new() });
+}
+
+#[test]
+fn test_cli_rejects_non_finite_by_default() {
+    let (options, _) = parse_flags(std::iter::empty()).unwrap();
+    assert_eq!(options.write.non_finite, NonFinitePolicy::Error);
+    let (options, _) = parse_flags(["--non-finite=literal".to_string()].into_iter()).unwrap();
+    assert_eq!(options.write.non_finite, NonFinitePolicy::Literal);
+    assert!(parse_flags(["--non-finite=zero".to_string()].into_iter()).is_err());
+}
+
+#[test]
+fn test_failed_output_leaves_the_file_alone() {
+    let path = std::env::temp_dir().join(format!("basic_json_parser_output_{}.json", std::process::id()));
+    let path = path.to_str().unwrap();
+    std::fs::write(path, "[]").unwrap();
+    let value = deserialize_with("{a: [1, Infinity]}", &ParseOptions::json5()).unwrap();
+    let options = WriteOptions { non_finite: NonFinitePolicy::Error, ..WriteOptions::default() };
+    let err = write_output(path, |output| value.write_json_with_options(output, None, &options)).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+    assert_eq!(std::fs::read_to_string(path).unwrap(), "[]");
+    assert!(!std::path::Path::new(&format!("{}.tmp", path)).exists());
+
+    let options = WriteOptions { non_finite: NonFinitePolicy::Null, ..WriteOptions::default() };
+    write_output(path, |output| value.write_json_with_options(output, None, &options)).unwrap();
+    assert_eq!(std::fs::read_to_string(path).unwrap(), r#"{"a":[1,null]}"#);
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_cli_commands_report_failure() {
+    let flags = |args: &[&str]| parse_flags(args.iter().map(|arg| arg.to_string())).unwrap().0;
+    assert!(run("[1]", &flags(&[])).is_ok());
+    assert!(run("[1,", &flags(&[])).is_err());
+    assert!(run("[1, NaN]", &flags(&["--json5"])).is_err());
+    assert!(run("[1, NaN]", &flags(&["--json5", "--non-finite=null"])).is_ok());
+}
+
+#[test]
+fn test_cli_query_uses_write_options() {
+    let path = std::env::temp_dir().join(format!("basic_json_parser_query_{}.json5", std::process::id()));
+    std::fs::write(&path, "{a: [1, NaN]}").unwrap();
+    let path = path.to_str().unwrap();
+    let flags = |args: &[&str]| parse_flags(args.iter().map(|arg| arg.to_string())).unwrap().0;
+    let err = run_query(path, "$.a[*]", &flags(&["--json5"])).unwrap_err();
+    assert!(matches!(err.downcast_ref::<JsonError>(), Some(JsonError::NonFiniteNumber { .. })), "{}", err);
+    assert!(run_query(path, "$.a[*]", &flags(&["--json5", "--non-finite=literal"])).is_ok());
+    std::fs::remove_file(path).unwrap();
+}
@@ -1,2 +1,22 @@
 mod json_number_tests;
-
+mod json_value_tests;
+mod json_pointer_tests;
+mod json_path_tests;
+mod json_patch_tests;
+mod json_merge_tests;
+mod json_stream_tests;
+mod json_source_tests;
+mod json_writer_tests;
+mod json_pretty_tests;
+mod json5_tests;
+mod json_surrogate_tests;
+mod json_error_tests;
+mod json_recovery_tests;
+mod json_limits_tests;
+mod json_duplicate_key_tests;
+mod json_convert_tests;
+mod json_borrowed_tests;
+mod json_lexer_tests;
+mod json_document_tests;
+#[cfg(feature = "serde")]
+mod json_serde_tests;
@@ -0,0 +1,223 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::mem;
+use std::ops::Index;
+use crate::core::{JsonNumber, JsonValue};
+use crate::number::compare_decimal;
+use crate::wtf8::Wtf8String;
+
+static NULL: JsonValue = JsonValue::Null;
+
+impl JsonValue {
+    /// Looks up `key` in an object. When a key is repeated the last
+    /// occurrence wins, matching `JSON.parse`.
+    pub fn get(&self, key: &str) -> Option<&JsonValue> {
+        match self {
+            JsonValue::Object(pairs) => pairs.iter().rev().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut JsonValue> {
+        match self {
+            JsonValue::Object(pairs) => pairs.iter_mut().rev().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    /// Returns the array element at `index`.
+    pub fn get_index(&self, index: usize) -> Option<&JsonValue> {
+        match self {
+            JsonValue::Array(items) => items.get(index),
+            _ => None,
+        }
+    }
+
+    pub fn get_index_mut(&mut self, index: usize) -> Option<&mut JsonValue> {
+        match self {
+            JsonValue::Array(items) => items.get_mut(index),
+            _ => None,
+        }
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self, JsonValue::Null)
+    }
+
+    pub fn is_bool(&self) -> bool {
+        matches!(self, JsonValue::Bool(_))
+    }
+
+    pub fn is_number(&self) -> bool {
+        matches!(self, JsonValue::Number(_))
+    }
+
+    pub fn is_i64(&self) -> bool {
+        self.as_i64().is_some()
+    }
+
+    pub fn is_u64(&self) -> bool {
+        self.as_u64().is_some()
+    }
+
+    /// See [`JsonNumber::is_f64`].
+    pub fn is_f64(&self) -> bool {
+        self.as_number().is_some_and(JsonNumber::is_f64)
+    }
+
+    /// True for [`JsonValue::Wtf8`] as well, which [`as_str`](Self::as_str) cannot return.
+    pub fn is_string(&self) -> bool {
+        matches!(self, JsonValue::String(_) | JsonValue::Wtf8(_))
+    }
+
+    pub fn is_array(&self) -> bool {
+        matches!(self, JsonValue::Array(_))
+    }
+
+    pub fn is_object(&self) -> bool {
+        matches!(self, JsonValue::Object(_))
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match *self {
+            JsonValue::Bool(b) => Some(b),
+            _ => None,
+        }
+    }
+
+    pub fn as_number(&self) -> Option<&JsonNumber> {
+        match self {
+            JsonValue::Number(n) => Some(n),
+            _ => None,
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        self.as_number().and_then(JsonNumber::as_i64)
+    }
+
+    pub fn as_u64(&self) -> Option<u64> {
+        self.as_number().and_then(JsonNumber::as_u64)
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        self.as_number().map(JsonNumber::as_f64)
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            JsonValue::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_wtf8(&self) -> Option<&Wtf8String> {
+        match self {
+            JsonValue::Wtf8(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&Vec<JsonValue>> {
+        match self {
+            JsonValue::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    pub fn as_array_mut(&mut self) -> Option<&mut Vec<JsonValue>> {
+        match self {
+            JsonValue::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    pub fn as_object(&self) -> Option<&Vec<(String, JsonValue)>> {
+        match self {
+            JsonValue::Object(pairs) => Some(pairs),
+            _ => None,
+        }
+    }
+
+    pub fn as_object_mut(&mut self) -> Option<&mut Vec<(String, JsonValue)>> {
+        match self {
+            JsonValue::Object(pairs) => Some(pairs),
+            _ => None,
+        }
+    }
+
+    /// Moves the value out, leaving `Null` in its place.
+    pub fn take(&mut self) -> JsonValue {
+        mem::replace(self, JsonValue::Null)
+    }
+
+    /// Number of elements in an array or entries in an object; 0 for scalars.
+    pub fn len(&self) -> usize {
+        match self {
+            JsonValue::Array(items) => items.len(),
+            JsonValue::Object(pairs) => pairs.len(),
+            _ => 0,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Missing keys and non-objects index to a shared `Null`.
+impl Index<&str> for JsonValue {
+    type Output = JsonValue;
+
+    fn index(&self, key: &str) -> &JsonValue {
+        self.get(key).unwrap_or(&NULL)
+    }
+}
+
+/// Out-of-range indices and non-arrays index to a shared `Null`.
+impl Index<usize> for JsonValue {
+    type Output = JsonValue;
+
+    fn index(&self, index: usize) -> &JsonValue {
+        self.get_index(index).unwrap_or(&NULL)
+    }
+}
+
+/// Deep equality where numbers compare by value and object member order is
+/// ignored. Objects are compared as [`get`](JsonValue::get) sees them: each
+/// repeated key by its last value.
+pub(crate) fn values_equal(a: &JsonValue, b: &JsonValue) -> bool {
+    match (a, b) {
+        (JsonValue::Number(x), JsonValue::Number(y)) => compare_numbers(x, y) == Some(Ordering::Equal),
+        (JsonValue::Array(x), JsonValue::Array(y)) => {
+            x.len() == y.len() && x.iter().zip(y).all(|(a, b)| values_equal(a, b))
+        }
+        (JsonValue::Object(x), JsonValue::Object(y)) => {
+            distinct_members(x).all(|(k, v)| b.get(k).is_some_and(|w| values_equal(v, w)))
+                && distinct_members(y).all(|(k, _)| a.get(k).is_some())
+        }
+        _ => a == b,
+    }
+}
+
+/// The members of an object that [`get`](JsonValue::get) finds: the last
+/// of each repeated key, in document order.
+pub(crate) fn distinct_members(pairs: &[(String, JsonValue)]) -> impl Iterator<Item = &(String, JsonValue)> {
+    let last: HashMap<&str, usize> = pairs.iter().enumerate().map(|(i, (key, _))| (key.as_str(), i)).collect();
+    pairs.iter().enumerate().filter(move |(i, (key, _))| last[key.as_str()] == *i).map(|(_, pair)| pair)
+}
+
+pub(crate) fn compare_numbers(a: &JsonNumber, b: &JsonNumber) -> Option<Ordering> {
+    fn exact(n: &JsonNumber) -> Option<i128> {
+        match n {
+            JsonNumber::Float(_) => None,
+            n => n.to_i128(),
+        }
+    }
+    let raw = matches!(a, JsonNumber::Raw(_)) || matches!(b, JsonNumber::Raw(_));
+    match (exact(a), exact(b)) {
+        (Some(x), Some(y)) => Some(x.cmp(&y)),
+        _ if raw => compare_decimal(a, b).or_else(|| a.as_f64().partial_cmp(&b.as_f64())),
+        _ => a.as_f64().partial_cmp(&b.as_f64()),
+    }
+}
@@ -0,0 +1,462 @@
+use std::io::{self, ErrorKind, Write};
+use crate::core::{JsonError, JsonNumber, JsonValue};
+use crate::parser::StringEscaper;
+use crate::pointer::JsonPointer;
+use crate::wtf8::Wtf8String;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Container {
+    Object,
+    Array,
+}
+
+/// Line terminator used by pretty output.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum NewlineStyle {
+    #[default]
+    Lf,
+    CrLf,
+}
+
+impl NewlineStyle {
+    fn as_bytes(self) -> &'static [u8] {
+        match self {
+            NewlineStyle::Lf => b"\n",
+            NewlineStyle::CrLf => b"\r\n",
+        }
+    }
+}
+
+/// Layout options for pretty output. The default reproduces
+/// [`JsonValue::to_json_string_pretty`]: four-space indent, `"key": value`
+/// and one element per line.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct PrettyConfig {
+    /// Text repeated once per nesting level, e.g. `"  "` or `"\t"`.
+    pub indent: String,
+    pub newline: NewlineStyle,
+    pub space_after_colon: bool,
+    /// Arrays and objects whose single-line form (`[8080, 8081]`) is at most
+    /// this many characters are kept on one line. 0 disables inlining.
+    /// Only applies to values written with [`JsonWriter::value`].
+    pub max_inline_width: usize,
+    /// End the document with a newline.
+    pub trailing_newline: bool,
+    /// Emit object members in key order. Only applies to values written
+    /// with [`JsonWriter::value`]; keys passed to [`JsonWriter::key`] are
+    /// written as given.
+    pub sort_keys: bool,
+}
+
+impl Default for PrettyConfig {
+    fn default() -> Self {
+        Self {
+            indent: "    ".to_string(),
+            newline: NewlineStyle::Lf,
+            space_after_colon: true,
+            max_inline_width: 0,
+            trailing_newline: false,
+            sort_keys: false,
+        }
+    }
+}
+
+/// What to write for NaN and ±Infinity, which JSON cannot represent.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum NonFinitePolicy {
+    /// Write `null`, losing the value.
+    #[default]
+    Null,
+    /// Fail with [`JsonError::NonFiniteNumber`] naming the offending value.
+    Error,
+    /// Write the JSON5 literals `NaN`, `Infinity` and `-Infinity`.
+    Literal,
+    /// Write `"NaN"`, `"Infinity"` and `"-Infinity"` as strings.
+    String,
+}
+
+/// Output settings that apply to compact and pretty output alike.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct WriteOptions {
+    /// Write whole floats as `42.0` instead of `42`, so they parse back as
+    /// floats rather than integers.
+    pub preserve_float: bool,
+    pub non_finite: NonFinitePolicy,
+}
+
+struct Frame {
+    container: Container,
+    // Elements (or members) started so far.
+    len: usize,
+    after_key: bool,
+    // Most recent key, kept to name the value in errors.
+    key: String,
+}
+
+/// Writes JSON text straight into any `io::Write`, either from a
+/// [`JsonValue`] or from a sequence of `begin_*`/`key`/value/`end_*` calls.
+///
+/// Output is produced in small pieces, so wrap files and sockets in a
+/// `BufWriter`. Calls that would produce invalid JSON (a key inside an
+/// array, a second top-level value, mismatched ends) fail with
+/// `ErrorKind::InvalidInput`. A number rejected by
+/// [`NonFinitePolicy::Error`] fails with `ErrorKind::InvalidData` wrapping
+/// a [`JsonError::NonFiniteNumber`].
+pub struct JsonWriter<W: Write> {
+    writer: W,
+    config: Option<PrettyConfig>,
+    options: WriteOptions,
+    stack: Vec<Frame>,
+    root_written: bool,
+}
+
+impl<W: Write> JsonWriter<W> {
+    /// A writer producing compact output with no whitespace.
+    pub fn new(writer: W) -> Self {
+        Self { writer, config: None, options: WriteOptions::default(), stack: Vec::new(), root_written: false }
+    }
+
+    /// A writer producing indented output, one element per line.
+    pub fn pretty(writer: W) -> Self {
+        Self::with_config(writer, PrettyConfig::default())
+    }
+
+    /// A writer producing pretty output laid out according to `config`.
+    pub fn with_config(writer: W, config: PrettyConfig) -> Self {
+        Self { config: Some(config), ..Self::new(writer) }
+    }
+
+    /// Applies `options` to everything written from now on.
+    pub fn set_options(&mut self, options: WriteOptions) {
+        self.options = options;
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    pub fn begin_object(&mut self) -> io::Result<()> {
+        self.begin(Container::Object, b"{")
+    }
+
+    pub fn end_object(&mut self) -> io::Result<()> {
+        self.end(Container::Object, b"}")
+    }
+
+    pub fn begin_array(&mut self) -> io::Result<()> {
+        self.begin(Container::Array, b"[")
+    }
+
+    pub fn end_array(&mut self) -> io::Result<()> {
+        self.end(Container::Array, b"]")
+    }
+
+    /// Writes an object key; the next call must write its value.
+    pub fn key(&mut self, key: &str) -> io::Result<()> {
+        match self.stack.last() {
+            Some(Frame { container: Container::Object, after_key: false, .. }) => {}
+            _ => return Err(misuse("key written outside of an object")),
+        }
+        self.separate()?;
+        self.write_string(key)?;
+        self.write_colon()?;
+        if let Some(frame) = self.stack.last_mut() {
+            frame.after_key = true;
+            frame.key.clear();
+            frame.key.push_str(key);
+        }
+        Ok(())
+    }
+
+    pub fn null(&mut self) -> io::Result<()> {
+        self.scalar(|w| w.writer.write_all(b"null"))
+    }
+
+    pub fn bool(&mut self, b: bool) -> io::Result<()> {
+        self.scalar(|w| w.writer.write_all(if b { b"true" } else { b"false" }))
+    }
+
+    pub fn number(&mut self, n: &JsonNumber) -> io::Result<()> {
+        self.scalar(|w| w.write_number(n))
+    }
+
+    pub fn string(&mut self, s: &str) -> io::Result<()> {
+        self.scalar(|w| w.write_string(s))
+    }
+
+    /// Writes a string with lone surrogates, escaping each as `\udXXX`.
+    pub fn wtf8(&mut self, s: &Wtf8String) -> io::Result<()> {
+        self.scalar(|w| w.write_wtf8(s))
+    }
+
+    /// Writes a complete value, recursing into arrays and objects.
+    pub fn value(&mut self, value: &JsonValue) -> io::Result<()> {
+        if let Some(config) = &self.config
+            && config.max_inline_width > 0
+            && !value.is_empty()
+            && inline_width(value, config, &self.options, config.max_inline_width).is_some()
+        {
+            return self.scalar(|w| w.write_inline(value));
+        }
+        match value {
+            JsonValue::Null => self.null(),
+            JsonValue::Bool(b) => self.bool(*b),
+            JsonValue::Number(n) => self.number(n),
+            JsonValue::String(s) => self.string(s),
+            JsonValue::Wtf8(s) => self.wtf8(s),
+            JsonValue::Array(items) => {
+                self.begin_array()?;
+                for item in items {
+                    self.value(item)?;
+                }
+                self.end_array()
+            }
+            JsonValue::Object(pairs) => {
+                self.begin_object()?;
+                for (key, item) in self.ordered(pairs) {
+                    self.key(key)?;
+                    self.value(item)?;
+                }
+                self.end_object()
+            }
+        }
+    }
+
+    fn ordered<'v>(&self, pairs: &'v [(String, JsonValue)]) -> Vec<&'v (String, JsonValue)> {
+        let mut ordered: Vec<_> = pairs.iter().collect();
+        if self.config.as_ref().is_some_and(|c| c.sort_keys) {
+            ordered.sort_by(|a, b| a.0.cmp(&b.0));
+        }
+        ordered
+    }
+
+    // Writes a container on a single line: `[1, 2]`, `{"a": 1}`.
+    fn write_inline(&mut self, value: &JsonValue) -> io::Result<()> {
+        match value {
+            JsonValue::Null => self.writer.write_all(b"null"),
+            JsonValue::Bool(b) => self.writer.write_all(if *b { b"true" } else { b"false" }),
+            JsonValue::Number(n) => self.write_number(n),
+            JsonValue::String(s) => self.write_string(s),
+            JsonValue::Wtf8(s) => self.write_wtf8(s),
+            JsonValue::Array(items) => {
+                self.writer.write_all(b"[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        self.writer.write_all(b", ")?;
+                    }
+                    self.write_inline(item)?;
+                }
+                self.writer.write_all(b"]")
+            }
+            JsonValue::Object(pairs) => {
+                self.writer.write_all(b"{")?;
+                for (i, (key, item)) in self.ordered(pairs).into_iter().enumerate() {
+                    if i > 0 {
+                        self.writer.write_all(b", ")?;
+                    }
+                    self.write_string(key)?;
+                    self.write_colon()?;
+                    self.write_inline(item)?;
+                }
+                self.writer.write_all(b"}")
+            }
+        }
+    }
+
+    fn write_number(&mut self, n: &JsonNumber) -> io::Result<()> {
+        if let JsonNumber::Float(f) = n
+            && !f.is_finite()
+        {
+            return match non_finite_text(*f, self.options.non_finite) {
+                Some(text) => self.writer.write_all(text.as_bytes()),
+                None => Err(io::Error::new(ErrorKind::InvalidData, JsonError::NonFiniteNumber {
+                    pointer: self.pointer().to_string(),
+                })),
+            };
+        }
+        if self.options.preserve_float {
+            write!(self.writer, "{:#}", n)
+        } else {
+            write!(self.writer, "{}", n)
+        }
+    }
+
+    // Pointer to the value being written, from the keys and indices on the stack.
+    fn pointer(&self) -> JsonPointer {
+        let mut pointer = JsonPointer::root();
+        for frame in &self.stack {
+            match frame.container {
+                Container::Object => pointer.push(frame.key.as_str()),
+                Container::Array => pointer.push(frame.len.saturating_sub(1).to_string()),
+            }
+        }
+        pointer
+    }
+
+    fn write_colon(&mut self) -> io::Result<()> {
+        let spaced = self.config.as_ref().is_some_and(|c| c.space_after_colon);
+        self.writer.write_all(if spaced { b": " } else { b":" })
+    }
+
+    fn scalar(&mut self, write: impl FnOnce(&mut Self) -> io::Result<()>) -> io::Result<()> {
+        self.before_value()?;
+        write(self)?;
+        self.after_value()
+    }
+
+    fn begin(&mut self, container: Container, open: &[u8]) -> io::Result<()> {
+        self.before_value()?;
+        self.writer.write_all(open)?;
+        self.stack.push(Frame { container, len: 0, after_key: false, key: String::new() });
+        Ok(())
+    }
+
+    fn end(&mut self, container: Container, close: &[u8]) -> io::Result<()> {
+        let has_items = match self.stack.last() {
+            Some(frame) if frame.container == container && !frame.after_key => frame.len > 0,
+            _ => return Err(misuse("end does not match an open container")),
+        };
+        self.stack.pop();
+        if self.config.is_some() && has_items {
+            self.newline()?;
+        }
+        self.writer.write_all(close)?;
+        self.after_value()
+    }
+
+    fn before_value(&mut self) -> io::Result<()> {
+        match self.stack.last_mut() {
+            None if self.root_written => Err(misuse("only one top-level value can be written")),
+            None => Ok(()),
+            Some(Frame { container: Container::Object, after_key, .. }) => {
+                if *after_key {
+                    *after_key = false;
+                    Ok(())
+                } else {
+                    Err(misuse("object value written without a key"))
+                }
+            }
+            Some(Frame { container: Container::Array, .. }) => self.separate(),
+        }
+    }
+
+    // Marks the document complete once the top-level value has been written.
+    fn after_value(&mut self) -> io::Result<()> {
+        if !self.stack.is_empty() {
+            return Ok(());
+        }
+        self.root_written = true;
+        match &self.config {
+            Some(config) if config.trailing_newline => self.writer.write_all(config.newline.as_bytes()),
+            _ => Ok(()),
+        }
+    }
+
+    // Emits the comma and, when pretty, the line break before a new element.
+    fn separate(&mut self) -> io::Result<()> {
+        let Some(frame) = self.stack.last_mut() else {
+            return Ok(());
+        };
+        let first = frame.len == 0;
+        frame.len += 1;
+        if !first {
+            self.writer.write_all(b",")?;
+        }
+        if self.config.is_some() {
+            self.newline()?;
+        }
+        Ok(())
+    }
+
+    fn newline(&mut self) -> io::Result<()> {
+        let Some(config) = &self.config else {
+            return Ok(());
+        };
+        self.writer.write_all(config.newline.as_bytes())?;
+        for _ in 0..self.stack.len() {
+            self.writer.write_all(config.indent.as_bytes())?;
+        }
+        Ok(())
+    }
+
+    fn write_string(&mut self, s: &str) -> io::Result<()> {
+        self.writer.write_all(b"\"")?;
+        if StringEscaper::needs_escaping(s) {
+            self.writer.write_all(StringEscaper::escape(s).as_bytes())?;
+        } else {
+            self.writer.write_all(s.as_bytes())?;
+        }
+        self.writer.write_all(b"\"")
+    }
+
+    fn write_wtf8(&mut self, s: &Wtf8String) -> io::Result<()> {
+        self.writer.write_all(b"\"")?;
+        self.writer.write_all(StringEscaper::escape_wtf8(s).as_bytes())?;
+        self.writer.write_all(b"\"")
+    }
+}
+
+// Replacement text for NaN or ±Infinity, or `None` when the policy rejects it.
+fn non_finite_text(f: f64, policy: NonFinitePolicy) -> Option<&'static str> {
+    let (literal, string) = if f.is_nan() {
+        ("NaN", "\"NaN\"")
+    } else if f > 0.0 {
+        ("Infinity", "\"Infinity\"")
+    } else {
+        ("-Infinity", "\"-Infinity\"")
+    };
+    match policy {
+        NonFinitePolicy::Null => Some("null"),
+        NonFinitePolicy::Error => None,
+        NonFinitePolicy::Literal => Some(literal),
+        NonFinitePolicy::String => Some(string),
+    }
+}
+
+fn misuse(message: &'static str) -> io::Error {
+    io::Error::new(ErrorKind::InvalidInput, message)
+}
+
+// Length of the single-line form of `value`, or `None` once it exceeds `limit`.
+fn inline_width(value: &JsonValue, config: &PrettyConfig, options: &WriteOptions, limit: usize) -> Option<usize> {
+    let width = match value {
+        JsonValue::Null => 4,
+        JsonValue::Bool(true) => 4,
+        JsonValue::Bool(false) => 5,
+        // A rejected number is never inlined, so the error can name its path.
+        JsonValue::Number(JsonNumber::Float(f)) if !f.is_finite() => non_finite_text(*f, options.non_finite)?.len(),
+        JsonValue::Number(n) if options.preserve_float => format!("{:#}", n).len(),
+        JsonValue::Number(n) => n.to_string().len(),
+        JsonValue::String(s) => string_width(s),
+        JsonValue::Wtf8(s) => StringEscaper::escape_wtf8(s).chars().count() + 2,
+        JsonValue::Array(items) => {
+            let mut width = 2 + items.len().saturating_sub(1) * 2;
+            for item in items {
+                width += inline_width(item, config, options, limit.checked_sub(width)?)?;
+            }
+            width
+        }
+        JsonValue::Object(pairs) => {
+            let colon = if config.space_after_colon { 2 } else { 1 };
+            let mut width = 2 + pairs.len().saturating_sub(1) * 2;
+            for (key, item) in pairs {
+                width += string_width(key) + colon;
+                width += inline_width(item, config, options, limit.checked_sub(width)?)?;
+            }
+            width
+        }
+    };
+    (width <= limit).then_some(width)
+}
+
+fn string_width(s: &str) -> usize {
+    if StringEscaper::needs_escaping(s) {
+        StringEscaper::escape(s).chars().count() + 2
+    } else {
+        s.chars().count() + 2
+    }
+}
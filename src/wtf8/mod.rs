@@ -0,0 +1,92 @@
+use std::fmt;
+
+/// A string holding at least one lone UTF-16 surrogate, read with
+/// [`LoneSurrogatePolicy::Preserve`](crate::LoneSurrogatePolicy::Preserve).
+///
+/// The text is kept as WTF-8: UTF-8 that may also encode U+D800..U+DFFF on
+/// their own, in three bytes each. Strings without a lone surrogate stay
+/// [`String`]s, so the two never hold the same text.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Wtf8String(Vec<u8>);
+
+/// A stretch of a [`Wtf8String`]: valid text, or one lone surrogate.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub(crate) enum Wtf8Piece<'a> {
+    Text(&'a str),
+    Surrogate(u16),
+}
+
+impl Wtf8String {
+    /// `text` with each surrogate inserted at its byte offset, in order.
+    pub(crate) fn splice(text: &str, surrogates: &[(usize, u16)]) -> Self {
+        let mut bytes = Vec::with_capacity(text.len() + 3 * surrogates.len());
+        let mut copied = 0;
+        for &(at, unit) in surrogates {
+            bytes.extend_from_slice(&text.as_bytes()[copied..at]);
+            bytes.extend_from_slice(&[0xED, 0xA0 | ((unit >> 6) & 0x1F) as u8, 0x80 | (unit & 0x3F) as u8]);
+            copied = at;
+        }
+        bytes.extend_from_slice(&text.as_bytes()[copied..]);
+        Self(bytes)
+    }
+
+    /// The WTF-8 encoding.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.0
+    }
+
+    /// The Unicode code points in order, surrogates included.
+    pub fn code_points(&self) -> impl Iterator<Item = u32> + '_ {
+        self.pieces().flat_map(|piece| {
+            let (text, unit) = match piece {
+                Wtf8Piece::Text(text) => (text, None),
+                Wtf8Piece::Surrogate(unit) => ("", Some(u32::from(unit))),
+            };
+            text.chars().map(u32::from).chain(unit)
+        })
+    }
+
+    /// The text with each lone surrogate replaced by U+FFFD.
+    pub fn to_string_lossy(&self) -> String {
+        let mut text = String::with_capacity(self.0.len());
+        for piece in self.pieces() {
+            match piece {
+                Wtf8Piece::Text(s) => text.push_str(s),
+                Wtf8Piece::Surrogate(_) => text.push(char::REPLACEMENT_CHARACTER),
+            }
+        }
+        text
+    }
+
+    /// Splits the string at its surrogates.
+    pub(crate) fn pieces(&self) -> impl Iterator<Item = Wtf8Piece<'_>> {
+        let mut rest = &self.0[..];
+        std::iter::from_fn(move || {
+            // In UTF-8 0xED is only followed by 0x80..=0x9F; 0xA0 and up
+            // start the encoding of a surrogate.
+            let at = rest.windows(2).position(|pair| pair[0] == 0xED && pair[1] >= 0xA0).unwrap_or(rest.len());
+            let piece = if at > 0 {
+                let text = std::str::from_utf8(&rest[..at]).expect("text between surrogates is UTF-8");
+                rest = &rest[at..];
+                Wtf8Piece::Text(text)
+            } else if let [_, high, low, tail @ ..] = rest {
+                rest = tail;
+                Wtf8Piece::Surrogate(0xD800 | (u16::from(*high & 0x1F) << 6) | u16::from(*low & 0x3F))
+            } else {
+                return None;
+            };
+            Some(piece)
+        })
+    }
+}
+
+// Lossy, like `to_string_lossy`.
+impl fmt::Display for Wtf8String {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.to_string_lossy())
+    }
+}
@@ -0,0 +1,146 @@
+//! Runs the parsing cases from JSONTestSuite (https://github.com/nst/JSONTestSuite)
+//! vendored under `tests/JSONTestSuite/test_parsing`:
+//!
+//! - `y_*` must be accepted,
+//! - `n_*` must be rejected,
+//! - `i_*` leave the outcome to the implementation; our decision for each is
+//!   listed in [`IMPLEMENTATION_DEFINED`].
+//!
+//! `n_structure_100000_opening_arrays.json` and `n_structure_open_array_object.json`
+//! are rejected by the default nesting limit
+//! ([`Limits::DEFAULT_MAX_DEPTH`](basic_json_parser::Limits::DEFAULT_MAX_DEPTH))
+//! long before they could overflow the stack.
+
+use std::fs;
+use std::path::Path;
+use basic_json_parser::{deserialize_reader_with, deserialize_with, from_reader, from_str, serialize, LoneSurrogatePolicy, ParseOptions};
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Outcome {
+    Accept,
+    Reject,
+}
+
+use Outcome::{Accept, Reject};
+
+const LONE_SURROGATE: &str = "lone surrogate escape; accepted under LoneSurrogatePolicy::Replace and Preserve";
+
+/// Decision and rationale for every `i_` case.
+const IMPLEMENTATION_DEFINED: &[(&str, Outcome, &str)] = &[
+    ("i_number_double_huge_neg_exp.json", Accept, "underflows to 0.0"),
+    ("i_number_huge_exp.json", Reject, "exponent overflows f64"),
+    ("i_number_neg_int_huge_exp.json", Reject, "exponent overflows f64"),
+    ("i_number_pos_double_huge_exp.json", Reject, "exponent overflows f64"),
+    ("i_number_real_neg_overflow.json", Reject, "exponent overflows f64"),
+    ("i_number_real_pos_overflow.json", Reject, "exponent overflows f64"),
+    ("i_number_real_underflow.json", Accept, "underflows to 0.0"),
+    ("i_number_too_big_neg_int.json", Accept, "below i64::MIN, read as a lossy f64"),
+    ("i_number_too_big_pos_int.json", Accept, "above u64::MAX, read as a lossy f64"),
+    ("i_number_very_big_negative_int.json", Accept, "below i64::MIN, read as a lossy f64"),
+    ("i_object_key_lone_2nd_surrogate.json", Reject, LONE_SURROGATE),
+    ("i_string_1st_surrogate_but_2nd_missing.json", Reject, LONE_SURROGATE),
+    ("i_string_1st_valid_surrogate_2nd_invalid.json", Reject, LONE_SURROGATE),
+    ("i_string_UTF-16LE_with_BOM.json", Reject, "input must be UTF-8"),
+    ("i_string_UTF-8_invalid_sequence.json", Reject, "invalid UTF-8"),
+    ("i_string_UTF8_surrogate_U+D800.json", Reject, "UTF-8 encoded surrogates are invalid UTF-8"),
+    ("i_string_incomplete_surrogate_and_escape_valid.json", Reject, LONE_SURROGATE),
+    ("i_string_incomplete_surrogate_pair.json", Reject, LONE_SURROGATE),
+    ("i_string_incomplete_surrogates_escape_valid.json", Reject, LONE_SURROGATE),
+    ("i_string_invalid_lonely_surrogate.json", Reject, LONE_SURROGATE),
+    ("i_string_invalid_surrogate.json", Reject, LONE_SURROGATE),
+    ("i_string_invalid_utf-8.json", Reject, "invalid UTF-8"),
+    ("i_string_inverted_surrogates_U+1D11E.json", Reject, LONE_SURROGATE),
+    ("i_string_iso_latin_1.json", Reject, "input must be UTF-8"),
+    ("i_string_lone_second_surrogate.json", Reject, LONE_SURROGATE),
+    ("i_string_lone_utf8_continuation_byte.json", Reject, "invalid UTF-8"),
+    ("i_string_not_in_unicode_range.json", Reject, "invalid UTF-8"),
+    ("i_string_overlong_sequence_2_bytes.json", Reject, "invalid UTF-8"),
+    ("i_string_overlong_sequence_6_bytes.json", Reject, "invalid UTF-8"),
+    ("i_string_overlong_sequence_6_bytes_null.json", Reject, "invalid UTF-8"),
+    ("i_string_truncated-utf-8.json", Reject, "invalid UTF-8"),
+    ("i_string_utf16BE_no_BOM.json", Reject, "input must be UTF-8"),
+    ("i_string_utf16LE_no_BOM.json", Reject, "input must be UTF-8"),
+    ("i_structure_500_nested_arrays.json", Accept, "within the default nesting limit of 512"),
+    ("i_structure_UTF-8_BOM_empty_object.json", Reject, "a BOM is not whitespace in strict mode"),
+];
+
+fn cases() -> Vec<(String, Vec<u8>)> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/JSONTestSuite/test_parsing");
+    let mut cases: Vec<_> = fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| {
+            let path = entry.unwrap().path();
+            let name = path.file_name().unwrap().to_string_lossy().into_owned();
+            (name, fs::read(&path).unwrap())
+        })
+        .collect();
+    cases.sort();
+    cases
+}
+
+// Parses through the reader and, when the bytes are valid UTF-8, through
+// `from_str` as well; both front ends must agree.
+fn outcome(name: &str, bytes: &[u8]) -> Outcome {
+    let from_bytes = from_reader(bytes).is_ok();
+    if let Ok(text) = std::str::from_utf8(bytes) {
+        assert_eq!(from_str(text).is_ok(), from_bytes, "{}: from_str and from_reader disagree", name);
+    }
+    if from_bytes { Accept } else { Reject }
+}
+
+fn expected(name: &str) -> Outcome {
+    if name.starts_with("y_") {
+        return Accept;
+    }
+    if name.starts_with("n_") {
+        return Reject;
+    }
+    match IMPLEMENTATION_DEFINED.iter().find(|(case, ..)| *case == name) {
+        Some((_, outcome, _)) => *outcome,
+        None => panic!("{} has no documented decision", name),
+    }
+}
+
+#[test]
+fn test_json_test_suite() {
+    let cases = cases();
+    assert!(cases.len() > 300, "corpus not found");
+    let failures: Vec<String> = cases
+        .iter()
+        .filter(|(name, bytes)| outcome(name, bytes) != expected(name))
+        .map(|(name, _)| format!("{} (expected {:?})", name, expected(name)))
+        .collect();
+    assert!(failures.is_empty(), "unexpected results:\n{}", failures.join("\n"));
+}
+
+#[test]
+fn test_every_decision_has_a_case() {
+    let names: Vec<String> = cases().into_iter().map(|(name, _)| name).collect();
+    for (name, ..) in IMPLEMENTATION_DEFINED {
+        assert!(names.iter().any(|n| n == name), "{} is not in the corpus", name);
+    }
+}
+
+#[test]
+fn test_lone_surrogate_cases_accepted_when_replaced() {
+    let options = ParseOptions { lone_surrogates: LoneSurrogatePolicy::Replace, ..ParseOptions::default() };
+    for (name, bytes) in cases() {
+        if IMPLEMENTATION_DEFINED.iter().any(|(case, _, reason)| *case == name && *reason == LONE_SURROGATE) {
+            assert!(deserialize_reader_with(&bytes[..], &options).is_ok(), "{} should be accepted", name);
+        }
+    }
+}
+
+#[test]
+fn test_lone_surrogate_cases_round_trip_when_preserved() {
+    let options = ParseOptions { lone_surrogates: LoneSurrogatePolicy::Preserve, ..ParseOptions::default() };
+    for (name, bytes) in cases() {
+        if IMPLEMENTATION_DEFINED.iter().any(|(case, _, reason)| *case == name && *reason == LONE_SURROGATE) {
+            match deserialize_reader_with(&bytes[..], &options) {
+                Ok(value) => assert_eq!(deserialize_with(&serialize(&value), &options).unwrap(), value, "{}", name),
+                // Member names cannot keep a surrogate.
+                Err(_) => assert!(name.starts_with("i_object_key"), "{} should be accepted", name),
+            }
+        }
+    }
+}
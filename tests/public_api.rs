@@ -0,0 +1,92 @@
+use basic_json_parser::{from_str, to_string, to_string_pretty, Error, ErrorContext, Number, Value};
+
+#[test]
+fn test_from_str_round_trip() {
+    let input = r#"{"name":"Alice","id":1,"roles":["admin","developer"],"active":true,"manager":null}"#;
+    let value = from_str(input).unwrap();
+    assert_eq!(to_string(&value), input);
+    assert_eq!(from_str(&to_string_pretty(&value)).unwrap(), value);
+}
+
+#[test]
+fn test_values_are_constructible() {
+    let value = Value::Array(vec![
+        Value::Number(Number::Integer(-1)),
+        Value::Number(Number::Float(2.5)),
+        Value::String("x".to_string()),
+    ]);
+    assert_eq!(to_string(&value), r#"[-1,2.5,"x"]"#);
+}
+
+#[test]
+fn test_number_accessors() {
+    let int = Number::Integer(42);
+    assert_eq!(int.as_i64(), Some(42));
+    assert_eq!(int.as_u64(), Some(42));
+    assert_eq!(int.as_f64(), 42.0);
+    assert!(int.is_i64() && !int.is_f64());
+
+    let negative = Number::Integer(-7);
+    assert_eq!(negative.as_u64(), None);
+
+    let big = Number::UnsignedInteger(u64::MAX);
+    assert_eq!(big.as_i64(), None);
+    assert_eq!(big.as_u64(), Some(u64::MAX));
+
+    let float = Number::Float(0.5);
+    assert_eq!(float.as_i64(), None);
+    assert!(float.is_f64());
+}
+
+#[test]
+fn test_errors_are_matchable() {
+    match from_str("[1, 2") {
+        Err(Error::InvalidToken { line, .. }) | Err(Error::UnexpectedEof { line, .. }) => {
+            assert_eq!(line, 1)
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    let err = from_str("{\n  \"a\": tru\n}").unwrap_err();
+    assert!(matches!(err, Error::InvalidToken { .. }));
+    assert_eq!(err.line(), 2);
+}
+
+#[test]
+fn test_driver_functions_are_exported() {
+    let value = basic_json_parser::deserialize("[1,2,3]").unwrap();
+    assert_eq!(basic_json_parser::serialize(&value), "[1,2,3]");
+    assert_eq!(basic_json_parser::serialize_pretty(&value), "[\n    1,\n    2,\n    3\n]");
+}
+
+#[test]
+fn test_from_reader_file() {
+    let file = std::fs::File::open(concat!(env!("CARGO_MANIFEST_DIR"), "/input.json")).unwrap();
+    let value = basic_json_parser::from_reader(file).unwrap();
+    assert_eq!(value["users"][0]["name"].as_str(), Some("Alice"));
+}
+
+#[test]
+fn test_to_writer() {
+    let value = from_str(r#"{"a":[1,2]}"#).unwrap();
+    let mut out = Vec::new();
+    basic_json_parser::to_writer(&mut out, &value).unwrap();
+    assert_eq!(out, br#"{"a":[1,2]}"#);
+
+    let mut writer = basic_json_parser::JsonWriter::new(Vec::new());
+    writer.begin_array().unwrap();
+    writer.value(&value).unwrap();
+    writer.end_array().unwrap();
+    assert_eq!(writer.into_inner(), br#"[{"a":[1,2]}]"#);
+}
+
+#[test]
+fn test_errors_carry_context() {
+    let source = "{\"a\": [1 2]}";
+    let err = from_str(source).unwrap_err();
+    let context: &ErrorContext = err.context().unwrap();
+    assert_eq!((context.offset, context.found.as_deref()), (9, Some("`2`")));
+    assert_eq!(context.expected, ["`,`", "`]`"]);
+    assert_eq!(context.path, "$['a']");
+    assert!(err.render(source, "input.json").contains("1 | {\"a\": [1 2]}\n  |          ^\n"));
+}